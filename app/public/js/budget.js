@@ -44,23 +44,27 @@ function changeMonth(delta) {
     const [year, month] = currentMonth.split('-').map(Number);
     let d = new Date(year, month - 1 + delta, 1);
     const newMonth = d.getFullYear() + '-' + String(d.getMonth() + 1).padStart(2, '0');
-    window.location.href = '/budget/' + newMonth;
+    window.location.href = BASE_PATH + '/budget/' + newMonth;
 }
 
+// Date and amount orders come from the server so they survive a reload; the rest sort in place.
+const SERVER_SORTS = ['date_desc', 'date_asc', 'amount_desc', 'amount_asc'];
+
 function sortTransactions() {
     const sortBy = document.getElementById('sort-select').value;
+    if (SERVER_SORTS.includes(sortBy)) {
+        const params = new URLSearchParams(window.location.search);
+        params.set('sort', sortBy);
+        window.location.search = params.toString();
+        return;
+    }
+
     const tbody = document.getElementById('transaction-table-body');
     if (!tbody) return;
     const rows = Array.from(tbody.querySelectorAll('tr'));
 
     rows.sort((a, b) => {
         switch (sortBy) {
-            case 'date-desc':
-                return b.dataset.date.localeCompare(a.dataset.date);
-            case 'date-asc':
-                return a.dataset.date.localeCompare(b.dataset.date);
-            case 'amount-desc':
-                return parseFloat(b.dataset.amountDollars) - parseFloat(a.dataset.amountDollars);
             case 'category':
                 const catA = a.querySelector('.category-col .badge')?.innerText.toLowerCase() || "";
                 const catB = b.querySelector('.category-col .badge')?.innerText.toLowerCase() || "";
@@ -77,8 +81,29 @@ function sortTransactions() {
     rows.forEach(row => tbody.appendChild(row));
 }
 
+// --- Live Updates ---
+
+// Our own edits also come back as events; ignore those that arrive shortly after one
+let lastLocalChange = 0;
+
+function noteLocalChange() {
+    lastLocalChange = Date.now();
+}
+
+function subscribeToMonthEvents() {
+    if (typeof currentMonth === 'undefined' || typeof EventSource === 'undefined') return;
+
+    const source = new EventSource(`${BASE_PATH}/budget/${currentMonth}/events`);
+    const showBanner = () => {
+        if (Date.now() - lastLocalChange < 3000) return;
+        document.getElementById('live-update-banner')?.classList.remove('d-none');
+    };
+    ['created', 'updated', 'deleted', 'resync'].forEach(name => source.addEventListener(name, showBanner));
+}
+
 // Global initialization logic that depends on other files
 document.addEventListener('DOMContentLoaded', () => {
     if (typeof initCharts === 'function') initCharts();
     restoreFilters();
+    subscribeToMonthEvents();
 });
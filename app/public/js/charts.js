@@ -89,7 +89,7 @@ function initCharts() {
     // Transaction Income Pie
     const incomeTransCtx = document.getElementById('incomeTransPieChart');
     if (incomeTransCtx) {
-        const incomeTrans = allTransactions.filter(t => t.is_income);
+        const incomeTrans = allTransactions.filter(t => t.is_income && !t.excluded);
         incomeTransPieChart = new Chart(incomeTransCtx, {
             type: 'pie',
             data: {
@@ -114,7 +114,7 @@ function initCharts() {
     // Transaction Expense Pie
     const expenseTransCtx = document.getElementById('expenseTransPieChart');
     if (expenseTransCtx) {
-        const expenseTrans = allTransactions.filter(t => !t.is_income);
+        const expenseTrans = allTransactions.filter(t => !t.is_income && !t.excluded);
         expenseTransPieChart = new Chart(expenseTransCtx, {
             type: 'pie',
             data: {
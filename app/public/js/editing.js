@@ -28,7 +28,8 @@ function editCategory(id) {
     const actionsCell = row.children[5];
 
     const currentName = nameCell.querySelector('.badge').innerText.trim();
-    const currentLimit = limitCell.querySelector('.limit-text').innerText.trim();
+    // The displayed limit is formatted ("$1,200.00"); the hidden input holds the plain number
+    const currentLimit = limitCell.querySelector('.limit-input').value;
     const currentColor = row.dataset.color;
     const currentIsIncome = row.dataset.isIncome === 'true';
 
@@ -70,13 +71,13 @@ async function saveCategory(id) {
         return;
     }
 
-    const limitResponse = await fetch('/categories/limit', {
+    const limitResponse = await fetch(BASE_PATH + '/categories/limit', {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify({ category_id: id, month: currentMonth, limit: parseFloat(limitInput.value) })
     });
     
-    const renameResponse = await fetch(`/categories/${id}`, {
+    const renameResponse = await fetch(`${BASE_PATH}/categories/${id}`, {
         method: 'PUT',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify({ 
@@ -97,7 +98,7 @@ async function saveCategory(id) {
 
 async function deleteCategory(id) {
     if(!confirm("Are you sure? This will affect all months and fail if there are transactions.")) return;
-    const response = await fetch(`/categories/${id}`, { method: 'DELETE' });
+    const response = await fetch(`${BASE_PATH}/categories/${id}`, { method: 'DELETE' });
     if (response.ok) location.reload();
     else alert("Error deleting category. It may have transactions.");
 }
@@ -111,13 +112,24 @@ function editLimit(catId) {
 
 async function saveLimit(catId) {
     const row = document.querySelector(`tr[data-category-id="${catId}"]`);
-    const val = row.querySelector('.limit-input').value;
-    
-    const response = await fetch('/categories/limit', {
-        method: 'POST',
-        headers: { 'Content-Type': 'application/json' },
-        body: JSON.stringify({ category_id: catId, month: currentMonth, limit: parseFloat(val) })
-    });
+    const input = row.querySelector('.limit-input');
+    const val = input.value;
+
+    let response;
+    if (val.trim() === '') {
+        // Clearing the field removes the month's limit
+        if (input.dataset.hasBudget !== 'true') {
+            location.reload();
+            return;
+        }
+        response = await fetch(`${BASE_PATH}/categories/${catId}/limit?month=${currentMonth}`, { method: 'DELETE' });
+    } else {
+        response = await fetch(BASE_PATH + '/categories/limit', {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ category_id: catId, month: currentMonth, limit: parseFloat(val) })
+        });
+    }
 
     if (response.ok) {
         location.reload();
@@ -126,47 +138,21 @@ async function saveLimit(catId) {
 
 // --- Transaction Editing ---
 
-function editRow(id) {
+async function editRow(id) {
     const row = document.getElementById(`row-${id}`);
     const originalHtml = row.innerHTML;
     cancelPreviousEdit();
+
+    // The server renders the edit cells from the stored transaction rather than the row's data attributes
+    const response = await fetch(`${BASE_PATH}/budget/transaction/${id}`, { headers: { 'Accept': 'text/html' } });
+    if (!response.ok) {
+        alert('Error loading transaction');
+        return;
+    }
+
     currentEditingRow = { element: row, originalHtml: originalHtml };
     row.classList.add('editing-row');
-    
-    const date = row.dataset.date;
-    const catId = row.dataset.category;
-    const cardId = row.dataset.card;
-    const notes = row.dataset.notes;
-    const amount = row.dataset.amountDollars;
-
-    const currentCat = categories.find(c => c.id == catId) || { id: 0, name: 'Unknown' };
-    const currentCard = cards.find(c => c.id == cardId) || { id: 0, name: 'Cash' };
-
-    row.innerHTML = `
-        <td><input type="date" id="edit-date-${id}" class="form-control form-control-sm" value="${date}"></td>
-        <td class="card-col">
-            <div class="autocomplete-wrapper">
-                <input type="text" id="edit-card-input-${id}" class="form-control form-control-sm" value="${currentCard.name}" placeholder="Card">
-                <input type="hidden" id="edit-card-id-${id}" value="${cardId}">
-            </div>
-        </td>
-        <td class="category-col">
-            <div class="autocomplete-wrapper">
-                <input type="text" id="edit-category-input-${id}" class="form-control form-control-sm" value="${currentCat.name}" placeholder="Cat">
-                <input type="hidden" id="edit-category-id-${id}" value="${catId}">
-            </div>
-        </td>
-        <td class="amount-col">
-            <input type="number" step="0.01" id="edit-amount-${id}" class="form-control form-control-sm" value="${amount}">
-        </td>
-        <td class="notes-col"><input type="text" id="edit-notes-${id}" class="form-control form-control-sm" value="${notes}" placeholder="Notes"></td>
-        <td style="white-space: nowrap;">
-            <div class="d-flex gap-1">
-                <button class="btn btn-sm btn-success" onclick="saveRow(${id})">Save</button>
-                <button class="btn btn-sm btn-secondary" onclick="cancelPreviousEdit()">Cancel</button>
-            </div>
-        </td>
-    `;
+    row.innerHTML = await response.text();
 
     new Autocomplete(document.getElementById(`edit-category-input-${id}`), categories.filter(c => c.is_active), (item) => {
         document.getElementById(`edit-category-id-${id}`).value = item ? item.id : "";
@@ -182,21 +168,24 @@ async function saveRow(id) {
     const cardIdInput = document.getElementById(`edit-card-id-${id}`);
     const amountInput = document.getElementById(`edit-amount-${id}`);
     const notesInput = document.getElementById(`edit-notes-${id}`);
+    const excludedInput = document.getElementById(`edit-excluded-${id}`);
 
     if (isNaN(parseFloat(amountInput.value))) {
         amountInput.classList.add('is-invalid');
         return;
     }
 
-    const response = await fetch(`/budget/transaction/${id}`, {
+    noteLocalChange();
+    const response = await fetch(`${BASE_PATH}/budget/transaction/${id}`, {
         method: 'PUT',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify({
             category_id: parseInt(catIdInput.value),
-            card_id: cardIdInput.value == "0" ? null : parseInt(cardIdInput.value),
+            card_id: cardIdInput.value == "0" ? "" : cardIdInput.value,
             transaction_date: dateInput.value,
             amount_dollars: parseFloat(amountInput.value),
-            notes: notesInput.value
+            notes: notesInput.value,
+            excluded: excludedInput.checked
         })
     });
 
@@ -205,6 +194,22 @@ async function saveRow(id) {
     }
 }
 
+// Ticked off against the bank statement; the checkbox reverts if the server refuses
+async function setCleared(id, checkbox) {
+    noteLocalChange();
+    const response = await fetch(`${BASE_PATH}/budget/transaction/${id}/cleared`, {
+        method: 'PATCH',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ cleared: checkbox.checked })
+    });
+
+    if (response.ok) {
+        document.getElementById(`row-${id}`).dataset.cleared = checkbox.checked;
+    } else {
+        checkbox.checked = !checkbox.checked;
+    }
+}
+
 // --- Card Management ---
 
 async function toggleCard(id) {
@@ -212,7 +217,7 @@ async function toggleCard(id) {
     if (!card) return;
 
     const newState = !card.is_active;
-    const response = await fetch(`/cards/${id}`, {
+    const response = await fetch(`${BASE_PATH}/cards/${id}`, {
         method: 'PUT',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify({ name: card.name, is_active: newState })
@@ -228,7 +233,7 @@ async function addCard() {
     const name = nameInput.value;
     if (!name) return;
 
-    const response = await fetch('/cards', {
+    const response = await fetch(BASE_PATH + '/cards', {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify({ name: name })
@@ -253,7 +258,8 @@ document.addEventListener('DOMContentLoaded', () => {
     if (confirmBtn) {
         confirmBtn.addEventListener('click', async () => {
             if (transactionToDelete) {
-                const response = await fetch('/budget/transaction/' + transactionToDelete, { method: 'DELETE' });
+                noteLocalChange();
+                const response = await fetch(BASE_PATH + '/budget/transaction/' + transactionToDelete, { method: 'DELETE' });
                 if (response.ok) location.reload();
             }
         });
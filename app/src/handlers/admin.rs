@@ -0,0 +1,118 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Extension, Json, Router,
+};
+use common::{AppState, Config, auth::UserContext};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+/// What the reset body's `confirm` must say, so a stray request cannot wipe the database.
+pub const RESET_CONFIRMATION: &str = "DELETE ALL DATA";
+
+/// Admin-only routes; empty unless `allow_reset` is set, so the reset endpoint is a 404 otherwise.
+pub fn admin_routes(config: &Config) -> Router<Arc<AppState>> {
+    if !config.allow_reset {
+        return Router::new();
+    }
+    Router::new().route("/admin/reset", post(reset))
+}
+
+#[derive(Deserialize)]
+pub struct ResetRequest {
+    #[serde(default)]
+    pub confirm: String,
+}
+
+/// Deletes all transactions, budgets, categories and cards.
+pub async fn reset(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Json(payload): Json<ResetRequest>,
+) -> Response {
+    if let Err(msg) = UserContext::authorize(ctx.as_deref()) {
+        return (StatusCode::FORBIDDEN, Json(json!({ "error": msg }))).into_response();
+    }
+    if payload.confirm != RESET_CONFIRMATION {
+        let msg = format!("Send {{\"confirm\": \"{}\"}} to delete all data", RESET_CONFIRMATION);
+        return (StatusCode::BAD_REQUEST, Json(json!({ "error": msg }))).into_response();
+    }
+
+    match state.db.truncate_all().await {
+        Ok(()) => {
+            tracing::warn!("All data deleted via /admin/reset");
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => common::errors::internal_error(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::{header, Request}};
+    use cards::service::CardService;
+    use categories::service::CategoryService;
+    use common::money::RoundingMode;
+    use database::get_test_db;
+    use tower::ServiceExt;
+    use transactions::{models::{CreateTransactionCommand, TransactionSort}, service::TransactionService};
+
+    fn reset_request(body: &str) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/admin/reset")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reset_requires_flag_and_confirmation() {
+        let config = Config { allow_reset: true, ..Config::default() };
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(db.clone(), config.clone()));
+        let rent = CategoryService::list_categories(&state.db).await.unwrap().into_iter().find(|c| c.name == "Rent").unwrap();
+        CategoryService::set_monthly_limit(None, &state.db, rent.id, "2026-01".to_string(), 1200.0, RoundingMode::HalfUp).await.unwrap();
+        TransactionService::create_transaction(None, &state.db, CreateTransactionCommand {
+            category_id: rent.id,
+            card_id: None,
+            transaction_date: "2026-01-05".to_string(),
+            amount_dollars: 1200.0,
+            notes: None,
+            excluded: false,
+            new_category: None,
+        }, None).await.unwrap();
+        let transactions = async || TransactionService::get_month_view(&state.db, "2026-01", TransactionSort::default()).await.unwrap().0;
+        let confirmed = json!({ "confirm": RESET_CONFIRMATION }).to_string();
+
+        // Disabled: the route is not there at all
+        let disabled = admin_routes(&Config::default()).with_state(state.clone());
+        let response = disabled.oneshot(reset_request(&confirmed)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let app = admin_routes(&config).with_state(state.clone());
+        let response = app.clone().oneshot(reset_request(r#"{"confirm": "yes"}"#)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(transactions().await.len(), 1);
+
+        let mut events = state.db.events().subscribe();
+        let response = app.oneshot(reset_request(&confirmed)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(transactions().await.is_empty());
+        let mut deleted = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            deleted.push(event.month);
+        }
+        assert!(deleted.contains(&"2026-01".to_string()), "{:?}", deleted);
+        assert!(CardService::list_cards(&state.db).await.unwrap().is_empty());
+        let categories = CategoryService::list_categories(&state.db).await.unwrap();
+        let names: Vec<&str> = categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Uncategorized"]);
+        let budgets = CategoryService::get_budget_view(&state.db, "2026-01").await.unwrap();
+        assert!(budgets.iter().all(|v| v.budget.is_none()));
+    }
+}
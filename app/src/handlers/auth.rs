@@ -1,39 +1,75 @@
 use axum::{
-    extract::State,
+    extract::{Query, State},
     response::{Html, IntoResponse, Redirect, Response},
     Form,
 };
-use common::{AppState, auth::AUTH_SESSION_KEY};
+use common::{AppState, auth::{safe_next, AUTH_AT_SESSION_KEY, AUTH_SESSION_KEY, LAST_MONTH_SESSION_KEY}, month::Month};
 use std::sync::Arc;
 use askama::Template;
 use serde::Deserialize;
 use tower_sessions::Session;
+use crate::setup::SetupService;
 
 #[derive(Template)]
 #[template(path = "login.html")]
 pub struct LoginTemplate {
+    pub app_name: String,
+    pub base_path: String,
     pub error: Option<String>,
+    /// Where to go after logging in; only ever a path checked by `safe_next`.
+    pub next: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LoginQuery {
+    pub next: Option<String>,
 }
 
 #[derive(Deserialize)]
 pub struct LoginForm {
     pub password: String,
+    pub next: Option<String>,
 }
 
-pub async fn root_redirect() -> Response {
-    let now = chrono::Local::now();
-    let month = now.format("%Y-%m").to_string();
-    Redirect::to(&format!("/budget/{}", month)).into_response()
+fn checked_next(next: Option<&str>) -> Option<String> {
+    next.and_then(safe_next).map(str::to_string)
+}
+
+/// Sends `/` to the month this session last viewed, or the current month. An instance without
+/// any data goes to first-run setup instead.
+pub async fn root_redirect(State(state): State<Arc<AppState>>, session: Session) -> Response {
+    if SetupService::needs_setup(&state.db).await.unwrap_or(false) {
+        return Redirect::to(&state.config.url_for("/setup")).into_response();
+    }
+
+    let last_month = session
+        .get::<String>(LAST_MONTH_SESSION_KEY)
+        .await
+        .unwrap_or(None)
+        .and_then(|m| m.parse::<Month>().ok());
+
+    let month = match last_month {
+        Some(month) => month.to_string(),
+        None => chrono::Local::now().format("%Y-%m").to_string(),
+    };
+    Redirect::to(&state.config.url_for(&format!("/budget/{}", month))).into_response()
 }
 
 pub async fn login_get(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<LoginQuery>,
 ) -> Response {
+    let next = checked_next(query.next.as_deref());
     if state.config.app_password.is_none() {
-        return Redirect::to("/").into_response();
+        return Redirect::to(&state.config.url_for(next.as_deref().unwrap_or("/"))).into_response();
     }
 
-    let template = LoginTemplate { error: None };
+    let template = LoginTemplate {
+        app_name: state.config.app_name.clone(),
+        base_path: state.config.base_path.clone(),
+        error: None,
+        next,
+    };
     match template.render() {
         Ok(html) => Html(html).into_response(),
         Err(_) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, "Template Error").into_response(),
@@ -45,16 +81,173 @@ pub async fn login_post(
     session: Session,
     Form(payload): Form<LoginForm>,
 ) -> Response {
-    if let Some(correct_password) = &state.config.app_password {
-        if payload.password == *correct_password {
-            let _ = session.insert(AUTH_SESSION_KEY, true).await;
-            return Redirect::to("/").into_response();
-        }
+    let next = checked_next(payload.next.as_deref());
+    if let Some(correct_password) = &state.config.app_password
+        && payload.password == *correct_password
+    {
+        let _ = session.insert(AUTH_SESSION_KEY, true).await;
+        let _ = session.insert(AUTH_AT_SESSION_KEY, chrono::Utc::now().timestamp()).await;
+        return Redirect::to(&state.config.url_for(next.as_deref().unwrap_or("/"))).into_response();
     }
 
-    let template = LoginTemplate { error: Some("Invalid password".into()) };
+    let template = LoginTemplate {
+        app_name: state.config.app_name.clone(),
+        base_path: state.config.base_path.clone(),
+        error: Some("Invalid password".into()),
+        next,
+    };
     match template.render() {
         Ok(html) => (axum::http::StatusCode::UNAUTHORIZED, Html(html)).into_response(),
         Err(_) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, "Template Error").into_response(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::{header, Request, StatusCode}, routing::get, Router};
+    use common::Config;
+    use database::get_test_db;
+    use tower::ServiceExt;
+    use axum::routing::post;
+    use tower_sessions::{MemoryStore, SessionManagerLayer};
+
+    #[tokio::test]
+    async fn test_login_page_shows_app_name() {
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(
+            db.clone(),
+            Config {
+                app_password: Some("secret".to_string()),
+                app_name: "Smith Family Budget".to_string(),
+                ..Config::default()
+            },
+        ));
+
+        let response = login_get(State(state), Query(LoginQuery { next: None })).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+
+        assert!(html.contains("<title>Login - Smith Family Budget</title>"));
+        assert!(html.contains("Smith Family Budget</h2>"));
+    }
+
+    #[tokio::test]
+    async fn test_root_redirects_to_last_viewed_month() {
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(db.clone(), Config::default()));
+        let app = Router::new()
+            .route("/", get(root_redirect))
+            .nest("/budget", transactions::handler::transactions_router(state.clone()))
+            .with_state(state)
+            .layer(SessionManagerLayer::new(MemoryStore::default()).with_secure(false));
+
+        let response = app.clone()
+            .oneshot(Request::builder().uri("/budget/2026-03").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap().split(';').next().unwrap().to_string();
+
+        let response = app
+            .oneshot(Request::builder().uri("/").header(header::COOKIE, cookie).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(response.headers()[header::LOCATION], "/budget/2026-03");
+    }
+
+    #[tokio::test]
+    async fn test_login_sets_cookie_with_configured_name() {
+        let config = Config {
+            app_password: Some("secret".to_string()),
+            cookie_name: "budget_smith".to_string(),
+            ..Config::default()
+        };
+        let session_layer = common::auth::session_layer(&config);
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(db.clone(), config));
+        let app = Router::new()
+            .route("/login", post(login_post))
+            .with_state(state)
+            .layer(session_layer);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/login")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from("password=secret"))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+        assert!(cookie.starts_with("budget_smith="), "unexpected cookie: {}", cookie);
+    }
+
+    async fn login_with_next(next: &str) -> axum::response::Response {
+        let config = Config { app_password: Some("secret".to_string()), ..Config::default() };
+        let session_layer = common::auth::session_layer(&config);
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(db.clone(), config));
+        let app = Router::new()
+            .route("/login", post(login_post))
+            .with_state(state)
+            .layer(session_layer);
+
+        let encoded: String = next.bytes().map(|b| format!("%{:02X}", b)).collect();
+        let request = Request::builder()
+            .method("POST")
+            .uri("/login")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(format!("password=secret&next={}", encoded)))
+            .unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_login_honors_local_next() {
+        let response = login_with_next("/budget/2026-01").await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(response.headers()[header::LOCATION], "/budget/2026-01");
+    }
+
+    #[tokio::test]
+    async fn test_login_rejects_external_next() {
+        for next in ["https://evil.com", "//evil.com"] {
+            let response = login_with_next(next).await;
+            assert_eq!(response.status(), StatusCode::SEE_OTHER);
+            assert_eq!(response.headers()[header::LOCATION], "/", "{} must not be followed", next);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_redirects_under_base_path() {
+        let config = Config { app_password: Some("secret".to_string()), base_path: "/money".to_string(), ..Config::default() };
+        let session_layer = common::auth::session_layer(&config);
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(db.clone(), config));
+        let routes = Router::new()
+            .route("/", get(root_redirect))
+            .route("/login", post(login_post));
+        let app = Router::new()
+            .nest("/money", routes)
+            .with_state(state)
+            .layer(session_layer);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/money/login")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from("password=secret"))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(response.headers()[header::LOCATION], "/money");
+
+        // The prefixed root is routed, and sends on to a prefixed month
+        let response = app.oneshot(Request::builder().uri("/money").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert!(response.headers()[header::LOCATION].to_str().unwrap().starts_with("/money/budget/"));
+    }
+}
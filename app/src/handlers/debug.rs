@@ -0,0 +1,45 @@
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use common::{AppState, auth::UserContext};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use transactions::service::{TransactionError, TransactionService};
+
+/// Connection pool pressure, for tuning `max_connections`.
+pub async fn pool_stats(
+    State(state): State<Arc<AppState>>,
+) -> Json<Value> {
+    let stats = state.db.pool_stats();
+    Json(json!({
+        "size": stats.size,
+        "idle": stats.idle,
+        "in_use": stats.in_use,
+    }))
+}
+
+/// Transactions referencing a card or category that no longer exists.
+pub async fn orphans(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, TransactionError> {
+    let orphans = TransactionService::list_orphans(&state.db).await?;
+    Ok(Json(orphans))
+}
+
+/// Transactions whose sign disagrees with their category (negative income, positive expenses).
+pub async fn sign_audit(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, TransactionError> {
+    let ids = TransactionService::audit_sign_consistency(&state.db).await?;
+    Ok(Json(json!({ "mis_signed": ids })))
+}
+
+/// Re-signs what `sign_audit` reports, returning the ids it changed.
+pub async fn fix_signs(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+) -> Result<impl IntoResponse, TransactionError> {
+    let ids = TransactionService::fix_sign_consistency(ctx.as_deref(), &state.db).await?;
+    if !ids.is_empty() {
+        tracing::warn!("Re-signed {} transaction(s) via /debug/sign-audit: {:?}", ids.len(), ids);
+    }
+    Ok(Json(json!({ "fixed": ids })))
+}
@@ -0,0 +1,141 @@
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse, Redirect, Response},
+    Extension, Form,
+};
+use askama::Template;
+use common::{AppState, auth::UserContext};
+use serde::Deserialize;
+use std::sync::Arc;
+use crate::setup::{SetupError, SetupOptions, SetupService};
+
+#[derive(Template)]
+#[template(path = "setup.html")]
+pub struct SetupTemplate {
+    pub app_name: String,
+    pub base_path: String,
+    pub error: Option<String>,
+    // Textarea contents, one name per line
+    pub income_categories: String,
+    pub expense_categories: String,
+    pub cards: String,
+}
+
+/// The setup form; each field is a textarea with one name per line.
+#[derive(Deserialize, Default)]
+pub struct SetupForm {
+    #[serde(default)]
+    pub income_categories: String,
+    #[serde(default)]
+    pub expense_categories: String,
+    #[serde(default)]
+    pub cards: String,
+}
+
+impl From<&SetupForm> for SetupOptions {
+    fn from(form: &SetupForm) -> Self {
+        let lines = |text: &str| text.lines().map(str::to_string).collect();
+        SetupOptions {
+            income_categories: lines(&form.income_categories),
+            expense_categories: lines(&form.expense_categories),
+            cards: lines(&form.cards),
+        }
+    }
+}
+
+fn render(state: &AppState, form: &SetupForm, error: Option<String>) -> Result<String, SetupError> {
+    let template = SetupTemplate {
+        app_name: state.config.app_name.clone(),
+        base_path: state.config.base_path.clone(),
+        error,
+        income_categories: form.income_categories.clone(),
+        expense_categories: form.expense_categories.clone(),
+        cards: form.cards.clone(),
+    };
+    template.render().map_err(|e| SetupError::Infrastructure(e.to_string()))
+}
+
+/// The first-run form, prefilled with the default categories. Once the instance has data this
+/// just goes back to `/`.
+pub async fn setup_get(State(state): State<Arc<AppState>>) -> Result<Response, SetupError> {
+    if !SetupService::needs_setup(&state.db).await? {
+        return Ok(Redirect::to(&state.config.url_for("/")).into_response());
+    }
+
+    let defaults = SetupOptions::defaults();
+    let form = SetupForm {
+        income_categories: defaults.income_categories.join("\n"),
+        expense_categories: defaults.expense_categories.join("\n"),
+        cards: String::new(),
+    };
+    Ok(Html(render(&state, &form, None)?).into_response())
+}
+
+pub async fn setup_post(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Form(form): Form<SetupForm>,
+) -> Result<Response, SetupError> {
+    if !SetupService::needs_setup(&state.db).await? {
+        return Ok(Redirect::to(&state.config.url_for("/")).into_response());
+    }
+
+    match SetupService::initialize(ctx.as_deref(), &state.db, SetupOptions::from(&form), &state.config.palette()).await {
+        Ok(_) => Ok(Redirect::to(&state.config.url_for("/")).into_response()),
+        Err(e @ SetupError::Infrastructure(_)) => Err(e),
+        // Back to the form with what was typed, so nothing has to be re-entered
+        Err(e) => {
+            let (status, msg) = e.status_and_message();
+            Ok((status, Html(render(&state, &form, Some(msg))?)).into_response())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::{header, Request, StatusCode}, routing::get, Router};
+    use categories::service::CategoryService;
+    use common::Config;
+    use database::get_test_db;
+    use tower::ServiceExt;
+    use tower_sessions::{MemoryStore, SessionManagerLayer};
+
+    #[tokio::test]
+    async fn test_setup_runs_once_on_an_empty_instance() {
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(db.clone(), Config::default()));
+        state.db.truncate_all().await.unwrap();
+        let app = Router::new()
+            .route("/", get(crate::handlers::auth::root_redirect))
+            .route("/setup", get(setup_get).post(setup_post))
+            .with_state(state.clone())
+            .layer(SessionManagerLayer::new(MemoryStore::default()));
+        let get_request = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let post_setup = || Request::builder()
+            .method("POST")
+            .uri("/setup")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from("income_categories=Paycheck&expense_categories=Housing%0D%0AFuel&cards=Visa"))
+            .unwrap();
+
+        let response = app.clone().oneshot(get_request("/")).await.unwrap();
+        assert_eq!(response.headers().get(header::LOCATION).unwrap(), "/setup");
+        let response = app.clone().oneshot(get_request("/setup")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.clone().oneshot(post_setup()).await.unwrap();
+        assert_eq!(response.headers().get(header::LOCATION).unwrap(), "/");
+        let names: Vec<String> = CategoryService::list_categories(&state.db).await.unwrap().into_iter().map(|c| c.name).collect();
+        assert_eq!(names, vec!["Fuel", "Housing", "Paycheck", "Uncategorized"]);
+
+        // Disabled now that there is data
+        let response = app.clone().oneshot(get_request("/setup")).await.unwrap();
+        assert_eq!(response.headers().get(header::LOCATION).unwrap(), "/");
+        let response = app.clone().oneshot(post_setup()).await.unwrap();
+        assert_eq!(response.headers().get(header::LOCATION).unwrap(), "/");
+        assert_eq!(CategoryService::list_categories(&state.db).await.unwrap().len(), 4);
+        let response = app.oneshot(get_request("/")).await.unwrap();
+        assert!(response.headers().get(header::LOCATION).unwrap().to_str().unwrap().starts_with("/budget/"));
+    }
+}
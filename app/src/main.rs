@@ -3,16 +3,17 @@ use axum::{
     Router, 
     middleware::{self},
 };
-use common::{AppState, Config, auth::{auth_middleware}};
+use common::{AppState, Config, auth::{auth_middleware, forwarded_proto_middleware, session_layer}, errors::debug_errors_middleware};
 use database::Database;
 use std::sync::Arc;
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use rust_embed::RustEmbed;
 use axum_embed::ServeEmbed;
-use tower_sessions::{MemoryStore, SessionManagerLayer};
 
+mod diagnostics;
 mod handlers;
+mod setup;
 use handlers::auth::{login_get, login_post, root_redirect};
 
 #[derive(RustEmbed, Clone)]
@@ -29,20 +30,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // 2. Load Config from CLI args
     let config = Config::parse();
+    diagnostics::log_startup(&config, Assets::iter().count());
 
     // 3. Initialize Database
-    let db = Database::new(&config.database_url).await?;
+    let db = Database::new(
+        &config.database_url,
+        config.slow_query_ms.map(std::time::Duration::from_millis),
+    ).await?;
+    let migration_status = db.migration_status().await?;
+    if migration_status.has_drift() {
+        tracing::error!("Database schema does not match this binary: {}", migration_status);
+        return Err(format!("migration drift detected: {}", migration_status).into());
+    }
     db.run_migrations().await?;
+    diagnostics::log_migrations(&migration_status);
 
-    let state = Arc::new(AppState {
-        db,
-        config: config.clone(),
-    });
+    let state = Arc::new(AppState::new(db.clone(), config.clone()));
 
     // 4. Session Store
-    let session_store = MemoryStore::default();
-    let session_layer = SessionManagerLayer::new(session_store)
-        .with_secure(cfg!(not(debug_assertions))); // Secure=true in Release mode
+    let session_layer = session_layer(&config);
 
     // 5. Routing
     let serve_assets = ServeEmbed::<Assets>::new();
@@ -51,25 +57,51 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Ensure this router has the correct State type from the start
     let protected_routes = Router::<Arc<AppState>>::new()
         .route("/", get(root_redirect))
+        .route("/setup", get(handlers::setup::setup_get).post(handlers::setup::setup_post))
+        .route("/debug/pool", get(handlers::debug::pool_stats))
+        .route("/debug/orphans", get(handlers::debug::orphans))
+        .route("/debug/sign-audit", get(handlers::debug::sign_audit).post(handlers::debug::fix_signs))
         .nest("/budget", transactions::handler::transactions_router(state.clone()))
-        .nest("/categories", categories::handler::categories_router(state.clone()))
+        .nest("/categories", categories::handler::categories_router(state.clone())
+            .merge(transactions::handler::category_export_router(state.clone())))
         .nest("/cards", cards::handler::cards_router(state.clone()))
+        .nest("/config", categories::handler::config_router(state.clone()))
+        .merge(handlers::admin::admin_routes(&config))
         .layer(middleware::from_fn_with_state(state.clone(), auth_middleware));
 
     // Combined Application Router
-    let app = Router::<Arc<AppState>>::new()
+    let routes = Router::<Arc<AppState>>::new()
         .route("/login", get(login_get).post(login_post))
         .nest_service("/public", serve_assets)
-        .merge(protected_routes)
+        .merge(protected_routes);
+
+    // Behind a reverse proxy on a path prefix everything moves under it
+    let routes = if config.base_path.is_empty() {
+        routes
+    } else {
+        Router::new().nest(&config.base_path, routes)
+    };
+
+    let app = routes
+        .layer(middleware::from_fn_with_state(state.clone(), debug_errors_middleware))
         .with_state(state)
         .layer(session_layer)
+        .layer(middleware::from_fn(forwarded_proto_middleware))
         .layer(TraceLayer::new_for_http());
 
     // 6. Start Server
     let addr = format!("0.0.0.0:{}", config.port);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     tracing::info!("Listening on {}", addr);
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+
+    // Let in-flight queries finish and checkpoint the WAL before exiting
+    db.close().await;
+    tracing::info!("Shut down");
 
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,149 @@
+//! First-run setup: seeding categories and cards on an instance that has none.
+
+use cards::service::CardService;
+use categories::{models::UNCATEGORIZED_CATEGORY_NAME, service::CategoryService};
+use common::{auth::UserContext, errors::{Domain, DomainError}, palette::Palette};
+use database::Database;
+use tracing::instrument;
+
+#[derive(Debug, Default)]
+pub struct Setup;
+
+impl Domain for Setup {
+    const NOT_FOUND: &'static str = "Setup not available";
+}
+
+pub type SetupError = DomainError<Setup>;
+
+/// Categories the setup form starts with, as (name, is_income).
+pub const DEFAULT_CATEGORIES: &[(&str, bool)] = &[
+    ("Salary", true),
+    ("Rent", false),
+    ("Groceries", false),
+    ("Dining Out", false),
+    ("Transportation", false),
+    ("Utilities", false),
+    ("Phone", false),
+    ("Health", false),
+    ("Subscriptions", false),
+    ("Other", false),
+];
+
+/// What to create on first run. Names are trimmed; blanks and repeats are skipped.
+#[derive(Debug, Default)]
+pub struct SetupOptions {
+    pub income_categories: Vec<String>,
+    pub expense_categories: Vec<String>,
+    pub cards: Vec<String>,
+}
+
+impl SetupOptions {
+    /// The default categories and no cards.
+    pub fn defaults() -> Self {
+        let names = |income: bool| DEFAULT_CATEGORIES.iter()
+            .filter(|(_, is_income)| *is_income == income)
+            .map(|(name, _)| name.to_string())
+            .collect();
+        Self { income_categories: names(true), expense_categories: names(false), cards: Vec::new() }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SetupSummary {
+    pub categories: usize,
+    pub cards: usize,
+}
+
+pub struct SetupService;
+
+impl SetupService {
+    /// True while the instance has no cards and no categories besides "Uncategorized", which
+    /// migrations and `/admin/reset` always leave behind.
+    pub async fn needs_setup(db: &Database) -> Result<bool, SetupError> {
+        let mut conn = db.connection().await?;
+        Self::needs_setup_on(&mut conn).await
+    }
+
+    async fn needs_setup_on(conn: &mut database::Connection) -> Result<bool, SetupError> {
+        let categories = CategoryService::list_categories_on(conn).await.map_err(DomainError::recast)?;
+        let cards = CardService::list_cards_on(conn).await.map_err(DomainError::recast)?;
+        Ok(cards.is_empty() && categories.iter().all(|c| c.name == UNCATEGORIZED_CATEGORY_NAME))
+    }
+
+    /// Creates the chosen categories and cards in one unit of work with the check that the
+    /// instance is still empty, so it can only run once and a failure leaves nothing behind.
+    /// Refused with `Conflict` once the instance has data. New categories get random colors from `palette`.
+    #[instrument(skip(db))]
+    pub async fn initialize(ctx: Option<&UserContext>, db: &Database, options: SetupOptions, palette: &Palette) -> Result<SetupSummary, SetupError> {
+        UserContext::authorize(ctx).map_err(SetupError::Forbidden)?;
+
+        let mut uow = db.begin().await?;
+        if !Self::needs_setup_on(uow.connection()).await? {
+            return Err(SetupError::Conflict("Setup has already been completed".to_string()));
+        }
+
+        let mut summary = SetupSummary { categories: 0, cards: 0 };
+        let mut category_ids = Vec::new();
+        let categories = options.income_categories.iter().map(|name| (name, true))
+            .chain(options.expense_categories.iter().map(|name| (name, false)));
+        for (name, is_income) in categories {
+            if name.trim().is_empty() {
+                continue;
+            }
+            // Finds rather than creates repeats and "Uncategorized"
+            let category = CategoryService::find_or_create_on(uow.connection(), name, is_income, None, palette).await.map_err(DomainError::recast)?;
+            if category.name != UNCATEGORIZED_CATEGORY_NAME && !category_ids.contains(&category.id) {
+                category_ids.push(category.id);
+            }
+        }
+
+        summary.categories = category_ids.len();
+
+        let mut seen = Vec::new();
+        for name in options.cards.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+            if seen.contains(&name.to_lowercase()) {
+                continue;
+            }
+            seen.push(name.to_lowercase());
+            CardService::create_card_on(uow.connection(), name.to_string()).await.map_err(DomainError::recast)?;
+            summary.cards += 1;
+        }
+
+        uow.commit().await?;
+
+        tracing::info!("First-run setup created {} categories and {} cards", summary.categories, summary.cards);
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::get_test_db;
+
+    #[tokio::test]
+    async fn test_initialize_seeds_chosen_defaults_once() {
+        let db = get_test_db().await;
+        assert!(!SetupService::needs_setup(&db).await.unwrap(), "migrations seed data");
+        db.truncate_all().await.unwrap();
+        assert!(SetupService::needs_setup(&db).await.unwrap());
+
+        let options = SetupOptions {
+            income_categories: vec!["Paycheck".to_string()],
+            expense_categories: vec!["Housing".to_string(), " ".to_string(), "housing".to_string(), "Uncategorized".to_string()],
+            cards: vec!["Visa".to_string(), "visa ".to_string()],
+        };
+        let summary = SetupService::initialize(None, &db, options, &Palette::default()).await.unwrap();
+        assert_eq!(summary, SetupSummary { categories: 2, cards: 1 });
+
+        let categories = CategoryService::list_categories(&db).await.unwrap();
+        let names: Vec<(&str, bool)> = categories.iter().map(|c| (c.name.as_str(), c.is_income)).collect();
+        assert_eq!(names, vec![("Housing", false), ("Paycheck", true), ("Uncategorized", false)]);
+        let cards = CardService::list_cards(&db).await.unwrap();
+        assert_eq!(cards.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["Visa"]);
+
+        assert!(!SetupService::needs_setup(&db).await.unwrap());
+        let again = SetupService::initialize(None, &db, SetupOptions::defaults(), &Palette::default()).await;
+        assert!(matches!(again, Err(SetupError::Conflict(_))));
+    }
+}
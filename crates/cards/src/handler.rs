@@ -1,37 +1,22 @@
-use crate::models::{CreateCardRequest, Card, UpdateCardRequest};
+use crate::models::{CreateCardRequest, Card, SetActiveBulkRequest, UpdateCardRequest};
 use crate::service::{CardError, CardService};
 use axum::{
     extract::{State, Path},
     http::StatusCode,
-    response::{IntoResponse, Response},
-    routing::{get, put},
-    Json, Router,
+    response::IntoResponse,
+    routing::{get, post},
+    Extension, Json, Router,
 };
-use common::AppState;
+use common::{AppState, auth::UserContext};
 use std::sync::Arc;
 use serde_json::json;
 
-impl IntoResponse for CardError {
-    fn into_response(self) -> Response {
-        let (status, msg) = match self {
-            CardError::InvalidInput(msg) => (StatusCode::BAD_REQUEST, msg),
-            CardError::Conflict(msg) => (StatusCode::CONFLICT, msg),
-            CardError::NotFound => (StatusCode::NOT_FOUND, "Card not found".to_string()),
-            CardError::Infrastructure(_) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Internal server error".to_string(),
-            ),
-        };
-        
-        (status, Json(json!({ "error": msg }))).into_response()
-    }
-}
-
 pub fn cards_router(state: Arc<AppState>) -> Router<Arc<AppState>> {
     Router::new()
         .route("/", get(list_active_cards).post(create_card))
         .route("/all", get(list_all_cards))
-        .route("/{id}", put(update_card))
+        .route("/active", post(set_active_bulk))
+        .route("/{id}", get(get_card).put(update_card))
         .with_state(state)
 }
 
@@ -51,17 +36,64 @@ async fn list_all_cards(
 
 async fn create_card(
     State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
     Json(payload): Json<CreateCardRequest>,
 ) -> Result<impl IntoResponse, CardError> {
-    let id = CardService::create_card(&state.db, payload.name).await?;
+    let id = CardService::create_card(ctx.as_deref(), &state.db, payload.name).await?;
     Ok((StatusCode::CREATED, Json(json!({ "id": id }))))
 }
 
+async fn get_card(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Card>, CardError> {
+    let card = CardService::get_card(&state.db, id).await?;
+    Ok(Json(card))
+}
+
 async fn update_card(
     State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
     Path(id): Path<i64>,
     Json(payload): Json<UpdateCardRequest>,
 ) -> Result<impl IntoResponse, CardError> {
-    CardService::update_card(&state.db, id, payload.name, payload.is_active).await?;
+    CardService::update_card(ctx.as_deref(), &state.db, id, payload.name, payload.is_active).await?;
     Ok(StatusCode::OK)
 }
+
+async fn set_active_bulk(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Json(payload): Json<SetActiveBulkRequest>,
+) -> Result<impl IntoResponse, CardError> {
+    let updated = CardService::set_active_bulk(ctx.as_deref(), &state.db, &payload.ids, payload.active).await?;
+    Ok(Json(json!({ "updated": updated })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use common::Config;
+    use database::get_test_db;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_get_card_returns_json_or_404() {
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(db.clone(), Config::default()));
+        let id = CardService::create_card(None, &state.db, "Visa".to_string()).await.unwrap();
+        let app = cards_router(state.clone()).with_state(state);
+
+        let request = |id: i64| Request::builder().uri(format!("/{}", id)).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(request(id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let card: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(card["name"], "Visa");
+
+        let response = app.oneshot(request(id + 1000)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -12,6 +12,13 @@ pub struct CreateCardRequest {
     pub name: String,
 }
 
+/// Activates or retires several cards at once.
+#[derive(Deserialize)]
+pub struct SetActiveBulkRequest {
+    pub ids: Vec<i64>,
+    pub active: bool,
+}
+
 #[derive(Deserialize)]
 pub struct UpdateCardRequest {
     pub name: String,
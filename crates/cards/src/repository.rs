@@ -75,6 +75,19 @@ impl<'a> CardRepository<'a> {
         Ok(())
     }
 
+    pub async fn set_active(&mut self, id: i64, active: bool) -> Result<(), RepositoryError> {
+        let result = sqlx::query("UPDATE cards SET is_active = $1 WHERE id = $2")
+            .bind(active)
+            .bind(id)
+            .execute(&mut *self.conn)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
     pub async fn find_by_id(&mut self, id: i64) -> Result<Option<Card>, RepositoryError> {
         let record = sqlx::query_as::<_, CardRecord>(
             "SELECT id, name, is_active FROM cards WHERE id = $1",
@@ -86,6 +99,7 @@ impl<'a> CardRepository<'a> {
         Ok(record.map(|r| r.into()))
     }
 
+    #[allow(dead_code)]
     pub async fn delete(&mut self, id: i64) -> Result<(), RepositoryError> {
         let result = sqlx::query("DELETE FROM cards WHERE id = $1")
             .bind(id)
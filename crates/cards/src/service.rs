@@ -1,84 +1,149 @@
 use crate::models::{Card, CreateCardRequest, UpdateCardRequest};
 use crate::repository::CardRepository;
-use database::{RepositoryError, Database};
+use database::Database;
+use common::errors::{Domain, DomainError};
+use common::auth::UserContext;
 use tracing::instrument;
 
-#[derive(Debug, thiserror::Error)]
-pub enum CardError {
-    #[error("Invalid input: {0}")]
-    InvalidInput(String),
-    #[error("Database error: {0}")]
-    Infrastructure(String),
-    #[error("Card not found")]
-    NotFound,
-    #[error("Card name already exists")]
-    Conflict(String),
-}
+#[derive(Debug, Default)]
+pub struct Cards;
 
-impl From<RepositoryError> for CardError {
-    fn from(err: RepositoryError) -> Self {
-        match err {
-            RepositoryError::NotFound => CardError::NotFound,
-            RepositoryError::UniqueViolation(msg) => CardError::Conflict(msg),
-            RepositoryError::Infrastructure(e) => CardError::Infrastructure(e.to_string()),
-            _ => CardError::Infrastructure(err.to_string()),
-        }
-    }
+impl Domain for Cards {
+    const NOT_FOUND: &'static str = "Card not found";
 }
 
+pub type CardError = DomainError<Cards>;
+
 pub struct CardService;
 
 impl CardService {
     #[instrument(skip(db))]
-    pub async fn create_card(db: &Database, name: String) -> Result<i64, CardError> {
+    pub async fn create_card(ctx: Option<&UserContext>, db: &Database, name: String) -> Result<i64, CardError> {
+        UserContext::authorize(ctx).map_err(CardError::Forbidden)?;
+        db.transaction(async |conn| Self::create_card_on(conn, name).await).await
+    }
+
+    /// `create_card` in a unit of work the caller already holds and has authorized.
+    pub async fn create_card_on(conn: &mut database::Connection, name: String) -> Result<i64, CardError> {
         if name.trim().is_empty() {
             return Err(CardError::InvalidInput("Card name cannot be empty".into()));
         }
 
         let req = CreateCardRequest { name: name.trim().to_string() };
-        
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
-        let mut repo = CardRepository::new(uow.connection());
-        
-        let id = repo.create(&req).await?;
-        
-        uow.commit().await.map_err(RepositoryError::from)?;
-        
-        Ok(id)
+        Ok(CardRepository::new(conn).create(&req).await?)
     }
 
     #[instrument(skip(db))]
     pub async fn list_cards(db: &Database) -> Result<Vec<Card>, CardError> {
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
-        let mut repo = CardRepository::new(uow.connection());
-        
+        let mut conn = db.connection().await?;
+        Self::list_cards_on(&mut conn).await
+    }
+
+    /// `list_cards` on a connection the caller already holds.
+    pub async fn list_cards_on(conn: &mut database::Connection) -> Result<Vec<Card>, CardError> {
+        let mut repo = CardRepository::new(conn);
         let cards = repo.list().await?;
         Ok(cards)
     }
 
+    #[instrument(skip(db))]
+    pub async fn get_card(db: &Database, id: i64) -> Result<Card, CardError> {
+        let mut conn = db.connection().await?;
+        let mut repo = CardRepository::new(&mut conn);
+
+        let card = repo.find_by_id(id).await?
+            .ok_or_else(CardError::not_found)?;
+
+        Ok(card)
+    }
+
     #[instrument(skip(db))]
     pub async fn list_active_cards(db: &Database) -> Result<Vec<Card>, CardError> {
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
-        let mut repo = CardRepository::new(uow.connection());
+        let mut conn = db.connection().await?;
+        let mut repo = CardRepository::new(&mut conn);
         
         let cards = repo.list_active().await?;
         Ok(cards)
     }
 
     #[instrument(skip(db))]
-    pub async fn update_card(db: &Database, id: i64, name: String, is_active: bool) -> Result<(), CardError> {
+    pub async fn update_card(ctx: Option<&UserContext>, db: &Database, id: i64, name: String, is_active: bool) -> Result<(), CardError> {
+        UserContext::authorize(ctx).map_err(CardError::Forbidden)?;
+
         if name.trim().is_empty() {
             return Err(CardError::InvalidInput("Card name cannot be empty".into()));
         }
 
         let req = UpdateCardRequest { name: name.trim().to_string(), is_active };
         
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
-        let mut repo = CardRepository::new(uow.connection());
-        
-        repo.update(id, &req).await?;
-        
-        uow.commit().await.map_err(RepositoryError::from)?;
-        Ok(())
+        db.transaction(async |conn| Ok(CardRepository::new(conn).update(id, &req).await?)).await
     }
-}
\ No newline at end of file
+
+    /// Activates or retires every listed card in one transaction. An unknown id fails the whole
+    /// batch with `NotFound`. Returns how many were updated.
+    #[instrument(skip(db))]
+    pub async fn set_active_bulk(ctx: Option<&UserContext>, db: &Database, ids: &[i64], active: bool) -> Result<usize, CardError> {
+        UserContext::authorize(ctx).map_err(CardError::Forbidden)?;
+
+        db.transaction(async |conn| {
+            let mut repo = CardRepository::new(conn);
+            for &id in ids {
+                repo.set_active(id, active).await?;
+            }
+            Ok(ids.len())
+        }).await
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::get_test_db;
+
+    #[tokio::test]
+    async fn test_create_duplicate_card_conflicts() {
+        let db = get_test_db().await;
+
+        CardService::create_card(None, &db, "Visa".to_string()).await.unwrap();
+        let result = CardService::create_card(None, &db, " visa ".to_string()).await;
+
+        assert!(matches!(result, Err(CardError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_rename_card_to_existing_name_conflicts() {
+        let db = get_test_db().await;
+
+        CardService::create_card(None, &db, "Visa".to_string()).await.unwrap();
+        let id = CardService::create_card(None, &db, "Amex".to_string()).await.unwrap();
+        let result = CardService::update_card(None, &db, id, "VISA ".to_string(), true).await;
+
+        assert!(matches!(result, Err(CardError::Conflict(_))));
+
+        // Renaming a card to its own name (in another case) is fine
+        CardService::update_card(None, &db, id, "AMEX".to_string(), true).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_card() {
+        let db = get_test_db().await;
+
+        let id = CardService::create_card(None, &db, "Visa".to_string()).await.unwrap();
+        let card = CardService::get_card(&db, id).await.unwrap();
+        assert_eq!(card.name, "Visa");
+        assert!(card.is_active);
+
+        let missing = CardService::get_card(&db, id + 1000).await;
+        assert!(matches!(missing, Err(CardError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_set_active_bulk_retires_cards() {
+        let db = get_test_db().await;
+        let visa = CardService::create_card(None, &db, "Visa".to_string()).await.unwrap();
+        let amex = CardService::create_card(None, &db, "Amex".to_string()).await.unwrap();
+
+        assert_eq!(CardService::set_active_bulk(None, &db, &[visa, amex], false).await.unwrap(), 2);
+        assert!(!CardService::get_card(&db, visa).await.unwrap().is_active);
+        assert!(!CardService::get_card(&db, amex).await.unwrap().is_active);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::models::{normalize_group, BudgetMode, Classification, CreateCategoryRequest, CreateMonthlyBudgetRequest, MAX_DEFAULT_NOTE_LEN};
+use crate::repository::CategoryRepository;
+use crate::budget_repository::MonthlyBudgetRepository;
+use crate::service::{CategoryError, CategoryService};
+use database::Database;
+use database::events::ChangeKind;
+use common::auth::UserContext;
+use common::money::RoundingMode;
+use common::month::Month;
+use common::palette::{self, Palette};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::instrument;
+
+/// Format version written by `export_config`; imports of any other version are rejected.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// The category setup and one month's limits, without transactions, for bootstrapping another
+/// instance.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct ConfigDocument {
+    pub version: u32,
+    pub month: String, // YYYY-MM the limits were taken from
+    pub categories: Vec<CategoryConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct CategoryConfig {
+    pub name: String,
+    pub color: String,
+    pub is_income: bool,
+    #[serde(default)]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub classification: Classification,
+    #[serde(default)]
+    pub allows_negative_budget: bool,
+    #[serde(default)]
+    pub default_note: Option<String>,
+    #[serde(default)]
+    pub budget: Option<BudgetConfig>, // None when the category had no limit that month
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct BudgetConfig {
+    #[serde(default)]
+    pub mode: BudgetMode,
+    #[serde(default)]
+    pub limit: i64, // Cents; unused for percent-of-income budgets
+    #[serde(default)]
+    pub percent: Option<f64>,
+}
+
+/// What an import did: categories created, categories matched by name, and limits written.
+#[derive(Debug, Serialize, PartialEq, Default)]
+pub struct ConfigImportSummary {
+    pub created: usize,
+    pub existing: usize,
+    pub budgets: usize,
+}
+
+pub struct BackupService;
+
+impl BackupService {
+    /// Active categories, by name, with their limits for `month`. Percent-of-income limits are
+    /// exported as percentages, so they follow the importing instance's income.
+    #[instrument(skip(db))]
+    pub async fn export_config(db: &Database, month: &str) -> Result<ConfigDocument, CategoryError> {
+        let month: Month = month.parse().map_err(CategoryError::InvalidInput)?;
+
+        let mut conn = db.connection().await?;
+        let views = MonthlyBudgetRepository::new(&mut conn).budget_view(&month.to_string()).await?;
+
+        let categories = views.into_iter()
+            .filter(|v| v.category.is_active)
+            .map(|v| CategoryConfig {
+                budget: v.budget.map(|b| BudgetConfig { mode: b.mode, limit: b.limit_amount, percent: b.percent }),
+                name: v.category.name,
+                color: v.category.color,
+                is_income: v.category.is_income,
+                group: v.category.group,
+                icon: v.category.icon,
+                classification: v.category.classification,
+                allows_negative_budget: v.category.allows_negative_budget,
+                default_note: v.category.default_note,
+            })
+            .collect();
+
+        Ok(ConfigDocument { version: CONFIG_VERSION, month: month.to_string(), categories })
+    }
+
+    /// Applies `doc` in one transaction, writing its limits into `month`. Categories are matched to
+    /// existing ones by case-insensitive name: a match keeps its own settings and only gets the
+    /// limit, anything else is created with the document's settings. An invalid entry, or a new
+    /// category beyond `max_categories`, fails the whole import. New categories with an invalid
+    /// color get a random one from `palette`.
+    #[instrument(skip(db, doc))]
+    pub async fn import_config(
+        ctx: Option<&UserContext>,
+        db: &Database,
+        doc: &ConfigDocument,
+        month: &str,
+        max_categories: Option<u32>,
+        palette: &Palette,
+    ) -> Result<ConfigImportSummary, CategoryError> {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        if doc.version != CONFIG_VERSION {
+            return Err(CategoryError::InvalidInput(format!(
+                "Unsupported config version {}, expected {}", doc.version, CONFIG_VERSION
+            )));
+        }
+        let month: Month = month.parse().map_err(CategoryError::InvalidInput)?;
+        let month = month.to_string();
+
+        let mut uow = db.begin().await?;
+        let mut summary = ConfigImportSummary::default();
+
+        // Lowercased name -> (id, allows_negative_budget), kept current so the document can't create a name twice
+        let mut known: HashMap<String, (i64, bool)> = CategoryRepository::new(uow.connection()).list().await?
+            .into_iter()
+            .map(|c| (c.name.to_lowercase(), (c.id, c.allows_negative_budget)))
+            .collect();
+
+        for entry in &doc.categories {
+            let mut req = CreateCategoryRequest::new(entry.name.clone(), entry.color.clone(), entry.is_income)
+                .map_err(CategoryError::InvalidInput)?;
+            let name = req.name.clone();
+
+            let (id, allow_negative) = match known.get(&name.to_lowercase()) {
+                Some(&found) => {
+                    summary.existing += 1;
+                    found
+                }
+                None => {
+                    if !palette::is_hex_color(&req.color) {
+                        req.color = CategoryService::get_random_pastel_color(palette);
+                    }
+                    req.icon = entry.icon.clone();
+                    let note = entry.default_note.as_deref().map(str::trim).filter(|n| !n.is_empty());
+                    if note.is_some_and(|n| n.chars().count() > MAX_DEFAULT_NOTE_LEN) {
+                        return Err(CategoryError::InvalidInput(format!(
+                            "Default note for \"{}\" cannot be longer than {} characters", name, MAX_DEFAULT_NOTE_LEN
+                        )));
+                    }
+
+                    let id = CategoryService::insert_category(uow.connection(), req, max_categories).await?;
+                    let mut repo = CategoryRepository::new(uow.connection());
+                    repo.set_group(id, normalize_group(entry.group.as_deref()).as_deref()).await?;
+                    repo.set_classification(id, entry.classification).await?;
+                    repo.set_allows_negative_budget(id, entry.allows_negative_budget).await?;
+                    repo.set_default_note(id, note).await?;
+
+                    known.insert(name.to_lowercase(), (id, entry.allows_negative_budget));
+                    summary.created += 1;
+                    (id, entry.allows_negative_budget)
+                }
+            };
+
+            if let Some(budget) = &entry.budget {
+                let budget_req = match budget.mode {
+                    // Already whole cents, so the rounding mode can't change it
+                    BudgetMode::Fixed => CreateMonthlyBudgetRequest::new(id, month.clone(), budget.limit as f64 / 100.0, allow_negative, RoundingMode::HalfUp),
+                    BudgetMode::PercentOfIncome => CreateMonthlyBudgetRequest::percent_of_income(id, month.clone(), budget.percent.unwrap_or(0.0)),
+                }
+                .map_err(|e| CategoryError::InvalidInput(format!("Budget for \"{}\": {}", name, e)))?;
+
+                MonthlyBudgetRepository::new(uow.connection()).upsert(&budget_req).await?;
+                summary.budgets += 1;
+            }
+        }
+
+        uow.commit().await?;
+        db.events().publish(month, ChangeKind::Updated, None);
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::get_test_db;
+
+    #[tokio::test]
+    async fn test_export_then_import_into_fresh_db() {
+        let source = get_test_db().await;
+        let travel = CategoryService::create_category_with_color(None, &source, "Travel".to_string(), false, "#112233".to_string(), Some("✈️".to_string()), None).await.unwrap();
+        let loan = CategoryService::create_category(None, &source, "Car Loan".to_string(), false, None).await.unwrap();
+        CategoryService::set_category_group(None, &source, travel, Some("Wants".to_string())).await.unwrap();
+        CategoryService::set_allows_negative_budget(None, &source, loan, true).await.unwrap();
+        CategoryService::set_monthly_limit(None, &source, travel, "2026-03".to_string(), 250.0, RoundingMode::HalfUp).await.unwrap();
+        CategoryService::set_monthly_limit(None, &source, loan, "2026-03".to_string(), -300.0, RoundingMode::HalfUp).await.unwrap();
+
+        let doc = BackupService::export_config(&source, "2026-03").await.unwrap();
+        // Round-trip through JSON like the endpoints do
+        let doc: ConfigDocument = serde_json::from_str(&serde_json::to_string(&doc).unwrap()).unwrap();
+
+        let target = get_test_db().await;
+        target.truncate_all().await.unwrap();
+        let mut events = target.events().subscribe();
+        let summary = BackupService::import_config(None, &target, &doc, "2026-03", None, &Palette::default()).await.unwrap();
+        assert_eq!(events.try_recv().unwrap().month, "2026-03");
+
+        assert_eq!(summary.existing, 1); // Uncategorized is always there
+        assert_eq!(summary.created, doc.categories.len() - 1);
+        assert_eq!(BackupService::export_config(&target, "2026-03").await.unwrap(), doc);
+    }
+
+    #[tokio::test]
+    async fn test_import_dedupes_by_name_and_keeps_existing_settings() {
+        let db = get_test_db().await;
+        CategoryService::create_category_with_color(None, &db, "Travel".to_string(), false, "#112233".to_string(), None, None).await.unwrap();
+
+        let entry = CategoryConfig {
+            name: " travel ".to_string(),
+            color: "#445566".to_string(),
+            is_income: false,
+            group: Some("Wants".to_string()),
+            icon: None,
+            classification: Classification::Want,
+            allows_negative_budget: false,
+            default_note: None,
+            budget: Some(BudgetConfig { mode: BudgetMode::Fixed, limit: 12_500, percent: None }),
+        };
+        let doc = ConfigDocument { version: CONFIG_VERSION, month: "2026-03".to_string(), categories: vec![entry.clone(), entry] };
+
+        let summary = BackupService::import_config(None, &db, &doc, "2026-04", None, &Palette::default()).await.unwrap();
+        assert_eq!(summary, ConfigImportSummary { created: 0, existing: 2, budgets: 2 });
+
+        let exported = BackupService::export_config(&db, "2026-04").await.unwrap();
+        let travel = exported.categories.iter().find(|c| c.name == "Travel").unwrap();
+        assert_eq!(travel.color, "#112233");
+        assert_eq!(travel.group, None);
+        assert_eq!(travel.budget.as_ref().map(|b| b.limit), Some(12_500));
+        assert_eq!(exported.categories.iter().filter(|c| c.name.eq_ignore_ascii_case("travel")).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_import_respects_max_categories() {
+        let db = get_test_db().await;
+        let existing = CategoryService::list_categories(&db).await.unwrap().len() as u32;
+        let entry = |name: &str| CategoryConfig {
+            name: name.to_string(),
+            color: "#445566".to_string(),
+            is_income: false,
+            group: None,
+            icon: None,
+            classification: Classification::default(),
+            allows_negative_budget: false,
+            default_note: None,
+            budget: None,
+        };
+        let doc = ConfigDocument { version: CONFIG_VERSION, month: "2026-03".to_string(), categories: vec![entry("Hiking"), entry("Sailing")] };
+
+        let result = BackupService::import_config(None, &db, &doc, "2026-03", Some(existing + 1), &Palette::default()).await;
+        assert!(matches!(result, Err(CategoryError::InvalidInput(msg)) if msg.contains("Category limit")));
+        assert_eq!(CategoryService::list_categories(&db).await.unwrap().len() as u32, existing);
+
+        let summary = BackupService::import_config(None, &db, &doc, "2026-03", Some(existing + 2), &Palette::default()).await.unwrap();
+        assert_eq!(summary.created, 2);
+
+        // Matching existing categories creates nothing, so a full instance can still take limits
+        let summary = BackupService::import_config(None, &db, &doc, "2026-03", Some(existing + 2), &Palette::default()).await.unwrap();
+        assert_eq!(summary.existing, 2);
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_unknown_version() {
+        let db = get_test_db().await;
+        let doc = ConfigDocument { version: 99, month: "2026-03".to_string(), categories: vec![] };
+
+        let result = BackupService::import_config(None, &db, &doc, "2026-03", None, &Palette::default()).await;
+        assert!(matches!(result, Err(CategoryError::InvalidInput(_))));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::models::{MonthlyBudget, CreateMonthlyBudgetRequest};
+use crate::models::{BudgetMode, Category, CategoryBudgetView, CategoryShare, CategoryVariance, Classification, MonthlyBudget, CreateMonthlyBudgetRequest};
 use database::{self, RepositoryError};
 use sqlx::FromRow;
 
@@ -8,6 +8,8 @@ struct MonthlyBudgetRecord {
     category_id: i64,
     month: String,
     limit_amount: i64,
+    mode: String,
+    percent: Option<f64>,
 }
 
 impl From<MonthlyBudgetRecord> for MonthlyBudget {
@@ -17,6 +19,121 @@ impl From<MonthlyBudgetRecord> for MonthlyBudget {
             category_id: record.category_id,
             month: record.month,
             limit_amount: record.limit_amount,
+            mode: BudgetMode::from_db(&record.mode),
+            percent: record.percent,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct CategoryVarianceRecord {
+    id: i64,
+    name: String,
+    color: String,
+    is_income: bool,
+    is_active: bool,
+    budget_group: Option<String>,
+    allows_negative_budget: bool,
+    default_note: Option<String>,
+    classification: String,
+    icon: Option<String>,
+    total_limit: i64,
+    total_spent: i64,
+}
+
+impl From<CategoryVarianceRecord> for CategoryVariance {
+    fn from(record: CategoryVarianceRecord) -> Self {
+        CategoryVariance {
+            category: Category {
+                id: record.id,
+                name: record.name,
+                color: record.color,
+                is_income: record.is_income,
+                is_active: record.is_active,
+                group: record.budget_group,
+                allows_negative_budget: record.allows_negative_budget,
+                default_note: record.default_note,
+                classification: Classification::from_db(&record.classification),
+                icon: record.icon,
+            },
+            total_limit: record.total_limit,
+            total_spent: record.total_spent,
+            variance: record.total_limit - record.total_spent,
+        }
+    }
+}
+
+/// A category left-joined to its budget for one month; the budget columns are NULL when unset.
+#[derive(FromRow)]
+struct BudgetViewRecord {
+    id: i64,
+    name: String,
+    color: String,
+    is_income: bool,
+    is_active: bool,
+    budget_group: Option<String>,
+    allows_negative_budget: bool,
+    default_note: Option<String>,
+    classification: String,
+    icon: Option<String>,
+    budget_id: Option<i64>,
+    month: Option<String>,
+    limit_amount: Option<i64>,
+    mode: Option<String>,
+    percent: Option<f64>,
+}
+
+impl From<BudgetViewRecord> for CategoryBudgetView {
+    fn from(record: BudgetViewRecord) -> Self {
+        let budget = match (record.budget_id, record.month, record.limit_amount, record.mode) {
+            (Some(id), Some(month), Some(limit_amount), Some(mode)) => Some(MonthlyBudget {
+                id,
+                category_id: record.id,
+                month,
+                limit_amount,
+                mode: BudgetMode::from_db(&mode),
+                percent: record.percent,
+            }),
+            _ => None,
+        };
+
+        CategoryBudgetView {
+            category: Category {
+                id: record.id,
+                name: record.name,
+                color: record.color,
+                is_income: record.is_income,
+                is_active: record.is_active,
+                group: record.budget_group,
+                allows_negative_budget: record.allows_negative_budget,
+                default_note: record.default_note,
+                classification: Classification::from_db(&record.classification),
+                icon: record.icon,
+            },
+            budget,
+            spent: 0,
+            remaining: 0,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct CategoryShareRecord {
+    id: i64,
+    name: String,
+    color: String,
+    spent: i64,
+    percent: f64,
+}
+
+impl From<CategoryShareRecord> for CategoryShare {
+    fn from(record: CategoryShareRecord) -> Self {
+        CategoryShare {
+            category_id: record.id,
+            name: record.name,
+            color: record.color,
+            spent: record.spent,
+            percent: record.percent,
         }
     }
 }
@@ -33,24 +150,61 @@ impl<'a> MonthlyBudgetRepository<'a> {
     pub async fn upsert(&mut self, req: &CreateMonthlyBudgetRequest) -> Result<(), RepositoryError> {
         sqlx::query(
             r#"
-            INSERT INTO monthly_budgets (category_id, month, limit_amount)
-            VALUES ($1, $2, $3)
+            INSERT INTO monthly_budgets (category_id, month, limit_amount, mode, percent)
+            VALUES ($1, $2, $3, $4, $5)
             ON CONFLICT(category_id, month) DO UPDATE SET
-            limit_amount = excluded.limit_amount
+            limit_amount = excluded.limit_amount,
+            mode = excluded.mode,
+            percent = excluded.percent
             "#
         )
         .bind(req.category_id)
         .bind(&req.month)
         .bind(req.limit_amount)
+        .bind(req.mode.as_str())
+        .bind(req.percent)
         .execute(&mut *self.conn)
         .await?;
         
         Ok(())
     }
 
+    pub async fn delete(&mut self, category_id: i64, month: &str) -> Result<(), RepositoryError> {
+        let result = sqlx::query("DELETE FROM monthly_budgets WHERE category_id = $1 AND month = $2")
+            .bind(category_id)
+            .bind(month)
+            .execute(&mut *self.conn)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
     pub async fn get_for_month(&mut self, month: &str) -> Result<Vec<MonthlyBudget>, RepositoryError> {
         let records = sqlx::query_as::<_, MonthlyBudgetRecord>(
-            "SELECT id, category_id, month, limit_amount FROM monthly_budgets WHERE month = $1",
+            "SELECT id, category_id, month, limit_amount, mode, percent FROM monthly_budgets WHERE month = $1",
+        )
+        .bind(month)
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(records.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Active categories plus archived ones that still have a budget this month, by name, each with
+    /// its month budget if set. Percent-of-income limits are left unresolved and actuals at zero.
+    pub async fn budget_view(&mut self, month: &str) -> Result<Vec<CategoryBudgetView>, RepositoryError> {
+        let records = sqlx::query_as::<_, BudgetViewRecord>(
+            r#"
+            SELECT c.id, c.name, c.color, c.is_income, c.is_active, c.budget_group, c.allows_negative_budget, c.default_note, c.classification, c.icon,
+                   b.id AS budget_id, b.month, b.limit_amount, b.mode, b.percent
+            FROM categories c
+            LEFT JOIN monthly_budgets b ON b.category_id = c.id AND b.month = $1
+            WHERE c.is_active = 1 OR b.id IS NOT NULL
+            ORDER BY c.name
+            "#,
         )
         .bind(month)
         .fetch_all(&mut *self.conn)
@@ -59,20 +213,38 @@ impl<'a> MonthlyBudgetRepository<'a> {
         Ok(records.into_iter().map(|r| r.into()).collect())
     }
 
-    pub async fn copy_budgets(&mut self, source_month: &str, target_month: &str) -> Result<u64, RepositoryError> {
+    /// Copies the source month's budgets into an empty target month, returning how many were copied
+    /// and how many were skipped because their category has since been archived (or deleted). Safe
+    /// to race: rows another caller inserted first are skipped via the `(category_id, month)` unique
+    /// constraint.
+    pub async fn copy_budgets(&mut self, source_month: &str, target_month: &str) -> Result<(u64, u64), RepositoryError> {
         let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM monthly_budgets WHERE month = $1")
             .bind(target_month)
             .fetch_one(&mut *self.conn)
             .await?;
 
         if count > 0 {
-            return Ok(0);
+            return Ok((0, 0));
         }
 
+        let skipped: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM monthly_budgets b
+            LEFT JOIN categories c ON c.id = b.category_id
+            WHERE b.month = $1 AND (c.id IS NULL OR c.is_active = 0)
+            "#
+        )
+        .bind(source_month)
+        .fetch_one(&mut *self.conn)
+        .await?;
+
         let result = sqlx::query(
             r#"
-            INSERT INTO monthly_budgets (category_id, month, limit_amount)
-            SELECT category_id, $1, limit_amount FROM monthly_budgets WHERE month = $2
+            INSERT INTO monthly_budgets (category_id, month, limit_amount, mode, percent)
+            SELECT b.category_id, $1, b.limit_amount, b.mode, b.percent FROM monthly_budgets b
+            JOIN categories c ON c.id = b.category_id
+            WHERE b.month = $2 AND c.is_active = 1
+            ON CONFLICT(category_id, month) DO NOTHING
             "#
         )
         .bind(target_month)
@@ -80,8 +252,159 @@ impl<'a> MonthlyBudgetRepository<'a> {
         .execute(&mut *self.conn)
         .await?;
 
+        Ok((result.rows_affected(), skipped as u64))
+    }
+
+    /// Actual income for the month in cents: positive, non-excluded transactions.
+    pub async fn month_income(&mut self, month: &str) -> Result<i64, RepositoryError> {
+        let income: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(amount), 0) FROM transactions WHERE amount > 0 AND excluded = 0 AND strftime('%Y-%m', transaction_date) = $1",
+        )
+        .bind(month)
+        .fetch_one(&mut *self.conn)
+        .await?;
+
+        Ok(income)
+    }
+
+    /// Expense-category spending for the month per classification, in cents. Excluded transactions
+    /// are not counted; classes with no spending are absent.
+    pub async fn spend_by_classification(&mut self, month: &str) -> Result<Vec<(Classification, i64)>, RepositoryError> {
+        let rows: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT c.classification, SUM(MAX(-t.amount, 0))
+            FROM transactions t
+            JOIN categories c ON c.id = t.category_id
+            WHERE c.is_income = 0 AND t.excluded = 0 AND strftime('%Y-%m', t.transaction_date) = $1
+            GROUP BY c.classification
+            "#,
+        )
+        .bind(month)
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(rows.into_iter().map(|(class, spent)| (Classification::from_db(&class), spent)).collect())
+    }
+
+    /// Each category's actual for the month in cents: received for income categories, spent for
+    /// expense categories, excluded transactions not counted.
+    pub async fn month_actuals(&mut self, month: &str) -> Result<Vec<(i64, i64)>, RepositoryError> {
+        let rows: Vec<(i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT t.category_id,
+                   SUM(CASE WHEN c.is_income THEN MAX(t.amount, 0) ELSE MAX(-t.amount, 0) END)
+            FROM transactions t
+            JOIN categories c ON c.id = t.category_id
+            WHERE t.excluded = 0 AND strftime('%Y-%m', t.transaction_date) = $1
+            GROUP BY t.category_id
+            "#,
+        )
+        .bind(month)
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Each expense category's spending for the month and its percentage of the total, largest
+    /// first. Categories with no spending are left out, so a month without any is empty.
+    pub async fn spend_shares(&mut self, month: &str) -> Result<Vec<CategoryShare>, RepositoryError> {
+        let records = sqlx::query_as::<_, CategoryShareRecord>(
+            r#"
+            WITH spend AS (
+                SELECT c.id, c.name, c.color, SUM(MAX(-t.amount, 0)) AS spent
+                FROM transactions t
+                JOIN categories c ON c.id = t.category_id
+                WHERE c.is_income = 0 AND t.excluded = 0 AND strftime('%Y-%m', t.transaction_date) = $1
+                GROUP BY c.id
+                HAVING spent > 0
+            )
+            SELECT id, name, color, spent, spent * 100.0 / SUM(spent) OVER () AS percent
+            FROM spend
+            ORDER BY spent DESC, name
+            "#,
+        )
+        .bind(month)
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(records.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Copies every source budget of an active category into the target month. Existing target
+    /// budgets are replaced when `overwrite` is set and left alone otherwise.
+    pub async fn copy_budgets_into(&mut self, source_month: &str, target_month: &str, overwrite: bool) -> Result<u64, RepositoryError> {
+        let on_conflict = if overwrite {
+            "DO UPDATE SET limit_amount = excluded.limit_amount, mode = excluded.mode, percent = excluded.percent"
+        } else {
+            "DO NOTHING"
+        };
+
+        let result = sqlx::query(&format!(
+            r#"
+            INSERT INTO monthly_budgets (category_id, month, limit_amount, mode, percent)
+            SELECT b.category_id, $1, b.limit_amount, b.mode, b.percent FROM monthly_budgets b
+            JOIN categories c ON c.id = b.category_id
+            WHERE b.month = $2 AND c.is_active = 1
+            ON CONFLICT(category_id, month) {}
+            "#,
+            on_conflict
+        ))
+        .bind(target_month)
+        .bind(source_month)
+        .execute(&mut *self.conn)
+        .await?;
+
         Ok(result.rows_affected())
     }
+
+    /// Summed limits and actuals per category for `from_month..=to_month`, largest overspend first.
+    /// Percent-of-income limits are resolved against each month's income. Categories with neither a
+    /// budget nor a transaction in the range are left out.
+    pub async fn budget_vs_actual(&mut self, from_month: &str, to_month: &str) -> Result<Vec<CategoryVariance>, RepositoryError> {
+        let records = sqlx::query_as::<_, CategoryVarianceRecord>(
+            r#"
+            WITH income AS (
+                SELECT strftime('%Y-%m', transaction_date) AS month, SUM(amount) AS total
+                FROM transactions
+                WHERE amount > 0 AND excluded = 0 AND strftime('%Y-%m', transaction_date) BETWEEN $1 AND $2
+                GROUP BY month
+            ),
+            limits AS (
+                SELECT b.category_id,
+                       SUM(CASE WHEN b.mode = 'percent_of_income'
+                                THEN CAST(ROUND(COALESCE(i.total, 0) * COALESCE(b.percent, 0) / 100.0) AS INTEGER)
+                                ELSE b.limit_amount END) AS total_limit
+                FROM monthly_budgets b
+                LEFT JOIN income i ON i.month = b.month
+                WHERE b.month BETWEEN $1 AND $2
+                GROUP BY b.category_id
+            ),
+            actuals AS (
+                SELECT t.category_id,
+                       SUM(CASE WHEN c.is_income THEN MAX(t.amount, 0) ELSE MAX(-t.amount, 0) END) AS total_spent
+                FROM transactions t
+                JOIN categories c ON c.id = t.category_id
+                WHERE t.excluded = 0 AND strftime('%Y-%m', t.transaction_date) BETWEEN $1 AND $2
+                GROUP BY t.category_id
+            )
+            SELECT c.id, c.name, c.color, c.is_income, c.is_active, c.budget_group, c.allows_negative_budget, c.default_note, c.classification, c.icon,
+                   COALESCE(l.total_limit, 0) AS total_limit,
+                   COALESCE(a.total_spent, 0) AS total_spent
+            FROM categories c
+            LEFT JOIN limits l ON l.category_id = c.id
+            LEFT JOIN actuals a ON a.category_id = c.id
+            WHERE l.category_id IS NOT NULL OR a.category_id IS NOT NULL
+            ORDER BY COALESCE(a.total_spent, 0) - COALESCE(l.total_limit, 0) DESC, c.name
+            "#,
+        )
+        .bind(from_month)
+        .bind(to_month)
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(records.into_iter().map(|r| r.into()).collect())
+    }
 }
 
 #[cfg(test)]
@@ -103,6 +426,7 @@ mod tests {
             color: "#000".to_string(),
             is_income: false,
             is_active: true,
+            icon: None,
         }).await.unwrap();
 
         let mut repo = MonthlyBudgetRepository::new(uow.connection());
@@ -110,6 +434,8 @@ mod tests {
             category_id: cat_id,
             month: "2026-01".to_string(),
             limit_amount: 5000,
+            mode: BudgetMode::Fixed,
+            percent: None,
         };
         
         repo.upsert(&req).await.unwrap();
@@ -123,6 +449,8 @@ mod tests {
             category_id: cat_id,
             month: "2026-01".to_string(),
             limit_amount: 7500,
+            mode: BudgetMode::Fixed,
+            percent: None,
         };
         repo.upsert(&update_req).await.unwrap();
         let budgets = repo.get_for_month("2026-01").await.unwrap();
@@ -140,6 +468,7 @@ mod tests {
             color: "#000".to_string(),
             is_income: false,
             is_active: true,
+            icon: None,
         }).await.unwrap();
 
         let mut repo = MonthlyBudgetRepository::new(uow.connection());
@@ -147,10 +476,12 @@ mod tests {
             category_id: cat_id,
             month: "2026-01".to_string(),
             limit_amount: 5000,
+            mode: BudgetMode::Fixed,
+            percent: None,
         }).await.unwrap();
 
         let affected = repo.copy_budgets("2026-01", "2026-02").await.unwrap();
-        assert_eq!(affected, 1);
+        assert_eq!(affected, (1, 0));
 
         let budgets = repo.get_for_month("2026-02").await.unwrap();
         assert_eq!(budgets.len(), 1);
@@ -1,65 +1,83 @@
-use crate::models::{CategoryBudgetView, UpdateCategoryRequest};
+use crate::models::{BudgetDiff, CategoryBudgetView, CategoryVariance, CreateCategoryRequest, DuplicateCandidate, SetActiveBulkRequest, SetCategoryGroupRequest, SetClassificationRequest, SetDefaultNoteRequest, SetNegativeBudgetRequest, UpdateCategoryRequest, YearInitialization};
 use crate::service::{CategoryError, CategoryService};
+use crate::backup::{BackupService, ConfigDocument, ConfigImportSummary};
 use axum::{
-    extract::{State, Query, Path},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Response, Redirect, Html},
-    routing::{get, post, put},
-    Form, Json, Router,
+    routing::{delete, get, post, put},
+    Extension, Form, Json, Router,
 };
-use common::AppState;
+use common::{AppState, auth::UserContext, month::{Month, ValidMonth}};
 use std::sync::Arc;
 use serde::Deserialize;
 use serde_json::json;
 
-impl IntoResponse for CategoryError {
-    fn into_response(self) -> Response {
-        let (status, msg) = match self {
-            CategoryError::InvalidInput(msg) => (StatusCode::BAD_REQUEST, msg),
-            CategoryError::Conflict(msg) => (StatusCode::CONFLICT, msg),
-            CategoryError::NotFound => (StatusCode::NOT_FOUND, "Category not found".to_string()),
-            CategoryError::Infrastructure(_) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Internal server error".to_string(),
-            ),
-        };
-        
-        (status, Json(json!({ "error": msg }))).into_response()
-    }
-}
-
 use askama::Template;
 
 #[derive(Template)]
 #[template(path = "manage_categories.html")]
 pub struct ManageCategoriesTemplate {
+    pub base_path: String,
     pub categories: Vec<crate::models::Category>,
     pub pastel_colors: Vec<String>,
+    pub error: Option<String>,
 }
 
 pub fn categories_router(state: Arc<AppState>) -> Router<Arc<AppState>> {
     Router::new()
         .route("/", get(list_categories_view).post(create_category))
+        // Kept for existing clients; `/` gives the same JSON to `Accept: application/json`
         .route("/api", get(list_categories_api))
+        .route("/active", post(set_active_bulk))
+        .route("/duplicates", get(find_duplicates))
         .route("/{id}", put(update_category).delete(delete_category))
+        .route("/{id}/group", put(set_category_group))
+        .route("/{id}/negative-budget", put(set_allows_negative_budget))
+        .route("/{id}/default-note", put(set_default_note))
+        .route("/{id}/classification", put(set_classification))
+        .route("/{id}/limit", delete(delete_limit))
         .route("/budget", get(get_budget_view))
+        .route("/with-budget", get(get_categories_with_budget))
         .route("/limit", post(set_limit))
+        .route("/limit/percent", post(set_limit_percent))
+        .route("/budget/copy-range", post(copy_budgets_range))
+        .route("/init-year", post(initialize_year))
+        .route("/variance", get(budget_vs_actual))
+        .route("/budget-diff", get(budget_diff))
+        .with_state(state)
+}
+
+/// Category and budget configuration export/import, nested under `/config`.
+pub fn config_router(state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/export.json", get(export_config))
+        .route("/import", post(import_config))
         .with_state(state)
 }
 
+/// The management page, or the category list as JSON when the client asks for `application/json`.
 async fn list_categories_view(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, CategoryError> {
+    headers: HeaderMap,
+) -> Result<Response, CategoryError> {
+    let wants_json = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/json"));
+
+    if wants_json {
+        return Ok(list_categories_api(State(state)).await?.into_response());
+    }
+    Ok(Html(render_manage_page(&state, None).await?).into_response())
+}
+
+async fn render_manage_page(state: &AppState, error: Option<String>) -> Result<String, CategoryError> {
     let categories = CategoryService::list_categories(&state.db).await?;
-    let pastel_colors = vec![
-        "#FFB3BA", "#FFDFBA", "#FFFFBA", "#BAFFC9", "#BAE1FF", 
-        "#E2F0CB", "#FDFD96", "#FFC3A0", "#FFD1DC", "#D4F0F0",
-        "#CCE2CB", "#B6CFB6", "#97C1A9", "#FCB7AF", "#FFDAC1",
-        "#E7FFAC", "#FFABAB", "#D5AAFF", "#85E3FF", "#B9F6CA"
-    ].into_iter().map(|s| s.to_string()).collect();
+    let pastel_colors = state.config.palette().colors().to_vec();
 
-    let template = ManageCategoriesTemplate { categories, pastel_colors };
-    Ok(Html(template.render().map_err(|e| CategoryError::Infrastructure(e.to_string()))?))
+    let template = ManageCategoriesTemplate { base_path: state.config.base_path.clone(), categories, pastel_colors, error };
+    template.render().map_err(|e| CategoryError::Infrastructure(e.to_string()))
 }
 
 async fn list_categories_api(
@@ -74,61 +92,135 @@ pub struct CreateCategoryForm {
     pub name: String,
     pub monthly_limit: f64,
     pub is_income: Option<String>,
+    /// Blank or missing picks a random pastel.
+    pub color: Option<String>,
+    /// Blank or missing means no icon.
+    pub icon: Option<String>,
 }
 
+/// Browser form posts get the management page back with the error inline (keeping the error's
+/// status code); API clients get the usual JSON error.
 async fn create_category(
     State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    headers: HeaderMap,
     Form(payload): Form<CreateCategoryForm>,
-) -> Result<impl IntoResponse, CategoryError> {
+) -> Result<Response, CategoryError> {
+    match create_category_with_limit(&state, ctx.as_deref(), payload).await {
+        Ok(()) => Ok(Redirect::to(&state.config.url_for("/")).into_response()),
+        Err(e) if common::errors::wants_html(&headers) && !matches!(e, CategoryError::Infrastructure(_)) => {
+            let (status, msg) = e.status_and_message();
+            Ok((status, Html(render_manage_page(&state, Some(msg)).await?)).into_response())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn create_category_with_limit(state: &AppState, ctx: Option<&UserContext>, payload: CreateCategoryForm) -> Result<(), CategoryError> {
     let is_income = payload.is_income.as_deref() == Some("on");
     
-    let id = CategoryService::create_category(
-        &state.db, 
-        payload.name,
-        is_income,
-    ).await?;
-    
-    // Set the initial limit for the current month
-    let now = chrono::Local::now();
-    let month = now.format("%Y-%m").to_string();
-    
-    CategoryService::set_monthly_limit(
+    let color = payload.color
+        .filter(|c| !c.trim().is_empty())
+        .unwrap_or_else(|| CategoryService::get_random_pastel_color(&state.config.palette()));
+    let mut req = CreateCategoryRequest::new(payload.name, color, is_income)
+        .map_err(CategoryError::InvalidInput)?;
+    req.icon = payload.icon;
+
+    // The initial limit is for the current month
+    CategoryService::create_category_with_limit(
+        ctx,
         &state.db,
-        id,
-        month,
-        payload.monthly_limit
+        req,
+        &Month::current().to_string(),
+        payload.monthly_limit,
+        state.config.max_categories,
+        state.config.rounding_mode,
     ).await?;
-    
-    Ok(Redirect::to("/")) 
+    Ok(())
 }
 
 async fn update_category(
     State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
     Path(id): Path<i64>,
     Json(payload): Json<UpdateCategoryRequest>,
 ) -> Result<impl IntoResponse, CategoryError> {
-    CategoryService::update_category(&state.db, id, payload.name, payload.color, payload.is_income, payload.is_active).await?;
+    CategoryService::update_category(ctx.as_deref(), &state.db, id, payload).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn set_category_group(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Path(id): Path<i64>,
+    Json(payload): Json<SetCategoryGroupRequest>,
+) -> Result<impl IntoResponse, CategoryError> {
+    CategoryService::set_category_group(ctx.as_deref(), &state.db, id, payload.group).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn set_active_bulk(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Json(payload): Json<SetActiveBulkRequest>,
+) -> Result<impl IntoResponse, CategoryError> {
+    let updated = CategoryService::set_active_bulk(ctx.as_deref(), &state.db, &payload.ids, payload.active).await?;
+    Ok(Json(json!({ "updated": updated })))
+}
+
+async fn set_allows_negative_budget(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Path(id): Path<i64>,
+    Json(payload): Json<SetNegativeBudgetRequest>,
+) -> Result<impl IntoResponse, CategoryError> {
+    CategoryService::set_allows_negative_budget(ctx.as_deref(), &state.db, id, payload.allowed).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn set_classification(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Path(id): Path<i64>,
+    Json(payload): Json<SetClassificationRequest>,
+) -> Result<impl IntoResponse, CategoryError> {
+    CategoryService::set_classification(ctx.as_deref(), &state.db, id, payload.classification).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn set_default_note(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Path(id): Path<i64>,
+    Json(payload): Json<SetDefaultNoteRequest>,
+) -> Result<impl IntoResponse, CategoryError> {
+    CategoryService::set_default_note(ctx.as_deref(), &state.db, id, payload.note).await?;
     Ok(StatusCode::OK)
 }
 
 async fn delete_category(
     State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
     Path(id): Path<i64>,
 ) -> Result<impl IntoResponse, CategoryError> {
-    CategoryService::delete_category(&state.db, id).await?;
+    CategoryService::delete_category(ctx.as_deref(), &state.db, id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
-#[derive(Deserialize)]
-struct BudgetQuery {
-    month: String,
+async fn get_budget_view(
+    State(state): State<Arc<AppState>>,
+    ValidMonth(month): ValidMonth,
+) -> Result<Json<Vec<CategoryBudgetView>>, CategoryError> {
+    let view = CategoryService::get_budget_view(&state.db, &month.to_string()).await?;
+    Ok(Json(view))
 }
 
-async fn get_budget_view(
+/// Each category with its limit, spent and remaining for the month, so the page needs one request.
+async fn get_categories_with_budget(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<BudgetQuery>,
+    ValidMonth(month): ValidMonth,
 ) -> Result<Json<Vec<CategoryBudgetView>>, CategoryError> {
-    let view = CategoryService::get_budget_view(&state.db, &params.month).await?;
+    let view = CategoryService::budget_view_with_actuals(&state.db, &month.to_string()).await?;
     Ok(Json(view))
 }
 
@@ -141,13 +233,267 @@ struct SetLimitRequest {
 
 async fn set_limit(
     State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
     Json(payload): Json<SetLimitRequest>,
 ) -> Result<impl IntoResponse, CategoryError> {
     CategoryService::set_monthly_limit(
+        ctx.as_deref(),
         &state.db,
         payload.category_id,
         payload.month,
         payload.limit,
+        state.config.rounding_mode,
     ).await?;
     Ok(StatusCode::OK)
 }
+
+#[derive(Deserialize)]
+struct DeleteLimitQuery {
+    month: String,
+}
+
+async fn delete_limit(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Path(id): Path<i64>,
+    Query(query): Query<DeleteLimitQuery>,
+) -> Result<impl IntoResponse, CategoryError> {
+    CategoryService::delete_monthly_limit(ctx.as_deref(), &state.db, id, &query.month).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct SetLimitPercentRequest {
+    category_id: i64,
+    month: String,
+    percent: f64,
+}
+
+async fn set_limit_percent(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Json(payload): Json<SetLimitPercentRequest>,
+) -> Result<impl IntoResponse, CategoryError> {
+    CategoryService::set_monthly_percent_of_income(
+        ctx.as_deref(),
+        &state.db,
+        payload.category_id,
+        payload.month,
+        payload.percent,
+    ).await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+struct CopyBudgetsRangeRequest {
+    source_month: String,
+    from_month: String,
+    to_month: String,
+    #[serde(default)]
+    overwrite: bool,
+}
+
+async fn copy_budgets_range(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Json(payload): Json<CopyBudgetsRangeRequest>,
+) -> Result<impl IntoResponse, CategoryError> {
+    let written = CategoryService::copy_budgets_range(
+        ctx.as_deref(),
+        &state.db,
+        &payload.source_month,
+        &payload.from_month,
+        &payload.to_month,
+        payload.overwrite,
+    ).await?;
+    Ok(Json(json!({ "written": written })))
+}
+
+#[derive(Deserialize)]
+struct InitializeYearRequest {
+    year: i32,
+    template_month: String,
+}
+
+async fn initialize_year(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Json(payload): Json<InitializeYearRequest>,
+) -> Result<Json<YearInitialization>, CategoryError> {
+    Ok(Json(CategoryService::initialize_year(ctx.as_deref(), &state.db, payload.year, &payload.template_month).await?))
+}
+
+#[derive(Deserialize)]
+struct VarianceQuery {
+    from: String,
+    to: String,
+}
+
+async fn find_duplicates(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<DuplicateCandidate>>, CategoryError> {
+    Ok(Json(CategoryService::find_duplicates(&state.db).await?))
+}
+
+async fn budget_vs_actual(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<VarianceQuery>,
+) -> Result<Json<Vec<CategoryVariance>>, CategoryError> {
+    let rows = CategoryService::budget_vs_actual(&state.db, &query.from, &query.to).await?;
+    Ok(Json(rows))
+}
+
+#[derive(Deserialize)]
+struct BudgetDiffQuery {
+    a: String,
+    b: String,
+}
+
+async fn budget_diff(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<BudgetDiffQuery>,
+) -> Result<Json<Vec<BudgetDiff>>, CategoryError> {
+    Ok(Json(CategoryService::budget_diff(&state.db, &query.a, &query.b).await?))
+}
+
+/// Month the config is exported from or imported into; defaults to the current month.
+#[derive(Deserialize)]
+struct ConfigMonthQuery {
+    month: Option<String>,
+}
+
+impl ConfigMonthQuery {
+    fn month(self) -> String {
+        self.month.unwrap_or_else(|| Month::current().to_string())
+    }
+}
+
+async fn export_config(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ConfigMonthQuery>,
+) -> Result<Json<ConfigDocument>, CategoryError> {
+    Ok(Json(BackupService::export_config(&state.db, &query.month()).await?))
+}
+
+async fn import_config(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Query(query): Query<ConfigMonthQuery>,
+    Json(doc): Json<ConfigDocument>,
+) -> Result<Json<ConfigImportSummary>, CategoryError> {
+    Ok(Json(BackupService::import_config(ctx.as_deref(), &state.db, &doc, &query.month(), state.config.max_categories, &state.config.palette()).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::{header, Request}};
+    use common::{money::RoundingMode, Config};
+    use database::{get_test_db, TestDb};
+    use tower::ServiceExt;
+
+    async fn app() -> (Router, Arc<AppState>, TestDb) {
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(db.clone(), Config::default()));
+        (categories_router(state.clone()).with_state(state.clone()), state, db)
+    }
+
+    fn create_request(accept: &str) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header(header::ACCEPT, accept)
+            .body(Body::from("name=Gym&monthly_limit=0"))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_category_form_post_renders_html_error() {
+        let (app, state, _db) = app().await;
+        CategoryService::create_category(None, &state.db, "Gym".to_string(), false, None).await.unwrap();
+
+        let response = app.oneshot(create_request("text/html")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert!(response.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/html"));
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+        assert!(html.contains("Master Categories"));
+        assert!(html.contains("alert-danger"));
+        assert!(html.contains("already exists"));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_category_api_post_returns_json_error() {
+        let (app, state, _db) = app().await;
+        CategoryService::create_category(None, &state.db, "Gym".to_string(), false, None).await.unwrap();
+
+        let response = app.oneshot(create_request("application/json")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(json["error"].as_str().unwrap().contains("already exists"));
+    }
+
+    #[tokio::test]
+    async fn test_categories_with_budget_combines_limit_and_actuals() {
+        let (app, state, _db) = app().await;
+        let gym = CategoryService::create_category(None, &state.db, "Gym".to_string(), false, None).await.unwrap();
+        let travel = CategoryService::create_category(None, &state.db, "Travel".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_limit(None, &state.db, gym, "2026-01".to_string(), 60.0, RoundingMode::HalfUp).await.unwrap();
+        for (id, cents) in [(gym, -4500), (travel, -12000)] {
+            sqlx::query("INSERT INTO transactions (category_id, transaction_date, amount) VALUES ($1, '2026-01-15', $2)")
+                .bind(id)
+                .bind(cents)
+                .execute(&state.db.pool)
+                .await
+                .unwrap();
+        }
+
+        let request = Request::builder().uri("/with-budget?month=2026-01").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let find = |id: i64| json.as_array().unwrap().iter().find(|v| v["category"]["id"] == id).unwrap().clone();
+
+        let gym = find(gym);
+        assert_eq!(gym["budget"]["limit_amount"], 6000);
+        assert_eq!(gym["spent"], 4500);
+        assert_eq!(gym["remaining"], 1500);
+
+        let travel = find(travel);
+        assert!(travel["budget"].is_null());
+        assert_eq!(travel["spent"], 12000);
+        assert_eq!(travel["remaining"], -12000);
+    }
+
+    async fn list_with_accept(app: Router, accept: &str) -> (StatusCode, Option<String>, String) {
+        let request = Request::builder().uri("/").header(header::ACCEPT, accept).body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let content_type = response.headers().get(header::CONTENT_TYPE).map(|v| v.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, content_type, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_categories_list_negotiates_on_accept() {
+        let (app, state, _db) = app().await;
+        CategoryService::create_category(None, &state.db, "Gym".to_string(), false, None).await.unwrap();
+
+        let (status, content_type, body) = list_with_accept(app.clone(), "application/json").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(content_type.unwrap().starts_with("application/json"));
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert!(json.as_array().unwrap().iter().any(|c| c["name"] == "Gym"));
+
+        let (status, content_type, body) = list_with_accept(app, "text/html,application/xhtml+xml").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(content_type.unwrap().starts_with("text/html"));
+        assert!(body.contains("<html") && body.contains("Gym"));
+    }
+}
@@ -1,6 +1,8 @@
 pub mod models;
 mod repository;
 mod budget_repository;
+mod month_note_repository;
 pub mod service;
+pub mod backup;
 pub mod handler;
 pub mod virtual_budget;
\ No newline at end of file
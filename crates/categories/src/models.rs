@@ -1,5 +1,10 @@
+use common::money::RoundingMode;
+use common::month::Month;
 use serde::{Deserialize, Serialize};
 
+/// Name of the seeded category that holds transactions awaiting categorization.
+pub const UNCATEGORIZED_CATEGORY_NAME: &str = "Uncategorized";
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Category {
     pub id: i64,
@@ -7,6 +12,51 @@ pub struct Category {
     pub color: String,
     pub is_income: bool,
     pub is_active: bool,
+    #[serde(default)]
+    pub group: Option<String>, // Free-form budget group, e.g. "Needs"
+    #[serde(default)]
+    pub allows_negative_budget: bool, // Debt paydown: a negative limit is a target to pay at least
+    #[serde(default)]
+    pub default_note: Option<String>, // Pre-fills the note of new transactions; never applied server-side
+    #[serde(default)]
+    pub classification: Classification,
+    #[serde(default)]
+    pub icon: Option<String>, // Emoji shown before the name
+}
+
+/// Where a category's spending goes in the needs/wants/savings ratio report.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Classification {
+    Need,
+    Want,
+    Saving,
+    /// Left out of the report. The default, so nothing is counted until it is classified.
+    #[default]
+    Ignore,
+}
+
+impl Classification {
+    /// The classes the ratio report covers, in report order.
+    pub const REPORTED: [Classification; 3] = [Classification::Need, Classification::Want, Classification::Saving];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Classification::Need => "need",
+            Classification::Want => "want",
+            Classification::Saving => "saving",
+            Classification::Ignore => "ignore",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "need" => Classification::Need,
+            "want" => Classification::Want,
+            "saving" => Classification::Saving,
+            _ => Classification::Ignore,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -15,6 +65,7 @@ pub struct CreateCategoryRequest {
     pub color: String,
     pub is_income: bool,
     pub is_active: bool,
+    pub icon: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -23,35 +74,209 @@ pub struct RawCreateCategoryRequest {
     pub is_income: bool,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Deserialize)]
 pub struct UpdateCategoryRequest {
     pub name: String,
     pub color: Option<String>,
     pub is_income: bool,
     pub is_active: bool,
+    /// Missing leaves the icon alone; blank clears it.
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SetCategoryGroupRequest {
+    pub group: Option<String>,
+}
+
+/// Activates or archives several categories at once.
+#[derive(Deserialize)]
+pub struct SetActiveBulkRequest {
+    pub ids: Vec<i64>,
+    pub active: bool,
+}
+
+#[derive(Deserialize)]
+pub struct SetNegativeBudgetRequest {
+    pub allowed: bool,
+}
+
+#[derive(Deserialize)]
+pub struct SetClassificationRequest {
+    pub classification: Classification,
+}
+
+/// Longest category default note accepted, in characters.
+pub const MAX_DEFAULT_NOTE_LEN: usize = 200;
+
+/// A blank or missing note clears it.
+#[derive(Deserialize)]
+pub struct SetDefaultNoteRequest {
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Longest month note accepted, in characters.
+pub const MAX_MONTH_NOTE_LEN: usize = 500;
+
+/// Free-text memo for a whole month.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct MonthNote {
+    pub month: String, // YYYY-MM
+    pub text: String,
+}
+
+#[derive(Deserialize)]
+pub struct SetMonthNoteRequest {
+    pub text: String,
+}
+
+/// Trims and collapses internal runs of whitespace so "  Fast   Food " becomes "Fast Food".
+pub fn normalize_category_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// A category and how many transactions use it.
+#[derive(Debug, Serialize, Clone)]
+pub struct CategoryUsage {
+    pub category: Category,
+    pub transaction_count: i64,
+}
+
+/// Two categories whose names differ only by case, spacing or one typo. `keep` is the one with more
+/// transactions (the older one on a tie), the suggested target when merging `merge` into it.
+#[derive(Debug, Serialize, Clone)]
+pub struct DuplicateCandidate {
+    pub keep: CategoryUsage,
+    pub merge: CategoryUsage,
+    pub distance: usize, // Edits between the normalized names; 0 when only case or spacing differ
+}
+
+/// Most edits between two normalized names for them to count as duplicates.
+pub const MAX_DUPLICATE_DISTANCE: usize = 1;
+
+/// Every pair of categories whose names are within `MAX_DUPLICATE_DISTANCE` edits once
+/// whitespace-normalized and lowercased, closest pairs first.
+pub fn duplicate_candidates(usages: &[CategoryUsage]) -> Vec<DuplicateCandidate> {
+    let keys: Vec<Vec<char>> = usages.iter()
+        .map(|u| normalize_category_name(&u.category.name).to_lowercase().chars().collect())
+        .collect();
+
+    let mut candidates = Vec::new();
+    for i in 0..usages.len() {
+        for j in (i + 1)..usages.len() {
+            let distance = edit_distance(&keys[i], &keys[j]);
+            if distance > MAX_DUPLICATE_DISTANCE {
+                continue;
+            }
+
+            let (a, b) = (&usages[i], &usages[j]);
+            let a_keeps = a.transaction_count > b.transaction_count
+                || (a.transaction_count == b.transaction_count && a.category.id < b.category.id);
+            let (keep, merge) = if a_keeps { (a, b) } else { (b, a) };
+            candidates.push(DuplicateCandidate { keep: keep.clone(), merge: merge.clone(), distance });
+        }
+    }
+
+    candidates.sort_by_key(|c| (c.distance, c.keep.category.id, c.merge.category.id));
+    candidates
+}
+
+/// Levenshtein distance: the fewest single-character insertions, deletions or substitutions.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Longest category icon accepted, in characters. Enough for one emoji including skin tone and
+/// joiner sequences, not for a word.
+pub const MAX_ICON_CHARS: usize = 8;
+
+/// Trims an icon; blank means no icon. Errors when it is too long or has spaces inside, since an
+/// icon is a single emoji or symbol rather than text.
+pub fn normalize_icon(icon: Option<&str>) -> Result<Option<String>, String> {
+    let Some(icon) = icon.map(str::trim).filter(|i| !i.is_empty()) else {
+        return Ok(None);
+    };
+    if icon.chars().count() > MAX_ICON_CHARS || icon.chars().any(char::is_whitespace) {
+        return Err(format!("Icon must be a single emoji or symbol, got {:?}", icon));
+    }
+    Ok(Some(icon.to_string()))
+}
+
+/// Normalizes a group name like a category name; blank means no group.
+pub fn normalize_group(group: Option<&str>) -> Option<String> {
+    group.map(normalize_category_name).filter(|g| !g.is_empty())
 }
 
 impl CreateCategoryRequest {
     pub fn new(name: String, color: String, is_income: bool) -> Result<Self, String> {
-        if name.trim().is_empty() {
+        let name = normalize_category_name(&name);
+        if name.is_empty() {
             return Err("Category name cannot be empty".to_string());
         }
         
         Ok(Self {
-            name: name.trim().to_string(),
+            name,
             color,
             is_income,
             is_active: true,
+            icon: None,
         })
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetMode {
+    #[default]
+    Fixed,
+    PercentOfIncome,
+}
+
+impl BudgetMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetMode::Fixed => "fixed",
+            BudgetMode::PercentOfIncome => "percent_of_income",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "percent_of_income" => BudgetMode::PercentOfIncome,
+            _ => BudgetMode::Fixed,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MonthlyBudget {
     pub id: i64,
     pub category_id: i64,
     pub month: String, // YYYY-MM
-    pub limit_amount: i64, // Cents
+    pub limit_amount: i64, // Cents; for percent budgets, the effective limit once resolved
+    pub mode: BudgetMode,
+    pub percent: Option<f64>, // Percent of income, for PercentOfIncome budgets
+}
+
+impl MonthlyBudget {
+    /// Resolves a percent-of-income budget's limit from the month's income (in cents).
+    pub fn resolve_limit(&mut self, month_income: i64) {
+        if self.mode == BudgetMode::PercentOfIncome {
+            let percent = self.percent.unwrap_or(0.0);
+            self.limit_amount = (month_income as f64 * percent / 100.0).round() as i64;
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -59,23 +284,42 @@ pub struct CreateMonthlyBudgetRequest {
     pub category_id: i64,
     pub month: String,
     pub limit_amount: i64,
+    pub mode: BudgetMode,
+    pub percent: Option<f64>,
 }
 
 impl CreateMonthlyBudgetRequest {
-    pub fn new(category_id: i64, month: String, limit_dollars: f64) -> Result<Self, String> {
-        if limit_dollars < 0.0 {
+    /// A fixed limit, rounded to cents per `rounding`. Negative limits are only accepted when
+    /// `allow_negative` is set, i.e. for categories flagged `allows_negative_budget`.
+    pub fn new(category_id: i64, month: String, limit_dollars: f64, allow_negative: bool, rounding: RoundingMode) -> Result<Self, String> {
+        if limit_dollars < 0.0 && !allow_negative {
             return Err("Limit cannot be negative".to_string());
         }
         
-        // Basic month format validation (YYYY-MM)
-        if month.len() != 7 || month.chars().nth(4) != Some('-') {
-             return Err("Invalid month format. Expected YYYY-MM".to_string());
+        month.parse::<Month>()?;
+
+        Ok(Self {
+            category_id,
+            month,
+            limit_amount: common::money::to_cents(limit_dollars, rounding),
+            mode: BudgetMode::Fixed,
+            percent: None,
+        })
+    }
+
+    pub fn percent_of_income(category_id: i64, month: String, percent: f64) -> Result<Self, String> {
+        if !(0.0..=100.0).contains(&percent) {
+            return Err("Percent of income must be between 0 and 100".to_string());
         }
 
+        month.parse::<Month>()?;
+
         Ok(Self {
             category_id,
             month,
-            limit_amount: (limit_dollars * 100.0).round() as i64,
+            limit_amount: 0,
+            mode: BudgetMode::PercentOfIncome,
+            percent: Some(percent),
         })
     }
 }
@@ -89,6 +333,221 @@ pub struct CategoryBudgetView {
     pub remaining: i64,
 }
 
+impl CategoryBudgetView {
+    /// Fills in `spent` and `remaining` from the category's actual for the month (in cents).
+    /// Income categories are ahead when actual exceeds the limit, expenses when it stays under.
+    pub fn set_actual(&mut self, actual: i64) {
+        self.spent = actual;
+        self.remaining = self.remaining_for(actual);
+    }
+
+    /// How far `actual` is on the good side of the limit, in cents; negative when over (or short).
+    /// Income categories and negative paydown targets are ahead when the actual reaches the target,
+    /// expenses when it stays under the limit. Saturates rather than overflowing on corrupt amounts.
+    pub fn remaining_for(&self, actual: i64) -> i64 {
+        let limit = self.budget.as_ref().map(|b| b.limit_amount).unwrap_or(0);
+        if self.category.is_income {
+            actual.saturating_sub(limit)
+        } else if limit < 0 {
+            // Paying down at least |limit| meets the target
+            actual.saturating_add(limit)
+        } else {
+            limit.saturating_sub(actual)
+        }
+    }
+}
+
+/// Budget rows sharing a category group, with subtotals in cents.
+#[derive(Debug, Serialize, Clone)]
+pub struct BudgetGroupView {
+    pub name: Option<String>, // None for ungrouped categories
+    pub categories: Vec<CategoryBudgetView>,
+    pub total_limit: i64,
+    pub total_spent: i64,
+    pub total_remaining: i64,
+}
+
+impl BudgetGroupView {
+    /// Nests views under their group, named groups alphabetically and ungrouped last, keeping
+    /// the incoming order within each group.
+    pub fn group(views: Vec<CategoryBudgetView>) -> Vec<BudgetGroupView> {
+        let mut groups: Vec<BudgetGroupView> = Vec::new();
+        for view in views {
+            let name = view.category.group.clone();
+            let index = match groups.iter().position(|g| g.name == name) {
+                Some(index) => index,
+                None => {
+                    groups.push(BudgetGroupView { name, categories: Vec::new(), total_limit: 0, total_spent: 0, total_remaining: 0 });
+                    groups.len() - 1
+                }
+            };
+
+            let group = &mut groups[index];
+            group.total_limit += view.budget.as_ref().map(|b| b.limit_amount).unwrap_or(0);
+            group.total_spent += view.spent;
+            group.total_remaining += view.remaining;
+            group.categories.push(view);
+        }
+
+        groups.sort_by(|a, b| match (&a.name, &b.name) {
+            (Some(a), Some(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+        groups
+    }
+}
+
+/// Result of filling a year's months from a template month. Months that already had budgets are
+/// left alone and listed as skipped.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct YearInitialization {
+    pub year: i32,
+    pub template_month: String,
+    pub initialized: Vec<String>, // YYYY-MM
+    pub skipped: Vec<String>,
+    pub written: u64, // Budget rows created across all months
+}
+
+/// Budget against actual for one category, summed over a range of months. All amounts in cents;
+/// for income categories "spent" is what was received.
+#[derive(Debug, Serialize, Clone)]
+pub struct CategoryVariance {
+    pub category: Category,
+    pub total_limit: i64,
+    pub total_spent: i64,
+    pub variance: i64, // total_limit - total_spent; negative when over
+}
+
+/// Target share of income per class, in percent, e.g. 50/30/20. Normally from `Config`.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub struct ClassificationTargets {
+    pub need: u32,
+    pub want: u32,
+    pub saving: u32,
+}
+
+impl ClassificationTargets {
+    pub fn percent_for(&self, classification: Classification) -> Option<u32> {
+        match classification {
+            Classification::Need => Some(self.need),
+            Classification::Want => Some(self.want),
+            Classification::Saving => Some(self.saving),
+            Classification::Ignore => None,
+        }
+    }
+}
+
+/// Spending in one class against its target. Percentages are of the month's income.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ClassificationShare {
+    pub classification: Classification,
+    pub spent: i64,               // Cents
+    pub percent: Option<f64>,     // None when the month has no income
+    pub target_percent: u32,
+    pub difference: Option<f64>,  // percent - target_percent; positive when above target
+}
+
+/// A month's needs/wants/savings split. Income categories and `Ignore` categories are left out.
+#[derive(Debug, Serialize, Clone)]
+pub struct ClassificationReport {
+    pub month: String,
+    pub income: i64, // Cents, the base of every percentage
+    pub classes: Vec<ClassificationShare>, // Need, Want, Saving
+}
+
+/// A month's plan: income categories' targets against expense categories' limits, flagging a
+/// plan that spends more than it expects to earn.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct BudgetBalance {
+    pub month: String,
+    pub planned_income: i64,   // Cents
+    pub planned_expenses: i64, // Cents
+    pub gap: i64,              // Cents by which planned expenses exceed planned income; 0 when they don't
+    pub over_budget: bool,
+}
+
+impl BudgetBalance {
+    /// Sums the budgets in `views` (with percent-of-income limits already resolved). Categories
+    /// without a budget plan nothing; negative paydown targets are not planned spending.
+    pub fn from_views(month: &str, views: &[CategoryBudgetView]) -> Self {
+        let limit = |v: &CategoryBudgetView| v.budget.as_ref().map(|b| b.limit_amount).unwrap_or(0);
+        let planned_income: i64 = views.iter().filter(|v| v.category.is_income).map(limit).sum();
+        let planned_expenses: i64 = views.iter().filter(|v| !v.category.is_income).map(|v| limit(v).max(0)).sum();
+        let gap = (planned_expenses - planned_income).max(0);
+
+        BudgetBalance { month: month.to_string(), planned_income, planned_expenses, gap, over_budget: gap > 0 }
+    }
+}
+
+/// How a category's budget moved from one month to another.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetChange {
+    Added,   // Only the later month has a budget
+    Removed, // Only the earlier month has a budget
+    Changed,
+}
+
+/// One category's budget in two months, in cents. Categories budgeted identically in both, or in
+/// neither, are not listed.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct BudgetDiff {
+    pub category_id: i64,
+    pub name: String,
+    pub limit_a: Option<i64>, // None without a budget in month a
+    pub limit_b: Option<i64>,
+    pub delta: i64, // limit_b - limit_a, a missing budget counting as 0
+    pub change: BudgetChange,
+}
+
+impl BudgetDiff {
+    /// Compares the budgets in two months' views (percent-of-income limits already resolved),
+    /// largest change first and then by name.
+    pub fn between(views_a: &[CategoryBudgetView], views_b: &[CategoryBudgetView]) -> Vec<Self> {
+        let limit = |views: &[CategoryBudgetView], id: i64| views.iter()
+            .find(|v| v.category.id == id)
+            .and_then(|v| v.budget.as_ref())
+            .map(|b| b.limit_amount);
+
+        let mut diffs: Vec<Self> = views_b.iter()
+            .map(|v| &v.category)
+            .chain(views_a.iter().map(|v| &v.category).filter(|c| !views_b.iter().any(|v| v.category.id == c.id)))
+            .filter_map(|category| {
+                let (limit_a, limit_b) = (limit(views_a, category.id), limit(views_b, category.id));
+                let change = match (limit_a, limit_b) {
+                    (None, Some(_)) => BudgetChange::Added,
+                    (Some(_), None) => BudgetChange::Removed,
+                    (Some(a), Some(b)) if a != b => BudgetChange::Changed,
+                    _ => return None,
+                };
+                Some(BudgetDiff {
+                    category_id: category.id,
+                    name: category.name.clone(),
+                    limit_a,
+                    limit_b,
+                    delta: limit_b.unwrap_or(0) - limit_a.unwrap_or(0),
+                    change,
+                })
+            })
+            .collect();
+
+        diffs.sort_by(|x, y| y.delta.abs().cmp(&x.delta.abs()).then_with(|| x.name.cmp(&y.name)));
+        diffs
+    }
+}
+
+/// An expense category's share of the month's total spending, for pie charts.
+#[derive(Debug, Serialize, Clone)]
+pub struct CategoryShare {
+    pub category_id: i64,
+    pub name: String,
+    pub color: String,
+    pub spent: i64,   // Cents
+    pub percent: f64, // Of all expense-category spending in the month, 0-100
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,8 +559,88 @@ mod tests {
         assert_eq!(req.color, "#ffffff");
     }
 
+    #[test]
+    fn test_create_category_request_normalizes_whitespace() {
+        let req = CreateCategoryRequest::new("  Fast   Food ".to_string(), "#ffffff".to_string(), false).unwrap();
+        assert_eq!(req.name, "Fast Food");
+    }
+
+    #[test]
+    fn test_create_monthly_budget_request_month_validation() {
+        assert!(CreateMonthlyBudgetRequest::new(1, "2026-01".to_string(), 10.0, false, RoundingMode::HalfUp).is_ok());
+        assert!(CreateMonthlyBudgetRequest::new(1, "2026-13".to_string(), 10.0, false, RoundingMode::HalfUp).is_err());
+        assert!(CreateMonthlyBudgetRequest::new(1, "2026-1".to_string(), 10.0, false, RoundingMode::HalfUp).is_err());
+    }
+
+    #[test]
+    fn test_create_monthly_budget_request_uses_given_rounding() {
+        let limit = |mode| CreateMonthlyBudgetRequest::new(1, "2026-01".to_string(), 10.005, false, mode).unwrap().limit_amount;
+        assert_eq!(limit(RoundingMode::HalfUp), 1001);
+        assert_eq!(limit(RoundingMode::HalfEven), 1000);
+        assert_eq!(limit(RoundingMode::Down), 1000);
+    }
+
+    #[test]
+    fn test_percent_of_income_budget_resolves_limit() {
+        let req = CreateMonthlyBudgetRequest::percent_of_income(1, "2026-01".to_string(), 20.0).unwrap();
+        let mut budget = MonthlyBudget {
+            id: 1,
+            category_id: req.category_id,
+            month: req.month,
+            limit_amount: req.limit_amount,
+            mode: req.mode,
+            percent: req.percent,
+        };
+
+        budget.resolve_limit(500000);
+        assert_eq!(budget.limit_amount, 100000);
+
+        assert!(CreateMonthlyBudgetRequest::percent_of_income(1, "2026-01".to_string(), 120.0).is_err());
+    }
+
     #[test]
     fn test_create_category_request_empty() {
         assert!(CreateCategoryRequest::new("   ".to_string(), "#ffffff".to_string(), false).is_err());
     }
-}
\ No newline at end of file
+
+    fn usage(id: i64, name: &str, transaction_count: i64) -> CategoryUsage {
+        CategoryUsage {
+            category: Category {
+                id,
+                name: name.to_string(),
+                color: "#ffffff".to_string(),
+                is_income: false,
+                is_active: true,
+                group: None,
+                allows_negative_budget: false,
+                default_note: None,
+                classification: Classification::Ignore,
+                icon: None,
+            },
+            transaction_count,
+        }
+    }
+
+    #[test]
+    fn test_duplicate_candidates_groups_similar_names() {
+        let usages = vec![usage(1, "Food", 3), usage(2, "food ", 7), usage(3, "Foood", 0), usage(4, "Fuel", 2)];
+
+        let candidates = duplicate_candidates(&usages);
+        let pairs: Vec<(i64, i64, usize)> = candidates.iter()
+            .map(|c| (c.keep.category.id, c.merge.category.id, c.distance))
+            .collect();
+
+        // "Food" and "food " only differ by case and spacing; the busier one is kept
+        assert_eq!(pairs, vec![(2, 1, 0), (1, 3, 1), (2, 3, 1)]);
+        assert_eq!(candidates[0].keep.transaction_count, 7);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(edit_distance(&chars("food"), &chars("food")), 0);
+        assert_eq!(edit_distance(&chars("food"), &chars("foood")), 1);
+        assert_eq!(edit_distance(&chars("food"), &chars("fuel")), 3);
+        assert_eq!(edit_distance(&chars(""), &chars("gym")), 3);
+    }
+}
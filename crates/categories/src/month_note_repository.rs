@@ -0,0 +1,58 @@
+use crate::models::MonthNote;
+use database::{self, RepositoryError};
+use sqlx::FromRow;
+
+#[derive(FromRow)]
+struct MonthNoteRecord {
+    month: String,
+    text: String,
+}
+
+impl From<MonthNoteRecord> for MonthNote {
+    fn from(record: MonthNoteRecord) -> Self {
+        MonthNote {
+            month: record.month,
+            text: record.text,
+        }
+    }
+}
+
+pub(crate) struct MonthNoteRepository<'a> {
+    conn: &'a mut database::Connection,
+}
+
+impl<'a> MonthNoteRepository<'a> {
+    pub fn new(conn: &'a mut database::Connection) -> Self {
+        Self { conn }
+    }
+
+    pub async fn upsert(&mut self, month: &str, text: &str) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO month_notes (month, text) VALUES ($1, $2) ON CONFLICT(month) DO UPDATE SET text = excluded.text",
+        )
+        .bind(month)
+        .bind(text)
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find(&mut self, month: &str) -> Result<Option<MonthNote>, RepositoryError> {
+        let record = sqlx::query_as::<_, MonthNoteRecord>("SELECT month, text FROM month_notes WHERE month = $1")
+            .bind(month)
+            .fetch_optional(&mut *self.conn)
+            .await?;
+
+        Ok(record.map(|r| r.into()))
+    }
+
+    pub async fn delete(&mut self, month: &str) -> Result<(), RepositoryError> {
+        sqlx::query("DELETE FROM month_notes WHERE month = $1")
+            .bind(month)
+            .execute(&mut *self.conn)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
-use crate::models::{Category, CreateCategoryRequest};
+use crate::models::{Category, Classification, CreateCategoryRequest};
 use database::{self, RepositoryError};
 use sqlx::FromRow;
+use std::collections::HashMap;
 
 #[derive(FromRow)]
 struct CategoryRecord {
@@ -9,6 +10,11 @@ struct CategoryRecord {
     color: String,
     is_income: bool,
     is_active: bool,
+    budget_group: Option<String>,
+    allows_negative_budget: bool,
+    default_note: Option<String>,
+    classification: String,
+    icon: Option<String>,
 }
 
 impl From<CategoryRecord> for Category {
@@ -19,6 +25,11 @@ impl From<CategoryRecord> for Category {
             color: record.color,
             is_income: record.is_income,
             is_active: record.is_active,
+            group: record.budget_group,
+            allows_negative_budget: record.allows_negative_budget,
+            default_note: record.default_note,
+            classification: Classification::from_db(&record.classification),
+            icon: record.icon,
         }
     }
 }
@@ -34,12 +45,13 @@ impl<'a> CategoryRepository<'a> {
 
     pub async fn create(&mut self, req: &CreateCategoryRequest) -> Result<i64, RepositoryError> {
         let id: i64 = sqlx::query_scalar(
-            "INSERT INTO categories (name, color, is_income, is_active) VALUES ($1, $2, $3, $4) RETURNING id",
+            "INSERT INTO categories (name, color, is_income, is_active, icon) VALUES ($1, $2, $3, $4, $5) RETURNING id",
         )
         .bind(&req.name)
         .bind(&req.color)
         .bind(req.is_income)
         .bind(req.is_active)
+        .bind(&req.icon)
         .fetch_one(&mut *self.conn)
         .await?;
         
@@ -48,7 +60,7 @@ impl<'a> CategoryRepository<'a> {
 
     pub async fn list(&mut self) -> Result<Vec<Category>, RepositoryError> {
         let records = sqlx::query_as::<_, CategoryRecord>(
-            "SELECT id, name, color, is_income, is_active FROM categories ORDER BY name",
+            "SELECT id, name, color, is_income, is_active, budget_group, allows_negative_budget, default_note, classification, icon FROM categories ORDER BY name",
         )
         .fetch_all(&mut *self.conn)
         .await?;
@@ -56,9 +68,26 @@ impl<'a> CategoryRepository<'a> {
         Ok(records.into_iter().map(|r| r.into()).collect())
     }
 
+    pub async fn count(&mut self) -> Result<i64, RepositoryError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM categories")
+            .fetch_one(&mut *self.conn)
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Transactions per category id, including excluded ones. Unused categories are absent.
+    pub async fn transaction_counts(&mut self) -> Result<HashMap<i64, i64>, RepositoryError> {
+        let rows: Vec<(i64, i64)> = sqlx::query_as("SELECT category_id, COUNT(*) FROM transactions GROUP BY category_id")
+            .fetch_all(&mut *self.conn)
+            .await?;
+
+        Ok(rows.into_iter().collect())
+    }
+
     pub async fn find_by_id(&mut self, id: i64) -> Result<Option<Category>, RepositoryError> {
         let record = sqlx::query_as::<_, CategoryRecord>(
-            "SELECT id, name, color, is_income, is_active FROM categories WHERE id = $1",
+            "SELECT id, name, color, is_income, is_active, budget_group, allows_negative_budget, default_note, classification, icon FROM categories WHERE id = $1",
         )
         .bind(id)
         .fetch_optional(&mut *self.conn)
@@ -92,6 +121,84 @@ impl<'a> CategoryRepository<'a> {
         Ok(())
     }
 
+    pub async fn set_group(&mut self, id: i64, group: Option<&str>) -> Result<(), RepositoryError> {
+        let result = sqlx::query("UPDATE categories SET budget_group = $1 WHERE id = $2")
+            .bind(group)
+            .bind(id)
+            .execute(&mut *self.conn)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
+    pub async fn set_active(&mut self, id: i64, active: bool) -> Result<(), RepositoryError> {
+        let result = sqlx::query("UPDATE categories SET is_active = $1 WHERE id = $2")
+            .bind(active)
+            .bind(id)
+            .execute(&mut *self.conn)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
+    pub async fn set_allows_negative_budget(&mut self, id: i64, allowed: bool) -> Result<(), RepositoryError> {
+        let result = sqlx::query("UPDATE categories SET allows_negative_budget = $1 WHERE id = $2")
+            .bind(allowed)
+            .bind(id)
+            .execute(&mut *self.conn)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
+    pub async fn set_classification(&mut self, id: i64, classification: Classification) -> Result<(), RepositoryError> {
+        let result = sqlx::query("UPDATE categories SET classification = $1 WHERE id = $2")
+            .bind(classification.as_str())
+            .bind(id)
+            .execute(&mut *self.conn)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
+    pub async fn set_icon(&mut self, id: i64, icon: Option<&str>) -> Result<(), RepositoryError> {
+        let result = sqlx::query("UPDATE categories SET icon = $1 WHERE id = $2")
+            .bind(icon)
+            .bind(id)
+            .execute(&mut *self.conn)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
+    pub async fn set_default_note(&mut self, id: i64, note: Option<&str>) -> Result<(), RepositoryError> {
+        let result = sqlx::query("UPDATE categories SET default_note = $1 WHERE id = $2")
+            .bind(note)
+            .bind(id)
+            .execute(&mut *self.conn)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
     pub async fn delete(&mut self, id: i64) -> Result<(), RepositoryError> {
         let result = sqlx::query("DELETE FROM categories WHERE id = $1")
             .bind(id)
@@ -122,6 +229,7 @@ mod tests {
             color: "#ff0000".to_string(),
             is_income: false,
             is_active: true,
+            icon: None,
         };
         let id = repo.create(&req).await.unwrap();
         assert!(id > 0);
@@ -146,12 +254,31 @@ mod tests {
             color: "#ffffff".to_string(),
             is_income: false,
             is_active: true,
+            icon: None,
         }).await.unwrap();
         
         let categories = repo.list().await.unwrap();
         assert_eq!(categories.len(), initial_count + 1);
     }
 
+    #[tokio::test]
+    async fn test_count_categories() {
+        let db = get_test_db().await;
+        let mut uow = db.begin().await.unwrap();
+        let mut repo = CategoryRepository::new(uow.connection());
+
+        let initial_count = repo.count().await.unwrap();
+        repo.create(&CreateCategoryRequest {
+            name: "Counted".to_string(),
+            color: "#ffffff".to_string(),
+            is_income: false,
+            is_active: false,
+            icon: None,
+        }).await.unwrap();
+
+        assert_eq!(repo.count().await.unwrap(), initial_count + 1);
+    }
+
     #[tokio::test]
     async fn test_update_category() {
         let db = get_test_db().await;
@@ -163,6 +290,7 @@ mod tests {
             color: "#000000".to_string(),
             is_income: false,
             is_active: true,
+            icon: None,
         }).await.unwrap();
         
         let update_req = UpdateCategoryRequest {
@@ -170,6 +298,7 @@ mod tests {
             color: Some("#ffffff".to_string()),
             is_income: true,
             is_active: false,
+            icon: None,
         };
         repo.update(id, &update_req.name, update_req.color.as_deref(), update_req.is_income, update_req.is_active).await.unwrap();
         
@@ -191,6 +320,7 @@ mod tests {
             color: "#ffffff".to_string(),
             is_income: false,
             is_active: true,
+            icon: None,
         }).await.unwrap();
         
         assert!(repo.find_by_id(id).await.unwrap().is_some());
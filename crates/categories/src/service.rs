@@ -1,189 +1,1270 @@
-use crate::models::{Category, CreateCategoryRequest, CreateMonthlyBudgetRequest, CategoryBudgetView};
+use crate::models::{duplicate_candidates, normalize_category_name, normalize_group, normalize_icon, BudgetBalance, BudgetDiff, BudgetGroupView, BudgetMode, Category, CreateCategoryRequest, CreateMonthlyBudgetRequest, UpdateCategoryRequest, CategoryBudgetView, CategoryShare, CategoryUsage, CategoryVariance, Classification, ClassificationReport, ClassificationShare, ClassificationTargets, DuplicateCandidate, MonthNote, YearInitialization, MAX_DEFAULT_NOTE_LEN, MAX_MONTH_NOTE_LEN};
 use crate::repository::CategoryRepository;
 use crate::budget_repository::MonthlyBudgetRepository;
-use database::{RepositoryError, Database};
+use crate::month_note_repository::MonthNoteRepository;
+use database::Database;
+use database::events::ChangeKind;
+use common::errors::{Domain, DomainError};
+use common::auth::UserContext;
 use tracing::instrument;
 use rand::seq::SliceRandom;
+use common::money::RoundingMode;
+use common::month::Month;
+use common::palette::{self, Palette};
 
-#[derive(Debug, thiserror::Error)]
-pub enum CategoryError {
-    #[error("Invalid input: {0}")]
-    InvalidInput(String),
-    #[error("Database error: {0}")]
-    Infrastructure(String),
-    #[error("Category already exists: {0}")]
-    Conflict(String),
-    #[error("Category not found")]
-    NotFound,
-}
+/// Longest range `copy_budgets_range` will fill in one call.
+const MAX_COPY_RANGE_MONTHS: usize = 24;
 
-impl From<RepositoryError> for CategoryError {
-    fn from(err: RepositoryError) -> Self {
-        match err {
-            RepositoryError::NotFound => CategoryError::NotFound,
-            RepositoryError::UniqueViolation(msg) => CategoryError::Conflict(msg),
-            RepositoryError::Infrastructure(e) => CategoryError::Infrastructure(e.to_string()),
-            _ => CategoryError::Infrastructure(err.to_string()),
-        }
-    }
+#[derive(Debug, Default)]
+pub struct Categories;
+
+impl Domain for Categories {
+    const NOT_FOUND: &'static str = "Category not found";
 }
 
+pub type CategoryError = DomainError<Categories>;
+
 pub struct CategoryService;
 
 impl CategoryService {
-    fn get_random_pastel_color() -> String {
-        let colors = vec![
-            "#FFB3BA", "#FFDFBA", "#FFFFBA", "#BAFFC9", "#BAE1FF", 
-            "#E2F0CB", "#FDFD96", "#FFC3A0", "#FFD1DC", "#D4F0F0",
-            "#CCE2CB", "#B6CFB6", "#97C1A9", "#FCB7AF", "#FFDAC1",
-            "#E7FFAC", "#FFABAB", "#D5AAFF", "#85E3FF", "#B9F6CA"
-        ];
+    /// A random color from `palette` (normally `Config::palette`) for a category created without a color.
+    pub fn get_random_pastel_color(palette: &Palette) -> String {
         let mut rng = rand::thread_rng();
-        colors.choose(&mut rng).unwrap_or(&"#FFFFFF").to_string()
+        palette.colors().choose(&mut rng).cloned().unwrap_or_else(|| "#FFFFFF".to_string())
     }
 
+    /// Creates an active category with a random color from the built-in palette. Handlers pick the
+    /// color from the configured palette and call `create_category_with_color` instead.
     #[instrument(skip(db))]
     pub async fn create_category(
+        ctx: Option<&UserContext>,
         db: &Database,
         name: String,
         is_income: bool,
+        max_categories: Option<u32>,
     ) -> Result<i64, CategoryError> {
-        let color = Self::get_random_pastel_color();
+        let color = Self::get_random_pastel_color(&Palette::default());
+        Self::create_category_with_color(ctx, db, name, is_income, color, None, max_categories).await
+    }
+
+    /// The category named `name`, compared case-insensitively after whitespace normalization, or a
+    /// new one with that name and a random color from `palette` when there is none. `is_income`
+    /// only applies to a new category.
+    #[instrument(skip(db))]
+    pub async fn find_or_create(
+        ctx: Option<&UserContext>,
+        db: &Database,
+        name: &str,
+        is_income: bool,
+        max_categories: Option<u32>,
+        palette: &Palette,
+    ) -> Result<Category, CategoryError> {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        let mut uow = db.begin().await?;
+        let category = Self::find_or_create_on(uow.connection(), name, is_income, max_categories, palette).await?;
+        uow.commit().await?;
+
+        Ok(category)
+    }
+
+    /// `find_or_create` in a unit of work the caller already holds and has authorized. When another
+    /// writer creates the same name first, the unique index rejects the insert and their category
+    /// is returned instead.
+    pub async fn find_or_create_on(
+        conn: &mut database::Connection,
+        name: &str,
+        is_income: bool,
+        max_categories: Option<u32>,
+        palette: &Palette,
+    ) -> Result<Category, CategoryError> {
+        if let Some(category) = Self::find_by_name_on(conn, name).await? {
+            return Ok(category);
+        }
+
+        let req = CreateCategoryRequest::new(name.to_string(), Self::get_random_pastel_color(palette), is_income)
+            .map_err(CategoryError::InvalidInput)?;
+        match Self::insert_category(conn, req, max_categories).await {
+            Ok(id) => CategoryRepository::new(conn).find_by_id(id).await?.ok_or_else(CategoryError::not_found),
+            Err(CategoryError::Conflict(msg)) => Self::find_by_name_on(conn, name).await?.ok_or(CategoryError::Conflict(msg)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The category named `name`, compared case-insensitively after whitespace normalization.
+    async fn find_by_name_on(conn: &mut database::Connection, name: &str) -> Result<Option<Category>, CategoryError> {
+        let wanted = normalize_category_name(name).to_lowercase();
+        Ok(CategoryRepository::new(conn).list().await?
+            .into_iter()
+            .find(|c| c.name.to_lowercase() == wanted))
+    }
+
+    /// Like `create_category`, but with the caller's `#RRGGBB` color instead of a random pastel.
+    #[instrument(skip(db))]
+    pub async fn create_category_with_color(
+        ctx: Option<&UserContext>,
+        db: &Database,
+        name: String,
+        is_income: bool,
+        color: String,
+        icon: Option<String>,
+        max_categories: Option<u32>,
+    ) -> Result<i64, CategoryError> {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
         let mut req = CreateCategoryRequest::new(name, color, is_income)
             .map_err(CategoryError::InvalidInput)?;
-        req.is_active = true;
+        req.icon = icon;
             
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
-        let mut repo = CategoryRepository::new(uow.connection());
-        
-        let id = repo.create(&req).await?;
-        
-        uow.commit().await.map_err(RepositoryError::from)?;
+        let mut uow = db.begin().await?;
+        let id = Self::insert_category(uow.connection(), req, max_categories).await?;
+        uow.commit().await?;
         
         Ok(id)
     }
 
+    /// Creates the category and sets its limit for `month` in one unit of work, so a failure setting
+    /// the limit leaves no category behind. The color must be `#RRGGBB`; the limit is rounded to
+    /// cents per `rounding`.
+    #[instrument(skip(db))]
+    pub async fn create_category_with_limit(
+        ctx: Option<&UserContext>,
+        db: &Database,
+        req: CreateCategoryRequest,
+        month: &str,
+        limit_dollars: f64,
+        max_categories: Option<u32>,
+        rounding: RoundingMode,
+    ) -> Result<i64, CategoryError> {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        let month: Month = month.parse().map_err(CategoryError::InvalidInput)?;
+
+        let mut uow = db.begin().await?;
+        let id = Self::insert_category(uow.connection(), req, max_categories).await?;
+
+        // A new category never allows a negative budget, so a negative limit fails here and rolls back
+        let budget = CreateMonthlyBudgetRequest::new(id, month.to_string(), limit_dollars, false, rounding)
+            .map_err(CategoryError::InvalidInput)?;
+        MonthlyBudgetRepository::new(uow.connection()).upsert(&budget).await?;
+
+        uow.commit().await?;
+        Ok(id)
+    }
+
+    /// Validates the color and icon, enforces `max_categories`, and inserts the category active.
+    pub(crate) async fn insert_category(conn: &mut database::Connection, mut req: CreateCategoryRequest, max_categories: Option<u32>) -> Result<i64, CategoryError> {
+        if !palette::is_hex_color(&req.color) {
+            return Err(CategoryError::InvalidInput(format!("Invalid color {:?}, expected #RRGGBB", req.color)));
+        }
+        req.is_active = true;
+        req.icon = normalize_icon(req.icon.as_deref()).map_err(CategoryError::InvalidInput)?;
+
+        let mut repo = CategoryRepository::new(conn);
+
+        if let Some(max) = max_categories
+            && repo.count().await? >= i64::from(max)
+        {
+            return Err(CategoryError::InvalidInput(format!("Category limit of {} reached", max)));
+        }
+        
+        repo.create(&req).await.map_err(|e| match CategoryError::from(e) {
+            CategoryError::Conflict(_) => CategoryError::Conflict(format!("A category named \"{}\" already exists", req.name)),
+            other => other,
+        })
+    }
+
     #[instrument(skip(db))]
     pub async fn update_category(
+        ctx: Option<&UserContext>,
         db: &Database,
         id: i64,
-        name: String,
-        color: Option<String>,
-        is_income: bool,
-        is_active: bool,
+        req: UpdateCategoryRequest,
     ) -> Result<(), CategoryError> {
-        if name.trim().is_empty() {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        let UpdateCategoryRequest { name, color, is_income, is_active, icon } = req;
+        let name = normalize_category_name(&name);
+        if name.is_empty() {
             return Err(CategoryError::InvalidInput("Category name cannot be empty".into()));
         }
+        let icon = icon.map(|i| normalize_icon(Some(&i))).transpose().map_err(CategoryError::InvalidInput)?;
 
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
+        let mut uow = db.begin().await?;
         let mut repo = CategoryRepository::new(uow.connection());
         
-        repo.update(id, name.trim(), color.as_deref(), is_income, is_active).await?;
+        repo.update(id, &name, color.as_deref(), is_income, is_active).await?;
+        if let Some(icon) = icon {
+            repo.set_icon(id, icon.as_deref()).await?;
+        }
         
-        uow.commit().await.map_err(RepositoryError::from)?;
+        uow.commit().await?;
         Ok(())
     }
 
+    /// Puts the category in a budget group, or takes it out of any group when `group` is blank.
+    #[instrument(skip(db))]
+    pub async fn set_category_group(ctx: Option<&UserContext>, db: &Database, id: i64, group: Option<String>) -> Result<(), CategoryError> {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        let group = normalize_group(group.as_deref());
+        db.transaction(async |conn| Ok(CategoryRepository::new(conn).set_group(id, group.as_deref()).await?)).await
+    }
+
+    /// Lets the category take a negative limit (a debt-paydown target). Existing budgets are kept.
+    #[instrument(skip(db))]
+    pub async fn set_allows_negative_budget(ctx: Option<&UserContext>, db: &Database, id: i64, allowed: bool) -> Result<(), CategoryError> {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        db.transaction(async |conn| Ok(CategoryRepository::new(conn).set_allows_negative_budget(id, allowed).await?)).await
+    }
+
+    #[instrument(skip(db))]
+    pub async fn set_classification(ctx: Option<&UserContext>, db: &Database, id: i64, classification: Classification) -> Result<(), CategoryError> {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        db.transaction(async |conn| Ok(CategoryRepository::new(conn).set_classification(id, classification).await?)).await
+    }
+
+    /// Sets the note that pre-fills new transactions in this category. Blank clears it. Only the
+    /// page uses it; transactions are saved with whatever note was submitted.
+    #[instrument(skip(db))]
+    pub async fn set_default_note(ctx: Option<&UserContext>, db: &Database, id: i64, note: Option<String>) -> Result<(), CategoryError> {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+        if note.as_ref().is_some_and(|n| n.chars().count() > MAX_DEFAULT_NOTE_LEN) {
+            return Err(CategoryError::InvalidInput(format!(
+                "Default note cannot be longer than {} characters", MAX_DEFAULT_NOTE_LEN
+            )));
+        }
+
+        db.transaction(async |conn| Ok(CategoryRepository::new(conn).set_default_note(id, note.as_deref()).await?)).await
+    }
+
+    /// Activates or archives every listed category in one transaction. An unknown id fails the
+    /// whole batch with `NotFound`. Returns how many were updated.
+    #[instrument(skip(db))]
+    pub async fn set_active_bulk(ctx: Option<&UserContext>, db: &Database, ids: &[i64], active: bool) -> Result<usize, CategoryError> {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        db.transaction(async |conn| {
+            let mut repo = CategoryRepository::new(conn);
+            for &id in ids {
+                repo.set_active(id, active).await?;
+            }
+            Ok(ids.len())
+        }).await
+    }
+
     #[instrument(skip(db))]
     pub async fn delete_category(
+        ctx: Option<&UserContext>,
         db: &Database,
         id: i64,
     ) -> Result<(), CategoryError> {
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        let mut uow = db.begin().await?;
         let mut repo = CategoryRepository::new(uow.connection());
         
         repo.delete(id).await?;
         
-        uow.commit().await.map_err(RepositoryError::from)?;
+        uow.commit().await?;
         Ok(())
     }
 
     #[instrument(skip(db))]
     pub async fn set_monthly_limit(
+        ctx: Option<&UserContext>,
         db: &Database,
         category_id: i64,
         month: String,
         limit_dollars: f64,
+        rounding: RoundingMode,
     ) -> Result<(), CategoryError> {
-        let req = CreateMonthlyBudgetRequest::new(category_id, month, limit_dollars)
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        let mut uow = db.begin().await?;
+        let category = CategoryRepository::new(uow.connection())
+            .find_by_id(category_id)
+            .await?
+            .ok_or_else(CategoryError::not_found)?;
+
+        let req = CreateMonthlyBudgetRequest::new(category_id, month, limit_dollars, category.allows_negative_budget, rounding)
             .map_err(CategoryError::InvalidInput)?;
 
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
         let mut repo = MonthlyBudgetRepository::new(uow.connection());
 
         repo.upsert(&req).await?;
-        uow.commit().await.map_err(RepositoryError::from)?;
+        uow.commit().await?;
+        Ok(())
+    }
+
+    /// Removes the category's budget for the month, so it shows as having none rather than $0.
+    /// If that leaves the month without any budgets, the next visit copies the previous month's
+    /// budgets in again, as for a new month.
+    #[instrument(skip(db))]
+    pub async fn delete_monthly_limit(
+        ctx: Option<&UserContext>,
+        db: &Database,
+        category_id: i64,
+        month: &str,
+    ) -> Result<(), CategoryError> {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        let month: Month = month.parse().map_err(CategoryError::InvalidInput)?;
+        let month = month.to_string();
+        db.transaction(async |conn| Ok(MonthlyBudgetRepository::new(conn).delete(category_id, &month).await?)).await
+    }
+
+    /// Sets the category's budget for the month to a percentage of that month's actual income.
+    #[instrument(skip(db))]
+    pub async fn set_monthly_percent_of_income(
+        ctx: Option<&UserContext>,
+        db: &Database,
+        category_id: i64,
+        month: String,
+        percent: f64,
+    ) -> Result<(), CategoryError> {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        let req = CreateMonthlyBudgetRequest::percent_of_income(category_id, month, percent)
+            .map_err(CategoryError::InvalidInput)?;
+
+        let mut uow = db.begin().await?;
+        CategoryRepository::new(uow.connection())
+            .find_by_id(category_id)
+            .await?
+            .ok_or_else(CategoryError::not_found)?;
+
+        let mut repo = MonthlyBudgetRepository::new(uow.connection());
+
+        repo.upsert(&req).await?;
+        uow.commit().await?;
         Ok(())
     }
 
     #[instrument(skip(db))]
     pub async fn list_categories(db: &Database) -> Result<Vec<Category>, CategoryError> {
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
-        let mut repo = CategoryRepository::new(uow.connection());
-        
-        let categories = repo.list().await?;
-        
-        Ok(categories)
+        let mut conn = db.connection().await?;
+        Self::list_categories_on(&mut conn).await
+    }
+
+    /// `list_categories` on a connection the caller already holds.
+    pub async fn list_categories_on(conn: &mut database::Connection) -> Result<Vec<Category>, CategoryError> {
+        Ok(CategoryRepository::new(conn).list().await?)
+    }
+
+    /// Pairs of categories that look like the same category created twice, with their transaction
+    /// counts, for deciding what to merge. Read-only.
+    #[instrument(skip(db))]
+    pub async fn find_duplicates(db: &Database) -> Result<Vec<DuplicateCandidate>, CategoryError> {
+        let mut conn = db.connection().await?;
+        let mut repo = CategoryRepository::new(&mut conn);
+
+        let counts = repo.transaction_counts().await?;
+        let usages: Vec<CategoryUsage> = repo.list().await?
+            .into_iter()
+            .map(|category| CategoryUsage { transaction_count: counts.get(&category.id).copied().unwrap_or(0), category })
+            .collect();
+
+        Ok(duplicate_candidates(&usages))
     }
 
     #[instrument(skip(db))]
     pub async fn get_category(db: &Database, id: i64) -> Result<Category, CategoryError> {
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
-        let mut repo = CategoryRepository::new(uow.connection());
-        
-        let category = repo.find_by_id(id).await?
-            .ok_or(CategoryError::NotFound)?;
-            
-        Ok(category)
+        let mut conn = db.connection().await?;
+        Self::get_category_on(&mut conn, id).await
+    }
+
+    /// `get_category` on a connection the caller already holds.
+    pub async fn get_category_on(conn: &mut database::Connection, id: i64) -> Result<Category, CategoryError> {
+        CategoryRepository::new(conn).find_by_id(id).await?
+            .ok_or_else(CategoryError::not_found)
     }
 
     #[instrument(skip(db))]
     pub async fn get_budget_view(db: &Database, month: &str) -> Result<Vec<CategoryBudgetView>, CategoryError> {
         tracing::info!("get_budget_view called for month: {}", month);
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
-        
-        // 1. Get all categories
-        let mut cat_repo = CategoryRepository::new(uow.connection());
-        let categories = cat_repo.list().await.map_err(|e| {
-            tracing::error!("Failed to list categories: {}", e);
-            CategoryError::from(e)
-        })?;
+        let mut conn = db.connection().await?;
+        Self::budget_view_on(&mut conn, month).await
+    }
+
+    /// The month's budget view with actuals filled in, nested under category groups with subtotals.
+    #[instrument(skip(db))]
+    pub async fn budget_view_grouped(db: &Database, month: &str) -> Result<Vec<BudgetGroupView>, CategoryError> {
+        Ok(BudgetGroupView::group(Self::budget_view_with_actuals(db, month).await?))
+    }
+
+    /// `get_budget_view` with `spent` and `remaining` filled in from the month's SQL-summed actuals.
+    #[instrument(skip(db))]
+    pub async fn budget_view_with_actuals(db: &Database, month: &str) -> Result<Vec<CategoryBudgetView>, CategoryError> {
+        let mut conn = db.connection().await?;
+        let mut views = Self::budget_view_on(&mut conn, month).await?;
+        let actuals = MonthlyBudgetRepository::new(&mut conn).month_actuals(month).await?;
+
+        for view in &mut views {
+            let actual = actuals.iter().find(|(id, _)| *id == view.category.id).map(|(_, a)| *a).unwrap_or(0);
+            view.set_actual(actual);
+        }
 
-        // 2. Get budgets for this month
-        let mut budget_repo = MonthlyBudgetRepository::new(uow.connection());
-        let budgets = budget_repo.get_for_month(month).await.map_err(|e| {
-            tracing::error!("Failed to get budgets for month: {}", e);
+        Ok(views)
+    }
+
+    /// Whether the month's expense limits add up to more than its income targets, and by how much.
+    #[instrument(skip(db))]
+    pub async fn budget_balance_check(db: &Database, month: &str) -> Result<BudgetBalance, CategoryError> {
+        let mut conn = db.connection().await?;
+        let views = Self::budget_view_on(&mut conn, month).await?;
+        Ok(BudgetBalance::from_views(month, &views))
+    }
+
+    /// `get_budget_view` on a connection the caller already holds, so it can share a unit of work
+    /// with reads from other crates.
+    pub async fn budget_view_on(conn: &mut database::Connection, month: &str) -> Result<Vec<CategoryBudgetView>, CategoryError> {
+        let mut budget_repo = MonthlyBudgetRepository::new(conn);
+        let mut views = budget_repo.budget_view(month).await.map_err(|e| {
+            tracing::error!("Failed to load budget view for month: {}", e);
             CategoryError::from(e)
         })?;
 
-        // 3. Build View (Merging)
-        let mut views = Vec::new();
-        for cat in categories {
-            let budget = budgets.iter().find(|b| b.category_id == cat.id).cloned();
-            
-            // Only include if active OR has a budget for this month
-            if cat.is_active || budget.is_some() {
-                views.push(CategoryBudgetView {
-                    category: cat,
-                    budget,
-                    spent: 0, // Placeholder
-                    remaining: 0, // Placeholder
-                });
+        // Percent-of-income budgets depend on what actually came in this month
+        let has_percent = views.iter().any(|v| v.budget.as_ref().is_some_and(|b| b.mode == BudgetMode::PercentOfIncome));
+        if has_percent {
+            let month_income = budget_repo.month_income(month).await?;
+            for budget in views.iter_mut().filter_map(|v| v.budget.as_mut()) {
+                budget.resolve_limit(month_income);
             }
         }
 
         Ok(views)
     }
 
+    /// Copies the source month's budgets into every month from `from_month` to `to_month` inclusive,
+    /// in one transaction. The source month itself is skipped if it falls inside the range, and
+    /// budgets of archived categories aren't copied. Returns the number of budget rows written.
+    #[instrument(skip(db))]
+    pub async fn copy_budgets_range(
+        ctx: Option<&UserContext>,
+        db: &Database,
+        source_month: &str,
+        from_month: &str,
+        to_month: &str,
+        overwrite: bool,
+    ) -> Result<u64, CategoryError> {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        let source: Month = source_month.parse().map_err(CategoryError::InvalidInput)?;
+        let from: Month = from_month.parse().map_err(CategoryError::InvalidInput)?;
+        let to: Month = to_month.parse().map_err(CategoryError::InvalidInput)?;
+
+        if from > to {
+            return Err(CategoryError::InvalidInput(format!("Range start {} is after its end {}", from, to)));
+        }
+
+        let mut targets = Vec::new();
+        let mut month = from;
+        while month <= to {
+            if targets.len() == MAX_COPY_RANGE_MONTHS {
+                return Err(CategoryError::InvalidInput(format!(
+                    "Range is longer than {} months", MAX_COPY_RANGE_MONTHS
+                )));
+            }
+            targets.push(month);
+            month = month.next();
+        }
+
+        let mut uow = db.begin().await?;
+        let mut repo = MonthlyBudgetRepository::new(uow.connection());
+
+        let source = source.to_string();
+        let mut written = 0;
+        for target in targets.iter().filter(|m| m.to_string() != source) {
+            written += repo.copy_budgets_into(&source, &target.to_string(), overwrite).await?;
+        }
+
+        uow.commit().await?;
+        Ok(written)
+    }
+
+    /// What changed between month `a`'s budgets and month `b`'s: changed, added and removed
+    /// limits, largest change first.
+    #[instrument(skip(db))]
+    pub async fn budget_diff(db: &Database, month_a: &str, month_b: &str) -> Result<Vec<BudgetDiff>, CategoryError> {
+        let a: Month = month_a.parse().map_err(CategoryError::InvalidInput)?;
+        let b: Month = month_b.parse().map_err(CategoryError::InvalidInput)?;
+
+        let mut conn = db.connection().await?;
+        let views_a = Self::budget_view_on(&mut conn, &a.to_string()).await?;
+        let views_b = Self::budget_view_on(&mut conn, &b.to_string()).await?;
+        Ok(BudgetDiff::between(&views_a, &views_b))
+    }
+
+    /// Per-category budget against actual summed over `from_month..=to_month`, largest overspend first.
+    #[instrument(skip(db))]
+    pub async fn budget_vs_actual(db: &Database, from_month: &str, to_month: &str) -> Result<Vec<CategoryVariance>, CategoryError> {
+        let from: Month = from_month.parse().map_err(CategoryError::InvalidInput)?;
+        let to: Month = to_month.parse().map_err(CategoryError::InvalidInput)?;
+
+        if from > to {
+            return Err(CategoryError::InvalidInput(format!("Range start {} is after its end {}", from, to)));
+        }
+
+        let mut conn = db.connection().await?;
+        let mut repo = MonthlyBudgetRepository::new(&mut conn);
+
+        Ok(repo.budget_vs_actual(&from.to_string(), &to.to_string()).await?)
+    }
+
+    /// Where the month's money went: expense categories' spending as shares of the total, largest
+    /// first. Income categories and excluded transactions are left out; a month with no spending
+    /// returns no rows rather than zero shares.
+    #[instrument(skip(db))]
+    pub async fn spend_shares(db: &Database, month: &str) -> Result<Vec<CategoryShare>, CategoryError> {
+        let month: Month = month.parse().map_err(CategoryError::InvalidInput)?;
+
+        let mut conn = db.connection().await?;
+        Ok(MonthlyBudgetRepository::new(&mut conn).spend_shares(&month.to_string()).await?)
+    }
+
+    /// The month's expense spending per need/want/saving class as a share of its income, against
+    /// `targets`. Every reported class is present, at zero when nothing was spent.
+    #[instrument(skip(db))]
+    pub async fn classification_report(db: &Database, month: &str, targets: ClassificationTargets) -> Result<ClassificationReport, CategoryError> {
+        let month: Month = month.parse().map_err(CategoryError::InvalidInput)?;
+        let month = month.to_string();
+
+        let mut conn = db.connection().await?;
+        let mut repo = MonthlyBudgetRepository::new(&mut conn);
+        let income = repo.month_income(&month).await?;
+        let spending = repo.spend_by_classification(&month).await?;
+
+        let classes = Classification::REPORTED.into_iter().map(|classification| {
+            let spent = spending.iter().find(|(c, _)| *c == classification).map(|(_, s)| *s).unwrap_or(0);
+            let target_percent = targets.percent_for(classification).unwrap_or(0);
+            let percent = (income > 0).then(|| spent as f64 * 100.0 / income as f64);
+            ClassificationShare {
+                classification,
+                spent,
+                percent,
+                target_percent,
+                difference: percent.map(|p| p - f64::from(target_percent)),
+            }
+        }).collect();
+
+        Ok(ClassificationReport { month, income, classes })
+    }
+
+    /// Copies the template month's budgets into each month of `year` that has none yet, in one
+    /// transaction. Budgets of archived categories aren't copied, as with the monthly rollover.
+    #[instrument(skip(db))]
+    pub async fn initialize_year(ctx: Option<&UserContext>, db: &Database, year: i32, template_month: &str) -> Result<YearInitialization, CategoryError> {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        let template: Month = template_month.parse().map_err(CategoryError::InvalidInput)?;
+        let template = template.to_string();
+        let months = (1..=12)
+            .map(|m| Month::new(year, m).map(|m| m.to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(CategoryError::InvalidInput)?;
+
+        let mut uow = db.begin().await?;
+        let mut repo = MonthlyBudgetRepository::new(uow.connection());
+
+        if repo.get_for_month(&template).await?.is_empty() {
+            return Err(CategoryError::InvalidInput(format!("Template month {} has no budgets", template)));
+        }
+
+        let mut result = YearInitialization { year, template_month: template.clone(), initialized: Vec::new(), skipped: Vec::new(), written: 0 };
+        let mut archived = 0;
+        for month in months {
+            let (copied, skipped) = repo.copy_budgets(&template, &month).await?;
+            if copied > 0 {
+                result.written += copied;
+                archived = archived.max(skipped);
+                result.initialized.push(month);
+            } else {
+                result.skipped.push(month);
+            }
+        }
+
+        uow.commit().await?;
+        if archived > 0 {
+            tracing::warn!("Not copying {} budget(s) from {} into {}: their categories are archived", archived, template, year);
+        }
+        Ok(result)
+    }
+
     #[instrument(skip(db))]
     pub async fn ensure_budgets_exist(db: &Database, current_month: &str, previous_month: &str) -> Result<(), CategoryError> {
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
+        let mut uow = db.begin().await?;
         let mut repo = MonthlyBudgetRepository::new(uow.connection());
         
-        repo.copy_budgets(previous_month, current_month).await?;
+        let (_, skipped) = repo.copy_budgets(previous_month, current_month).await?;
+        if skipped > 0 {
+            tracing::warn!("Not copying {} budget(s) from {} to {}: their categories are archived", skipped, previous_month, current_month);
+        }
         
-        uow.commit().await.map_err(RepositoryError::from)?;
+        uow.commit().await?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+pub struct MonthNoteService;
+
+impl MonthNoteService {
+    /// Sets the month's note, replacing any existing one. A blank note removes it and returns `None`.
+    #[instrument(skip(db, text))]
+    pub async fn set(ctx: Option<&UserContext>, db: &Database, month: &str, text: &str) -> Result<Option<MonthNote>, CategoryError> {
+        UserContext::authorize(ctx).map_err(CategoryError::Forbidden)?;
+
+        let month: Month = month.parse().map_err(CategoryError::InvalidInput)?;
+        let text = text.trim();
+        if text.chars().count() > MAX_MONTH_NOTE_LEN {
+            return Err(CategoryError::InvalidInput(format!(
+                "Note cannot be longer than {} characters", MAX_MONTH_NOTE_LEN
+            )));
+        }
+
+        let month = month.to_string();
+        let note = db.transaction(async |conn| -> Result<Option<MonthNote>, CategoryError> {
+            let mut repo = MonthNoteRepository::new(conn);
+            if text.is_empty() {
+                repo.delete(&month).await?;
+                return Ok(None);
+            }
+            repo.upsert(&month, text).await?;
+            Ok(Some(MonthNote { month: month.clone(), text: text.to_string() }))
+        })
+        .await?;
+
+        db.events().publish(month, ChangeKind::Updated, None);
+        Ok(note)
+    }
+
+    #[instrument(skip(db))]
+    pub async fn get(db: &Database, month: &str) -> Result<Option<MonthNote>, CategoryError> {
+        let mut conn = db.connection().await?;
+        Self::get_on(&mut conn, month).await
+    }
+
+    /// `get` on a connection the caller already holds.
+    pub async fn get_on(conn: &mut database::Connection, month: &str) -> Result<Option<MonthNote>, CategoryError> {
+        Ok(MonthNoteRepository::new(conn).find(month).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::get_test_db;
+    use crate::models::BudgetChange;
+
+    #[tokio::test]
+    async fn test_create_category_case_insensitive_conflict() {
+        let db = get_test_db().await;
+
+        CategoryService::create_category(None, &db, "Food".to_string(), false, None).await.unwrap();
+        let result = CategoryService::create_category(None, &db, "  food ".to_string(), false, None).await;
+
+        assert!(matches!(result, Err(CategoryError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_rename_category_to_existing_name_conflicts() {
+        let db = get_test_db().await;
+
+        CategoryService::create_category(None, &db, "Food".to_string(), false, None).await.unwrap();
+        let id = CategoryService::create_category(None, &db, "Travel".to_string(), false, None).await.unwrap();
+        let result = CategoryService::update_category(None, &db, id, UpdateCategoryRequest { name: "FOOD".to_string(), color: None, is_income: false, is_active: true, icon: None }).await;
+
+        assert!(matches!(result, Err(CategoryError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_create_category_respects_max_categories() {
+        let db = get_test_db().await;
+        let existing = CategoryService::list_categories(&db).await.unwrap().len() as u32;
+        let max = Some(existing + 1);
+
+        CategoryService::create_category(None, &db, "Last One".to_string(), false, max).await.unwrap();
+        let result = CategoryService::create_category(None, &db, "One Too Many".to_string(), false, max).await;
+
+        assert!(matches!(result, Err(CategoryError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_ensure_budgets_exist_does_not_duplicate() {
+        let db = get_test_db().await;
+        let id = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, id, "2026-01".to_string(), 50.0, RoundingMode::HalfUp).await.unwrap();
+        let source_count = CategoryService::get_budget_view(&db, "2026-01").await.unwrap()
+            .iter().filter(|v| v.budget.is_some()).count();
+
+        let (a, b) = tokio::join!(
+            CategoryService::ensure_budgets_exist(&db, "2026-02", "2026-01"),
+            CategoryService::ensure_budgets_exist(&db, "2026-02", "2026-01"),
+        );
+        a.unwrap();
+        b.unwrap();
+
+        let copied: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM monthly_budgets WHERE month = '2026-02'")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(copied as usize, source_count);
+    }
+
+    #[tokio::test]
+    async fn test_copy_budgets_range_fills_each_month() {
+        let db = get_test_db().await;
+        let id = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, id, "2026-01".to_string(), 50.0, RoundingMode::HalfUp).await.unwrap();
+        // An existing budget in the range is kept without overwrite
+        CategoryService::set_monthly_limit(None, &db, id, "2026-03".to_string(), 80.0, RoundingMode::HalfUp).await.unwrap();
+
+        CategoryService::copy_budgets_range(None, &db, "2026-01", "2026-02", "2026-07", false).await.unwrap();
+
+        for month in ["2026-02", "2026-03", "2026-04", "2026-05", "2026-06", "2026-07"] {
+            let views = CategoryService::get_budget_view(&db, month).await.unwrap();
+            let budget = views.iter().find(|v| v.category.id == id).and_then(|v| v.budget.as_ref()).unwrap();
+            let expected = if month == "2026-03" { 8000 } else { 5000 };
+            assert_eq!(budget.limit_amount, expected, "budget for {}", month);
+        }
+
+        CategoryService::copy_budgets_range(None, &db, "2026-01", "2026-02", "2026-07", true).await.unwrap();
+        let views = CategoryService::get_budget_view(&db, "2026-03").await.unwrap();
+        let budget = views.iter().find(|v| v.category.id == id).and_then(|v| v.budget.as_ref()).unwrap();
+        assert_eq!(budget.limit_amount, 5000);
+    }
+
+    #[tokio::test]
+    async fn test_initialize_year_fills_every_month_from_template() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let travel = CategoryService::create_category(None, &db, "Travel".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, dining, "2029-12".to_string(), 50.0, RoundingMode::HalfUp).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, travel, "2029-12".to_string(), 120.0, RoundingMode::HalfUp).await.unwrap();
+        // A month already budgeted is left alone
+        CategoryService::set_monthly_limit(None, &db, dining, "2030-05".to_string(), 80.0, RoundingMode::HalfUp).await.unwrap();
+
+        // 2030 stays clear of the budgets seeded for the current month
+        let result = CategoryService::initialize_year(None, &db, 2030, "2029-12").await.unwrap();
+        assert_eq!(result.initialized.len(), 11);
+        assert_eq!(result.skipped, vec!["2030-05".to_string()]);
+        assert_eq!(result.written, 22);
+
+        for month in 1..=12 {
+            let month = format!("2030-{:02}", month);
+            let views = CategoryService::get_budget_view(&db, &month).await.unwrap();
+            let limit = |id: i64| views.iter().find(|v| v.category.id == id).and_then(|v| v.budget.as_ref()).map(|b| b.limit_amount);
+            if month == "2030-05" {
+                assert_eq!((limit(dining), limit(travel)), (Some(8000), None));
+            } else {
+                assert_eq!((limit(dining), limit(travel)), (Some(5000), Some(12000)), "budgets for {}", month);
+            }
+        }
+
+        let empty_template = CategoryService::initialize_year(None, &db, 2031, "2024-01").await;
+        assert!(matches!(empty_template, Err(CategoryError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn test_copy_budgets_range_validates_range() {
+        let db = get_test_db().await;
+
+        let backwards = CategoryService::copy_budgets_range(None, &db, "2026-01", "2026-06", "2026-02", false).await;
+        assert!(matches!(backwards, Err(CategoryError::InvalidInput(_))));
+
+        let too_long = CategoryService::copy_budgets_range(None, &db, "2026-01", "2026-02", "2028-12", false).await;
+        assert!(matches!(too_long, Err(CategoryError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn test_percent_of_income_budget_follows_income() {
+        let db = get_test_db().await;
+        let salary = CategoryService::create_category(None, &db, "Paycheck".to_string(), true, None).await.unwrap();
+        let savings = CategoryService::create_category(None, &db, "Savings".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_percent_of_income(None, &db, savings, "2026-01".to_string(), 20.0).await.unwrap();
+
+        let add_income = |cents: i64| {
+            sqlx::query("INSERT INTO transactions (category_id, transaction_date, amount) VALUES ($1, '2026-01-01', $2)")
+                .bind(salary)
+                .bind(cents)
+                .execute(&db.pool)
+        };
+        let savings_limit = || async {
+            CategoryService::get_budget_view(&db, "2026-01").await.unwrap()
+                .into_iter()
+                .find(|v| v.category.id == savings)
+                .and_then(|v| v.budget)
+                .unwrap()
+                .limit_amount
+        };
+
+        add_income(500000).await.unwrap();
+        assert_eq!(savings_limit().await, 100000);
+
+        add_income(100000).await.unwrap();
+        assert_eq!(savings_limit().await, 120000);
+    }
+
+    #[tokio::test]
+    async fn test_budget_vs_actual_sums_two_months() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let fuel = CategoryService::create_category(None, &db, "Fuel".to_string(), false, None).await.unwrap();
+        for month in ["2026-01", "2026-02"] {
+            CategoryService::set_monthly_limit(None, &db, dining, month.to_string(), 100.0, RoundingMode::HalfUp).await.unwrap();
+            CategoryService::set_monthly_limit(None, &db, fuel, month.to_string(), 80.0, RoundingMode::HalfUp).await.unwrap();
+        }
+        // Outside the range, must not count
+        CategoryService::set_monthly_limit(None, &db, dining, "2026-03".to_string(), 500.0, RoundingMode::HalfUp).await.unwrap();
+
+        for (category_id, date, cents) in [
+            (dining, "2026-01-05", -9000),
+            (dining, "2026-02-10", -15000),
+            (dining, "2026-03-01", -99900),
+            (fuel, "2026-01-07", -4000),
+            (fuel, "2026-02-07", -3000),
+        ] {
+            sqlx::query("INSERT INTO transactions (category_id, transaction_date, amount) VALUES ($1, $2, $3)")
+                .bind(category_id)
+                .bind(date)
+                .bind(cents)
+                .execute(&db.pool)
+                .await
+                .unwrap();
+        }
+
+        let rows = CategoryService::budget_vs_actual(&db, "2026-01", "2026-02").await.unwrap();
+        let dining_row = &rows[0];
+        assert_eq!(dining_row.category.id, dining, "largest overspend first");
+        assert_eq!(dining_row.total_limit, 20000);
+        assert_eq!(dining_row.total_spent, 24000);
+        assert_eq!(dining_row.variance, -4000);
+
+        let fuel_row = rows.iter().find(|r| r.category.id == fuel).unwrap();
+        assert_eq!(fuel_row.total_limit, 16000);
+        assert_eq!(fuel_row.total_spent, 7000);
+        assert_eq!(fuel_row.variance, 9000);
+
+        let result = CategoryService::budget_vs_actual(&db, "2026-02", "2026-01").await;
+        assert!(matches!(result, Err(CategoryError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn test_budget_view_grouped_subtotals() {
+        let db = get_test_db().await;
+        let mut ids = Vec::new();
+        for (name, group, limit) in [("Mortgage", "Needs", 1000.0), ("Power", "  Needs ", 150.0), ("Concerts", "Wants", 80.0)] {
+            let id = CategoryService::create_category(None, &db, name.to_string(), false, None).await.unwrap();
+            CategoryService::set_category_group(None, &db, id, Some(group.to_string())).await.unwrap();
+            CategoryService::set_monthly_limit(None, &db, id, "2026-01".to_string(), limit, RoundingMode::HalfUp).await.unwrap();
+            ids.push(id);
+        }
+
+        for (category_id, cents) in [(ids[0], -100000), (ids[1], -12000), (ids[2], -9500), (ids[2], 2000)] {
+            sqlx::query("INSERT INTO transactions (category_id, transaction_date, amount) VALUES ($1, '2026-01-10', $2)")
+                .bind(category_id)
+                .bind(cents)
+                .execute(&db.pool)
+                .await
+                .unwrap();
+        }
+
+        let groups = CategoryService::budget_view_grouped(&db, "2026-01").await.unwrap();
+        let names: Vec<Option<&str>> = groups.iter().map(|g| g.name.as_deref()).collect();
+        assert_eq!(&names[..2], &[Some("Needs"), Some("Wants")]);
+        assert_eq!(names.last(), Some(&None), "ungrouped categories come last");
+
+        let needs = &groups[0];
+        assert_eq!(needs.categories.len(), 2);
+        assert_eq!(needs.total_limit, 115000);
+        assert_eq!(needs.total_spent, 112000);
+        assert_eq!(needs.total_remaining, 3000);
+
+        let wants = &groups[1];
+        assert_eq!(wants.total_limit, 8000);
+        assert_eq!(wants.total_spent, 9500);
+        assert_eq!(wants.total_remaining, -1500);
+    }
+
+    #[tokio::test]
+    async fn test_budget_view_includes_archived_only_with_budget() {
+        let db = get_test_db().await;
+        let kept = CategoryService::create_category(None, &db, "Old Gym".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, kept, "2026-01".to_string(), 30.0, RoundingMode::HalfUp).await.unwrap();
+        CategoryService::update_category(None, &db, kept, UpdateCategoryRequest { name: "Old Gym".to_string(), color: None, is_income: false, is_active: false, icon: None }).await.unwrap();
+        let dropped = CategoryService::create_category(None, &db, "Old Club".to_string(), false, None).await.unwrap();
+        CategoryService::update_category(None, &db, dropped, UpdateCategoryRequest { name: "Old Club".to_string(), color: None, is_income: false, is_active: false, icon: None }).await.unwrap();
+
+        let view = CategoryService::get_budget_view(&db, "2026-01").await.unwrap();
+
+        let archived = view.iter().find(|v| v.category.id == kept).expect("archived with a budget is shown");
+        assert_eq!(archived.budget.as_ref().unwrap().limit_amount, 3000);
+        assert!(view.iter().all(|v| v.category.id != dropped), "archived without a budget is hidden");
+
+        // The archived budget is for January only
+        let february = CategoryService::get_budget_view(&db, "2026-02").await.unwrap();
+        assert!(february.iter().all(|v| v.category.id != kept));
+    }
+
+    #[tokio::test]
+    async fn test_negative_budget_only_for_flagged_categories() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let loan = CategoryService::create_category(None, &db, "Car Loan".to_string(), false, None).await.unwrap();
+        CategoryService::set_allows_negative_budget(None, &db, loan, true).await.unwrap();
+
+        let result = CategoryService::set_monthly_limit(None, &db, dining, "2026-01".to_string(), -200.0, RoundingMode::HalfUp).await;
+        assert!(matches!(result, Err(CategoryError::InvalidInput(_))));
+        CategoryService::set_monthly_limit(None, &db, loan, "2026-01".to_string(), -200.0, RoundingMode::HalfUp).await.unwrap();
+
+        // Paying 250 against a 200 paydown target is 50 ahead; paying 150 is 50 short
+        let view = CategoryService::get_budget_view(&db, "2026-01").await.unwrap();
+        let mut loan_view = view.into_iter().find(|v| v.category.id == loan).unwrap();
+        assert!(loan_view.category.allows_negative_budget);
+        assert_eq!(loan_view.budget.as_ref().unwrap().limit_amount, -20000);
+        loan_view.set_actual(25000);
+        assert_eq!(loan_view.remaining, 5000);
+        loan_view.set_actual(15000);
+        assert_eq!(loan_view.remaining, -5000);
+    }
+
+    #[tokio::test]
+    async fn test_spend_shares_sum_to_one_hundred() {
+        let db = get_test_db().await;
+        let mut ids = Vec::new();
+        for name in ["Dining", "Travel", "Hobbies"] {
+            ids.push(CategoryService::create_category(None, &db, name.to_string(), false, None).await.unwrap());
+        }
+        let bonus = CategoryService::create_category(None, &db, "Bonus".to_string(), true, None).await.unwrap();
+
+        let rows = [(ids[0], -5000, 0), (ids[1], -3000, 0), (ids[2], -2000, 0), (ids[2], -9900, 1), (bonus, 50000, 0)];
+        for (category_id, cents, excluded) in rows {
+            sqlx::query("INSERT INTO transactions (category_id, transaction_date, amount, excluded) VALUES ($1, '2026-05-10', $2, $3)")
+                .bind(category_id)
+                .bind(cents)
+                .bind(excluded)
+                .execute(&db.pool)
+                .await
+                .unwrap();
+        }
+
+        let shares = CategoryService::spend_shares(&db, "2026-05").await.unwrap();
+        assert_eq!(shares.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), ["Dining", "Travel", "Hobbies"]);
+        assert_eq!(shares[0].spent, 5000);
+        assert!((shares[0].percent - 50.0).abs() < 1e-9);
+        let total: f64 = shares.iter().map(|s| s.percent).sum();
+        assert!((total - 100.0).abs() < 1e-6);
+
+        assert!(CategoryService::spend_shares(&db, "2026-06").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_monthly_limit() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let travel = CategoryService::create_category(None, &db, "Travel".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, dining, "2026-01".to_string(), 50.0, RoundingMode::HalfUp).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, travel, "2026-01".to_string(), 80.0, RoundingMode::HalfUp).await.unwrap();
+
+        CategoryService::delete_monthly_limit(None, &db, dining, "2026-01").await.unwrap();
+
+        let view = CategoryService::get_budget_view(&db, "2026-01").await.unwrap();
+        assert!(view.iter().find(|v| v.category.id == dining).unwrap().budget.is_none());
+        assert!(view.iter().find(|v| v.category.id == travel).unwrap().budget.is_some());
+
+        let again = CategoryService::delete_monthly_limit(None, &db, dining, "2026-01").await;
+        assert!(matches!(again, Err(CategoryError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_month_note_upsert_and_read_back() {
+        let db = get_test_db().await;
+        assert_eq!(MonthNoteService::get(&db, "2026-07").await.unwrap(), None);
+
+        MonthNoteService::set(None, &db, "2026-07", "  Vacation month ").await.unwrap();
+        let note = MonthNoteService::get(&db, "2026-07").await.unwrap().unwrap();
+        assert_eq!(note.text, "Vacation month");
+
+        MonthNoteService::set(None, &db, "2026-07", "Vacation month, expect overspend").await.unwrap();
+        let note = MonthNoteService::get(&db, "2026-07").await.unwrap().unwrap();
+        assert_eq!(note.text, "Vacation month, expect overspend");
+        assert_eq!(MonthNoteService::get(&db, "2026-08").await.unwrap(), None);
+
+        // Blank clears it
+        assert_eq!(MonthNoteService::set(None, &db, "2026-07", " ").await.unwrap(), None);
+        assert_eq!(MonthNoteService::get(&db, "2026-07").await.unwrap(), None);
+
+        assert!(matches!(MonthNoteService::set(None, &db, "2026-7", "x").await, Err(CategoryError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn test_create_category_with_limit_creates_both() {
+        let db = get_test_db().await;
+        let req = CreateCategoryRequest::new("Gym".to_string(), "#12AB9F".to_string(), false).unwrap();
+
+        let id = CategoryService::create_category_with_limit(None, &db, req, "2026-02", 45.5, None, RoundingMode::HalfUp).await.unwrap();
+
+        let views = CategoryService::get_budget_view(&db, "2026-02").await.unwrap();
+        let view = views.iter().find(|v| v.category.id == id).unwrap();
+        assert_eq!(view.category.name, "Gym");
+        assert_eq!(view.budget.as_ref().map(|b| b.limit_amount), Some(4550));
+    }
+
+    #[tokio::test]
+    async fn test_create_category_with_limit_rolls_back_on_bad_limit() {
+        let db = get_test_db().await;
+        let before = CategoryService::list_categories(&db).await.unwrap().len();
+        let req = CreateCategoryRequest::new("Gym".to_string(), "#12AB9F".to_string(), false).unwrap();
+
+        // The category insert succeeds, then the negative limit is rejected
+        let result = CategoryService::create_category_with_limit(None, &db, req, "2026-02", -10.0, None, RoundingMode::HalfUp).await;
+
+        assert!(matches!(result, Err(CategoryError::InvalidInput(_))));
+        let after = CategoryService::list_categories(&db).await.unwrap();
+        assert_eq!(after.len(), before);
+        assert!(!after.iter().any(|c| c.name == "Gym"));
+    }
+
+    #[tokio::test]
+    async fn test_create_category_with_color_stores_it_exactly() {
+        let db = get_test_db().await;
+        let id = CategoryService::create_category_with_color(None, &db, "Gym".to_string(), false, "#12AB9f".to_string(), None, None).await.unwrap();
+
+        let category = CategoryService::get_category(&db, id).await.unwrap();
+        assert_eq!(category.color, "#12AB9f");
+
+        let err = CategoryService::create_category_with_color(None, &db, "Travel".to_string(), false, "teal".to_string(), None, None).await.unwrap_err();
+        assert!(matches!(err, CategoryError::InvalidInput(_)));
+    }
+
+    #[tokio::test]
+    async fn test_set_active_bulk_archives_all_or_nothing() {
+        let db = get_test_db().await;
+        let mut ids = Vec::new();
+        for name in ["Gym", "Dining", "Travel"] {
+            ids.push(CategoryService::create_category(None, &db, name.to_string(), false, None).await.unwrap());
+        }
+
+        // An unknown id rolls back the whole batch
+        let err = CategoryService::set_active_bulk(None, &db, &[ids[0], 9999], false).await.unwrap_err();
+        assert!(matches!(err, CategoryError::NotFound(_)));
+        assert!(CategoryService::get_category(&db, ids[0]).await.unwrap().is_active);
+
+        assert_eq!(CategoryService::set_active_bulk(None, &db, &ids, false).await.unwrap(), 3);
+        for id in ids {
+            assert!(!CategoryService::get_category(&db, id).await.unwrap().is_active);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_duplicates_counts_transactions() {
+        let db = get_test_db().await;
+        let gym = CategoryService::create_category(None, &db, "Gym".to_string(), false, None).await.unwrap();
+        let gyms = CategoryService::create_category(None, &db, "Gyms".to_string(), false, None).await.unwrap();
+        for _ in 0..2 {
+            sqlx::query("INSERT INTO transactions (category_id, transaction_date, amount) VALUES ($1, '2026-02-01', -1000)")
+                .bind(gyms)
+                .execute(&db.pool)
+                .await
+                .unwrap();
+        }
+
+        let duplicates = CategoryService::find_duplicates(&db).await.unwrap();
+
+        let pair = duplicates.iter().find(|c| c.merge.category.id == gym).unwrap();
+        assert_eq!(pair.keep.category.id, gyms);
+        assert_eq!(pair.keep.transaction_count, 2);
+        assert_eq!(pair.merge.transaction_count, 0);
+        assert_eq!(pair.distance, 1);
+    }
+
+    #[tokio::test]
+    async fn test_default_note_round_trips() {
+        let db = get_test_db().await;
+        let fuel = CategoryService::create_category(None, &db, "Fuel".to_string(), false, None).await.unwrap();
+        assert_eq!(CategoryService::get_category(&db, fuel).await.unwrap().default_note, None);
+
+        CategoryService::set_default_note(None, &db, fuel, Some("  Shell, pump 4 ".to_string())).await.unwrap();
+        assert_eq!(CategoryService::get_category(&db, fuel).await.unwrap().default_note.as_deref(), Some("Shell, pump 4"));
+        let view = CategoryService::get_budget_view(&db, "2026-01").await.unwrap().into_iter().find(|v| v.category.id == fuel).unwrap();
+        assert_eq!(view.category.default_note.as_deref(), Some("Shell, pump 4"));
+
+        CategoryService::set_default_note(None, &db, fuel, Some("   ".to_string())).await.unwrap();
+        assert_eq!(CategoryService::get_category(&db, fuel).await.unwrap().default_note, None);
+
+        let too_long = "x".repeat(MAX_DEFAULT_NOTE_LEN + 1);
+        assert!(matches!(CategoryService::set_default_note(None, &db, fuel, Some(too_long)).await, Err(CategoryError::InvalidInput(_))));
+        assert!(matches!(CategoryService::set_default_note(None, &db, 9999, None).await, Err(CategoryError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_classification_report_percentages() {
+        let db = get_test_db().await;
+        let paycheck = CategoryService::create_category(None, &db, "Paycheck".to_string(), true, None).await.unwrap();
+        let housing = CategoryService::create_category(None, &db, "Housing".to_string(), false, None).await.unwrap();
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let travel = CategoryService::create_category(None, &db, "Travel".to_string(), false, None).await.unwrap();
+        let savings = CategoryService::create_category(None, &db, "Savings".to_string(), false, None).await.unwrap();
+        let gifts = CategoryService::create_category(None, &db, "Gifts".to_string(), false, None).await.unwrap();
+        for (id, class) in [(housing, Classification::Need), (dining, Classification::Want), (travel, Classification::Want), (savings, Classification::Saving)] {
+            CategoryService::set_classification(None, &db, id, class).await.unwrap();
+        }
+        for (id, cents, excluded) in [(paycheck, 400000, false), (housing, -220000, false), (dining, -30000, false), (travel, -50000, false), (travel, -99999, true), (savings, -40000, false), (gifts, -10000, false)] {
+            sqlx::query("INSERT INTO transactions (category_id, transaction_date, amount, excluded) VALUES ($1, '2026-03-10', $2, $3)")
+                .bind(id)
+                .bind(cents)
+                .bind(excluded)
+                .execute(&db.pool)
+                .await
+                .unwrap();
+        }
+        let targets = ClassificationTargets { need: 50, want: 30, saving: 20 };
+
+        let report = CategoryService::classification_report(&db, "2026-03", targets).await.unwrap();
+
+        assert_eq!(report.income, 400000);
+        let summary: Vec<(Classification, i64, Option<f64>, Option<f64>)> = report.classes.iter()
+            .map(|c| (c.classification, c.spent, c.percent, c.difference))
+            .collect();
+        // Gifts is unclassified and the excluded trip is not counted
+        assert_eq!(summary, vec![
+            (Classification::Need, 220000, Some(55.0), Some(5.0)),
+            (Classification::Want, 80000, Some(20.0), Some(-10.0)),
+            (Classification::Saving, 40000, Some(10.0), Some(-10.0)),
+        ]);
+
+        let empty = CategoryService::classification_report(&db, "2026-04", targets).await.unwrap();
+        assert!(empty.classes.iter().all(|c| c.spent == 0 && c.percent.is_none()));
+        assert_eq!(CategoryService::get_category(&db, gifts).await.unwrap().classification, Classification::Ignore);
+    }
+
+    #[tokio::test]
+    async fn test_budget_for_missing_category_is_not_found() {
+        let db = get_test_db().await;
+
+        let limit = CategoryService::set_monthly_limit(None, &db, 9999, "2026-01".to_string(), 50.0, RoundingMode::HalfUp).await;
+        assert!(matches!(limit, Err(CategoryError::NotFound(_))));
+
+        let percent = CategoryService::set_monthly_percent_of_income(None, &db, 9999, "2026-01".to_string(), 10.0).await;
+        assert!(matches!(percent, Err(CategoryError::NotFound(_))));
+
+        let orphans: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM monthly_budgets WHERE category_id = 9999")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(orphans, 0);
+    }
+
+    #[tokio::test]
+    async fn test_archived_category_budget_not_copied_forward() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let gym = CategoryService::create_category(None, &db, "Gym".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, dining, "2026-01".to_string(), 50.0, RoundingMode::HalfUp).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, gym, "2026-01".to_string(), 40.0, RoundingMode::HalfUp).await.unwrap();
+
+        CategoryService::set_active_bulk(None, &db, &[gym], false).await.unwrap();
+        CategoryService::ensure_budgets_exist(&db, "2026-02", "2026-01").await.unwrap();
+
+        let budgeted: Vec<i64> = sqlx::query_scalar("SELECT category_id FROM monthly_budgets WHERE month = '2026-02'")
+            .fetch_all(&db.pool)
+            .await
+            .unwrap();
+        assert!(budgeted.contains(&dining));
+        assert!(!budgeted.contains(&gym));
+
+        CategoryService::copy_budgets_range(None, &db, "2026-01", "2026-03", "2026-04", true).await.unwrap();
+        let budgeted: Vec<i64> = sqlx::query_scalar("SELECT category_id FROM monthly_budgets WHERE month = '2026-04'")
+            .fetch_all(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(budgeted, vec![dining]);
+    }
+
+    #[tokio::test]
+    async fn test_budget_balance_check_reports_gap() {
+        let db = get_test_db().await;
+        let wages = CategoryService::create_category(None, &db, "Wages".to_string(), true, None).await.unwrap();
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let travel = CategoryService::create_category(None, &db, "Travel".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, wages, "2026-05".to_string(), 1000.0, RoundingMode::HalfUp).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, dining, "2026-05".to_string(), 400.0, RoundingMode::HalfUp).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, travel, "2026-05".to_string(), 750.0, RoundingMode::HalfUp).await.unwrap();
+
+        let check = CategoryService::budget_balance_check(&db, "2026-05").await.unwrap();
+        assert_eq!(check.planned_income, 100000);
+        assert_eq!(check.planned_expenses, 115000);
+        assert_eq!(check.gap, 15000);
+        assert!(check.over_budget);
+
+        CategoryService::set_monthly_limit(None, &db, travel, "2026-05".to_string(), 500.0, RoundingMode::HalfUp).await.unwrap();
+        let check = CategoryService::budget_balance_check(&db, "2026-05").await.unwrap();
+        assert_eq!(check.gap, 0);
+        assert!(!check.over_budget);
+    }
+
+    #[tokio::test]
+    async fn test_icon_round_trips_and_rejects_text() {
+        let db = get_test_db().await;
+        let coffee = CategoryService::create_category_with_color(None, &db, "Coffee".to_string(), false, "#FFDFBA".to_string(), Some(" ☕ ".to_string()), None).await.unwrap();
+        assert_eq!(CategoryService::get_category(&db, coffee).await.unwrap().icon.as_deref(), Some("☕"));
+
+        let update = |icon| UpdateCategoryRequest { name: "Coffee Shops".to_string(), color: None, is_income: false, is_active: true, icon };
+
+        // Leaving the icon out of an update keeps it
+        CategoryService::update_category(None, &db, coffee, update(None)).await.unwrap();
+        let view = CategoryService::get_budget_view(&db, "2026-01").await.unwrap().into_iter().find(|v| v.category.id == coffee).unwrap();
+        assert_eq!(view.category.icon.as_deref(), Some("☕"));
+
+        CategoryService::update_category(None, &db, coffee, update(Some("👩‍💻".to_string()))).await.unwrap();
+        assert_eq!(CategoryService::get_category(&db, coffee).await.unwrap().icon.as_deref(), Some("👩‍💻"));
+
+        let too_long = "x".repeat(50);
+        let err = CategoryService::update_category(None, &db, coffee, update(Some(too_long.clone()))).await;
+        assert!(matches!(err, Err(CategoryError::InvalidInput(_))));
+        let err = CategoryService::create_category_with_color(None, &db, "Tea".to_string(), false, "#FFDFBA".to_string(), Some(too_long), None).await;
+        assert!(matches!(err, Err(CategoryError::InvalidInput(_))));
+        assert_eq!(CategoryService::get_category(&db, coffee).await.unwrap().icon.as_deref(), Some("👩‍💻"));
+
+        CategoryService::update_category(None, &db, coffee, update(Some("".to_string()))).await.unwrap();
+        assert_eq!(CategoryService::get_category(&db, coffee).await.unwrap().icon, None);
+    }
+
+    #[test]
+    fn test_random_color_comes_from_the_palette() {
+        let palette: Palette = "#102030,#405060".parse().unwrap();
+        for _ in 0..20 {
+            assert!(palette.colors().contains(&CategoryService::get_random_pastel_color(&palette)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_budget_diff_lists_changed_added_and_removed() {
+        let db = get_test_db().await;
+        let create = async |name: &str| CategoryService::create_category(None, &db, name.to_string(), false, None).await.unwrap();
+        let (fuel, gym, books, pets) = (create("Fuel").await, create("Gym").await, create("Books").await, create("Pets").await);
+        for (id, month, dollars) in [
+            (fuel, "2026-01", 100.0), (fuel, "2026-02", 140.0),
+            (gym, "2026-01", 30.0),
+            (books, "2026-02", 25.0),
+            (pets, "2026-01", 60.0), (pets, "2026-02", 60.0),
+        ] {
+            CategoryService::set_monthly_limit(None, &db, id, month.to_string(), dollars, RoundingMode::HalfUp).await.unwrap();
+        }
+
+        let diff = CategoryService::budget_diff(&db, "2026-01", "2026-02").await.unwrap();
+        let mine: Vec<_> = diff.into_iter().filter(|d| [fuel, gym, books, pets].contains(&d.category_id)).collect();
+        assert_eq!(mine, vec![
+            BudgetDiff { category_id: fuel, name: "Fuel".to_string(), limit_a: Some(10000), limit_b: Some(14000), delta: 4000, change: BudgetChange::Changed },
+            BudgetDiff { category_id: gym, name: "Gym".to_string(), limit_a: Some(3000), limit_b: None, delta: -3000, change: BudgetChange::Removed },
+            BudgetDiff { category_id: books, name: "Books".to_string(), limit_a: None, limit_b: Some(2500), delta: 2500, change: BudgetChange::Added },
+        ]);
+
+        assert!(matches!(CategoryService::budget_diff(&db, "2026-13", "2026-02").await, Err(CategoryError::InvalidInput(_))));
+    }
+}
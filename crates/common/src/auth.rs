@@ -2,21 +2,115 @@ use axum::{
     middleware::Next,
     response::{IntoResponse, Redirect, Response},
     extract::{Request, State},
+    http::{header, HeaderMap, StatusCode},
 };
-use tower_sessions::Session;
+use tower_sessions::{cookie::{time::OffsetDateTime, SameSite}, Expiry, MemoryStore, Session, SessionManagerLayer};
 use std::sync::Arc;
-use crate::AppState;
+use crate::{AppState, Config};
 
 pub const AUTH_SESSION_KEY: &str = "authenticated";
+/// Unix time the session logged in, for `session_ttl_minutes`.
+pub const AUTH_AT_SESSION_KEY: &str = "authenticated_at";
+/// Unix time of the session's last authenticated request, for `idle_timeout_minutes`.
+pub const LAST_SEEN_SESSION_KEY: &str = "last_seen";
+/// The `YYYY-MM` month the session last opened, where `/` lands next time.
+pub const LAST_MONTH_SESSION_KEY: &str = "last_month";
+
+/// Role held by anyone signed in with the app password.
+pub const OWNER_ROLE: &str = "owner";
+
+/// Who is making a request. `auth_middleware` attaches one as a request extension and handlers pass
+/// it to the mutating service methods.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserContext {
+    pub user_id: i64,
+    pub roles: Vec<String>,
+}
+
+impl UserContext {
+    /// The single shared-password user, who may do everything.
+    pub fn owner() -> Self {
+        Self { user_id: 1, roles: vec![OWNER_ROLE.to_string()] }
+    }
+
+    /// Checks the caller may modify data: a context without roles is refused. `None` is an
+    /// internal caller (startup jobs, tests) and is allowed.
+    pub fn authorize(ctx: Option<&UserContext>) -> Result<(), String> {
+        match ctx {
+            Some(ctx) if ctx.roles.is_empty() => Err("No roles assigned".to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// `next` if it is safe to redirect to after login: a path on this site. Anything that a browser
+/// could resolve to another origin (`https://...`, `//host`, `/\host`) or that is not a path at all
+/// is refused, so the login page cannot be used as an open redirect.
+pub fn safe_next(next: &str) -> Option<&str> {
+    let local = next.starts_with('/')
+        && !next.starts_with("//")
+        && !next.contains('\\')
+        && !next.chars().any(|c| c.is_control());
+    local.then_some(next)
+}
+
+/// The login page URL that returns to `path` afterwards.
+pub fn login_url(path: &str) -> String {
+    if path.is_empty() || path == "/" {
+        return "/login".to_string();
+    }
+
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    format!("/login?next={}", encoded)
+}
+
+/// Unix time an authenticated session stops being valid: `idle_timeout_minutes` after its last
+/// request, but no later than `session_ttl_minutes` after login. `None` when neither is configured.
+pub fn session_deadline(config: &Config, authenticated_at: i64, last_seen: i64) -> Option<i64> {
+    let idle = config.idle_timeout_minutes.map(|minutes| last_seen + minutes as i64 * 60);
+    let absolute = config.session_ttl_minutes.map(|minutes| authenticated_at + minutes as i64 * 60);
+    match (idle, absolute) {
+        (Some(idle), Some(absolute)) => Some(idle.min(absolute)),
+        (idle, absolute) => idle.or(absolute),
+    }
+}
+
+/// Checks an authenticated session against the timeouts and records this request as activity,
+/// sliding the session's expiry forward. False (and the session flushed) once it has timed out.
+async fn refresh_session(session: &Session, config: &Config) -> bool {
+    let now = chrono::Utc::now().timestamp();
+    let authenticated_at: i64 = session.get(AUTH_AT_SESSION_KEY).await.unwrap_or(None).unwrap_or(now);
+    let last_seen: i64 = session.get(LAST_SEEN_SESSION_KEY).await.unwrap_or(None).unwrap_or(authenticated_at);
+
+    if session_deadline(config, authenticated_at, last_seen).is_some_and(|deadline| deadline <= now) {
+        let _ = session.flush().await;
+        return false;
+    }
+
+    let _ = session.insert(LAST_SEEN_SESSION_KEY, now).await;
+    if let Some(deadline) = session_deadline(config, authenticated_at, now)
+        && let Ok(at) = OffsetDateTime::from_unix_timestamp(deadline)
+    {
+        session.set_expiry(Some(Expiry::AtDateTime(at)));
+    }
+    true
+}
 
 pub async fn auth_middleware(
     State(state): State<Arc<AppState>>,
     session: Session,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Response {
     // If no password is set, authentication is disabled
     if state.config.app_password.is_none() {
+        request.extensions_mut().insert(UserContext::owner());
         return next.run(request).await;
     }
 
@@ -26,9 +120,300 @@ pub async fn auth_middleware(
         .unwrap_or(None)
         .unwrap_or(false);
 
-    if authenticated {
+    if authenticated && refresh_session(&session, &state.config).await {
+        request.extensions_mut().insert(UserContext::owner());
         next.run(request).await
+    } else if is_document_request(request.headers()) {
+        // Back to the page they asked for once they have logged in
+        let requested = request.uri().path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
+        Redirect::to(&state.config.url_for(&login_url(requested))).into_response()
     } else {
-        Redirect::to("/login").into_response()
+        // HTMX/fetch callers would otherwise follow the redirect and swap the login page into a fragment
+        (
+            StatusCode::UNAUTHORIZED,
+            [("HX-Redirect", state.config.url_for("/login")), (header::CONTENT_TYPE.as_str(), "application/json".to_string())],
+            r#"{"error":"Authentication required"}"#,
+        )
+            .into_response()
+    }
+}
+
+/// The session layer with cookie attributes taken from the config.
+pub fn session_layer(config: &Config) -> SessionManagerLayer<MemoryStore> {
+    SessionManagerLayer::new(MemoryStore::default())
+        .with_name(config.cookie_name.clone())
+        .with_secure(config.secure_cookies)
+        .with_same_site(SameSite::Lax)
+}
+
+/// Adds `Secure` to cookies set on requests a TLS-terminating proxy forwarded as
+/// `X-Forwarded-Proto: https`, since the app itself only sees plain HTTP there.
+/// Must run outside the session layer so it sees the `Set-Cookie` header.
+pub async fn forwarded_proto_middleware(request: Request, next: Next) -> Response {
+    let forwarded_https = request
+        .headers()
+        .get("X-Forwarded-Proto")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|proto| proto.eq_ignore_ascii_case("https"));
+
+    let mut response = next.run(request).await;
+    if !forwarded_https {
+        return response;
+    }
+
+    let cookies: Vec<_> = response.headers().get_all(header::SET_COOKIE).iter().cloned().collect();
+    response.headers_mut().remove(header::SET_COOKIE);
+    for cookie in cookies {
+        let value = match cookie.to_str() {
+            Ok(v) if !v.split(';').any(|attr| attr.trim().eq_ignore_ascii_case("secure")) => {
+                format!("{}; Secure", v).parse().unwrap_or(cookie)
+            }
+            _ => cookie,
+        };
+        response.headers_mut().append(header::SET_COOKIE, value);
+    }
+    response
+}
+
+/// True for top-level browser navigations, false for HTMX and API (fetch/JSON) requests.
+fn is_document_request(headers: &HeaderMap) -> bool {
+    if headers.contains_key("HX-Request") {
+        return false;
+    }
+
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .map(|accept| accept.contains("text/html"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use database::{get_test_db, TestDb};
+    use tower::ServiceExt;
+
+    async fn protected_app() -> (Router, TestDb) {
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(
+            db.clone(),
+            Config {
+                app_password: Some("secret".to_string()),
+                ..Config::default()
+            },
+        ));
+
+        let app = Router::new()
+            .route("/budget", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(state.clone(), auth_middleware))
+            .with_state(state)
+            .layer(SessionManagerLayer::new(MemoryStore::default()));
+        (app, db)
+    }
+
+    #[tokio::test]
+    async fn test_html_navigation_redirects_to_login() {
+        let (app, _db) = protected_app().await;
+        let request = Request::builder()
+            .uri("/budget")
+            .header(header::ACCEPT, "text/html,application/xhtml+xml")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert!(response.status().is_redirection());
+        assert_eq!(response.headers().get(header::LOCATION).unwrap(), "/login?next=/budget");
+    }
+
+    #[test]
+    fn test_safe_next_only_allows_local_paths() {
+        assert_eq!(safe_next("/budget/2026-01"), Some("/budget/2026-01"));
+        assert_eq!(safe_next("/budget/2026-01?filter=over"), Some("/budget/2026-01?filter=over"));
+        for unsafe_next in ["https://evil.com", "//evil.com", "/\\evil.com", "evil.com", "javascript:alert(1)", "", "/\nSet-Cookie"] {
+            assert_eq!(safe_next(unsafe_next), None, "{:?} should be refused", unsafe_next);
+        }
+    }
+
+    #[test]
+    fn test_login_url_encodes_the_query() {
+        assert_eq!(login_url("/"), "/login");
+        assert_eq!(login_url("/budget/2026-01?filter=over&sort=date_asc"), "/login?next=/budget/2026-01%3Ffilter%3Dover%26sort%3Ddate_asc");
+    }
+
+    #[tokio::test]
+    async fn test_htmx_request_gets_401_with_hx_redirect() {
+        let (app, _db) = protected_app().await;
+        let request = Request::builder()
+            .uri("/budget")
+            .header(header::ACCEPT, "text/html")
+            .header("HX-Request", "true")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers().get("HX-Redirect").unwrap(), "/login");
+    }
+
+    #[tokio::test]
+    async fn test_json_request_gets_401() {
+        let (app, _db) = protected_app().await;
+        let request = Request::builder()
+            .uri("/budget")
+            .header(header::ACCEPT, "application/json")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_attaches_owner_context() {
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(db.clone(), Config { app_password: None, ..Config::default() }));
+        let app = Router::new()
+            .route("/budget", get(|ctx: Option<axum::Extension<UserContext>>| async move {
+                assert_eq!(ctx.as_deref(), Some(&UserContext::owner()));
+                "ok"
+            }))
+            .layer(middleware::from_fn_with_state(state.clone(), auth_middleware))
+            .with_state(state)
+            .layer(SessionManagerLayer::new(MemoryStore::default()));
+
+        let response = app.oneshot(Request::builder().uri("/budget").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    async fn session_cookie(config: Config, forwarded_proto: Option<&str>) -> String {
+        let app = Router::new()
+            .route("/", get(|session: Session| async move {
+                session.insert("seen", true).await.unwrap();
+                "ok"
+            }))
+            .layer(session_layer(&config))
+            .layer(middleware::from_fn(forwarded_proto_middleware));
+
+        let mut request = Request::builder().uri("/");
+        if let Some(proto) = forwarded_proto {
+            request = request.header("X-Forwarded-Proto", proto);
+        }
+        let response = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        response.headers()[header::SET_COOKIE].to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_session_cookie_follows_secure_cookies_config() {
+        let secure = session_cookie(Config { secure_cookies: true, ..Config::default() }, None).await;
+        assert!(secure.contains("; Secure"));
+        assert!(secure.contains("SameSite=Lax"));
+
+        let insecure = session_cookie(Config { secure_cookies: false, ..Config::default() }, None).await;
+        assert!(!insecure.contains("Secure"));
+        assert!(insecure.contains("SameSite=Lax"));
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_https_marks_cookie_secure() {
+        let cookie = session_cookie(Config { secure_cookies: false, ..Config::default() }, Some("https")).await;
+        assert_eq!(cookie.matches("Secure").count(), 1);
+
+        let cookie = session_cookie(Config { secure_cookies: true, ..Config::default() }, Some("https")).await;
+        assert_eq!(cookie.matches("Secure").count(), 1, "not added twice");
+    }
+
+    /// A protected `/budget` plus `/login`, and `/age/{minutes}` which moves the session's
+    /// timestamps back as if that much time had passed.
+    async fn timeout_app(idle_timeout_minutes: Option<u64>, session_ttl_minutes: Option<u64>) -> (Router, TestDb) {
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(
+            db.clone(),
+            Config {
+                app_password: Some("secret".to_string()),
+                idle_timeout_minutes,
+                session_ttl_minutes,
+                ..Config::default()
+            },
+        ));
+
+        let protected = Router::new()
+            .route("/budget", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(state.clone(), auth_middleware));
+        let app = Router::new()
+            .merge(protected)
+            .route("/login", get(|session: Session| async move {
+                session.insert(AUTH_SESSION_KEY, true).await.unwrap();
+                session.insert(AUTH_AT_SESSION_KEY, chrono::Utc::now().timestamp()).await.unwrap();
+                "ok"
+            }))
+            .route("/age/{minutes}", get(|session: Session, axum::extract::Path(minutes): axum::extract::Path<i64>| async move {
+                for key in [AUTH_AT_SESSION_KEY, LAST_SEEN_SESSION_KEY] {
+                    if let Some(at) = session.get::<i64>(key).await.unwrap() {
+                        session.insert(key, at - minutes * 60).await.unwrap();
+                    }
+                }
+                "ok"
+            }))
+            .with_state(state)
+            .layer(SessionManagerLayer::new(MemoryStore::default()));
+        (app, db)
+    }
+
+    async fn get_with_cookie(app: &Router, uri: &str, cookie: &str) -> StatusCode {
+        let request = Request::builder()
+            .uri(uri)
+            .header(header::COOKIE, cookie)
+            .header(header::ACCEPT, "application/json")
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    async fn log_in(app: &Router) -> String {
+        let response = app.clone().oneshot(Request::builder().uri("/login").body(Body::empty()).unwrap()).await.unwrap();
+        let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+        cookie.split(';').next().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_activity_keeps_idle_session_alive() {
+        let (app, _db) = timeout_app(Some(30), None).await;
+        let cookie = log_in(&app).await;
+
+        for _ in 0..4 {
+            get_with_cookie(&app, "/age/20", &cookie).await;
+            assert_eq!(get_with_cookie(&app, "/budget", &cookie).await, StatusCode::OK);
+        }
+
+        get_with_cookie(&app, "/age/31", &cookie).await;
+        assert_eq!(get_with_cookie(&app, "/budget", &cookie).await, StatusCode::UNAUTHORIZED);
+        // The session was logged out, not just refused once
+        assert_eq!(get_with_cookie(&app, "/budget", &cookie).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_session_ttl_expires_despite_activity() {
+        let (app, _db) = timeout_app(Some(30), Some(60)).await;
+        let cookie = log_in(&app).await;
+
+        for _ in 0..2 {
+            get_with_cookie(&app, "/age/25", &cookie).await;
+            assert_eq!(get_with_cookie(&app, "/budget", &cookie).await, StatusCode::OK);
+        }
+
+        get_with_cookie(&app, "/age/25", &cookie).await;
+        assert_eq!(get_with_cookie(&app, "/budget", &cookie).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_session_deadline_takes_the_earlier_limit() {
+        let config = |idle, ttl| Config { idle_timeout_minutes: idle, session_ttl_minutes: ttl, ..Config::default() };
+        assert_eq!(session_deadline(&config(None, None), 0, 600), None);
+        assert_eq!(session_deadline(&config(Some(30), None), 0, 600), Some(600 + 1800));
+        assert_eq!(session_deadline(&config(None, Some(60)), 0, 600), Some(3600));
+        assert_eq!(session_deadline(&config(Some(30), Some(60)), 0, 3000), Some(3600));
     }
-}
\ No newline at end of file
+}
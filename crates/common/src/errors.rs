@@ -0,0 +1,290 @@
+use crate::AppState;
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use database::RepositoryError;
+use serde_json::json;
+use std::fmt;
+use std::sync::Arc;
+
+/// Names the domain a [`DomainError`] comes from, so the shared variants can still say what was
+/// not found.
+pub trait Domain: fmt::Debug + Default {
+    /// The 404 message, e.g. "Card not found".
+    const NOT_FOUND: &'static str;
+}
+
+/// The error every domain service returns, aliased per crate (e.g. `CardError`).
+#[derive(Debug)]
+pub enum DomainError<D: Domain> {
+    InvalidInput(String),
+    /// Carries the domain marker; build it with [`DomainError::not_found`].
+    NotFound(D),
+    Conflict(String),
+    Forbidden(String),
+    /// Temporarily unable to serve, e.g. no free database connection; clients should retry.
+    Unavailable,
+    Infrastructure(String),
+}
+
+/// Seconds clients are told to wait in `Retry-After` before retrying an `Unavailable` response.
+pub const RETRY_AFTER_SECS: u64 = 2;
+
+impl<D: Domain> DomainError<D> {
+    pub fn not_found() -> Self {
+        DomainError::NotFound(D::default())
+    }
+
+    /// The same error for another domain, for a service passing on what a service from another
+    /// crate returned. A not-found there is not the caller's missing resource, so it becomes
+    /// `Infrastructure`.
+    pub fn recast<E: Domain>(self) -> DomainError<E> {
+        match self {
+            DomainError::InvalidInput(msg) => DomainError::InvalidInput(msg),
+            DomainError::Conflict(msg) => DomainError::Conflict(msg),
+            DomainError::Forbidden(msg) => DomainError::Forbidden(msg),
+            DomainError::Unavailable => DomainError::Unavailable,
+            DomainError::Infrastructure(msg) => DomainError::Infrastructure(msg),
+            not_found @ DomainError::NotFound(_) => DomainError::Infrastructure(not_found.to_string()),
+        }
+    }
+
+    /// Status and user-facing message, shared by the JSON and the HTML form responses.
+    pub fn status_and_message(&self) -> (StatusCode, String) {
+        match self {
+            DomainError::InvalidInput(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
+            DomainError::NotFound(_) => (StatusCode::NOT_FOUND, D::NOT_FOUND.to_string()),
+            DomainError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
+            DomainError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.clone()),
+            DomainError::Unavailable => (StatusCode::SERVICE_UNAVAILABLE, "Server busy, try again shortly".to_string()),
+            DomainError::Infrastructure(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string()),
+        }
+    }
+}
+
+impl<D: Domain> fmt::Display for DomainError<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DomainError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+            DomainError::NotFound(_) => f.write_str(D::NOT_FOUND),
+            DomainError::Conflict(msg) => write!(f, "Conflict: {}", msg),
+            DomainError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
+            DomainError::Unavailable => f.write_str("Service unavailable: database busy"),
+            DomainError::Infrastructure(msg) => write!(f, "Database error: {}", msg),
+        }
+    }
+}
+
+impl<D: Domain> std::error::Error for DomainError<D> {}
+
+impl<D: Domain> From<RepositoryError> for DomainError<D> {
+    fn from(err: RepositoryError) -> Self {
+        match err {
+            RepositoryError::NotFound => DomainError::not_found(),
+            RepositoryError::UniqueViolation(msg) => DomainError::Conflict(msg),
+            RepositoryError::Busy => DomainError::Unavailable,
+            RepositoryError::Infrastructure(e) => DomainError::Infrastructure(e.to_string()),
+            _ => DomainError::Infrastructure(err.to_string()),
+        }
+    }
+}
+
+impl<D: Domain> IntoResponse for DomainError<D> {
+    fn into_response(self) -> Response {
+        if let DomainError::Infrastructure(detail) = self {
+            return internal_error(detail);
+        }
+
+        let (status, msg) = self.status_and_message();
+        if let DomainError::Unavailable = self {
+            return (status, [(header::RETRY_AFTER, RETRY_AFTER_SECS.to_string())], Json(json!({ "error": msg }))).into_response();
+        }
+        (status, Json(json!({ "error": msg }))).into_response()
+    }
+}
+
+/// The underlying cause of a 500, carried as a response extension so it never reaches the
+/// client unless `debug_errors_middleware` puts it there.
+#[derive(Clone, Debug)]
+pub struct ErrorDetail(pub String);
+
+/// Generic 500 response for infrastructure failures, with the real cause attached for debugging.
+pub fn internal_error(detail: String) -> Response {
+    tracing::error!("Internal server error: {}", detail);
+    let mut response = (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({ "error": "Internal server error" })),
+    )
+        .into_response();
+    response.extensions_mut().insert(ErrorDetail(detail));
+    response
+}
+
+/// True when the client expects an HTML page back, i.e. a plain browser form post or navigation,
+/// so form handlers can re-render their page with an inline error instead of returning JSON.
+/// An explicit `Accept: application/json` always wins.
+pub fn wants_html(headers: &HeaderMap) -> bool {
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    if accept.contains("application/json") {
+        return false;
+    }
+    if accept.contains("text/html") {
+        return true;
+    }
+
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("application/x-www-form-urlencoded"))
+}
+
+/// With `Config::debug_errors` on, adds the error detail to 500 bodies as `"detail"`.
+pub async fn debug_errors_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+    if !state.config.debug_errors {
+        return response;
+    }
+
+    match response.extensions().get::<ErrorDetail>().cloned() {
+        Some(ErrorDetail(detail)) => (
+            response.status(),
+            Json(json!({ "error": "Internal server error", "detail": detail })),
+        )
+            .into_response(),
+        None => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use database::get_test_db;
+    use tower::ServiceExt;
+
+    async fn body_for(debug_errors: bool) -> String {
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(db.clone(), Config { debug_errors, ..Config::default() }));
+
+        let app = Router::new()
+            .route("/boom", get(|| async { internal_error("no such table: widgets".to_string()) }))
+            .layer(middleware::from_fn_with_state(state.clone(), debug_errors_middleware))
+            .with_state(state);
+
+        let response = app
+            .oneshot(Request::builder().uri("/boom").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_detail_hidden_by_default() {
+        let body = body_for(false).await;
+        assert!(body.contains("Internal server error"));
+        assert!(!body.contains("no such table"));
+    }
+
+    #[tokio::test]
+    async fn test_detail_shown_with_debug_errors() {
+        let body = body_for(true).await;
+        assert!(body.contains("no such table: widgets"));
+    }
+
+    #[test]
+    fn test_wants_html() {
+        let headers = |pairs: &[(header::HeaderName, &str)]| {
+            let mut map = HeaderMap::new();
+            for (name, value) in pairs {
+                map.insert(name.clone(), value.parse().unwrap());
+            }
+            map
+        };
+
+        assert!(wants_html(&headers(&[(header::CONTENT_TYPE, "application/x-www-form-urlencoded")])));
+        assert!(wants_html(&headers(&[(header::ACCEPT, "text/html,application/xhtml+xml")])));
+        assert!(!wants_html(&headers(&[
+            (header::CONTENT_TYPE, "application/x-www-form-urlencoded"),
+            (header::ACCEPT, "application/json"),
+        ])));
+        assert!(!wants_html(&headers(&[(header::CONTENT_TYPE, "application/json")])));
+    }
+
+    #[derive(Debug, Default)]
+    struct Widgets;
+
+    impl Domain for Widgets {
+        const NOT_FOUND: &'static str = "Widget not found";
+    }
+
+    async fn status_and_body(err: DomainError<Widgets>) -> (StatusCode, String) {
+        let response = err.into_response();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_domain_error_status_per_variant() {
+        let (status, body) = status_and_body(DomainError::InvalidInput("Name is required".into())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("Name is required"));
+
+        let (status, body) = status_and_body(DomainError::not_found()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(body.contains("Widget not found"));
+
+        let (status, _) = status_and_body(DomainError::Conflict("Widget already exists".into())).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let (status, _) = status_and_body(DomainError::Forbidden("Read-only".into())).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let response = DomainError::<Widgets>::Unavailable.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], RETRY_AFTER_SECS.to_string());
+
+        let (status, body) = status_and_body(DomainError::Infrastructure("disk I/O error".into())).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!body.contains("disk I/O error"));
+    }
+
+    #[test]
+    fn test_domain_error_from_repository_error() {
+        let err: DomainError<Widgets> = RepositoryError::NotFound.into();
+        assert!(matches!(err, DomainError::NotFound(_)));
+        assert_eq!(err.to_string(), "Widget not found");
+
+        let err: DomainError<Widgets> = RepositoryError::UniqueViolation("name".into()).into();
+        assert!(matches!(err, DomainError::Conflict(msg) if msg == "name"));
+    }
+
+    #[derive(Debug, Default)]
+    struct Gadgets;
+
+    impl Domain for Gadgets {
+        const NOT_FOUND: &'static str = "Gadget not found";
+    }
+
+    #[test]
+    fn test_recast_keeps_the_kind() {
+        let busy: DomainError<Gadgets> = DomainError::<Widgets>::from(RepositoryError::Busy).recast();
+        assert!(matches!(busy, DomainError::Unavailable));
+        let invalid: DomainError<Gadgets> = DomainError::<Widgets>::InvalidInput("bad".into()).recast();
+        assert!(matches!(invalid, DomainError::InvalidInput(msg) if msg == "bad"));
+        let missing: DomainError<Gadgets> = DomainError::<Widgets>::not_found().recast();
+        assert!(matches!(missing, DomainError::Infrastructure(msg) if msg == "Widget not found"));
+    }
+}
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Deserializer};
+
+/// Serde `deserialize_with` helper for optional foreign keys such as `card_id`, so form posts and
+/// JSON bodies share one representation: a missing field, `null` and `""` all mean `None`, and an
+/// id may be sent as a number or a numeric string. Use with `#[serde(default)]`.
+pub fn deserialize_optional_id<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(i64),
+        Text(String),
+    }
+
+    match Option::<Raw>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Raw::Number(id)) => Ok(Some(id)),
+        Some(Raw::Text(s)) if s.trim().is_empty() => Ok(None),
+        Some(Raw::Text(s)) => s
+            .trim()
+            .parse::<i64>()
+            .map(Some)
+            .map_err(|_| serde::de::Error::custom(format!("invalid id: {:?}", s))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Payload {
+        #[serde(default, deserialize_with = "deserialize_optional_id")]
+        card_id: Option<i64>,
+    }
+
+    fn parse(json: &str) -> Result<Option<i64>, serde_json::Error> {
+        serde_json::from_str::<Payload>(json).map(|p| p.card_id)
+    }
+
+    #[test]
+    fn test_empty_null_and_missing_are_none() {
+        assert_eq!(parse(r#"{"card_id": ""}"#).unwrap(), None);
+        assert_eq!(parse(r#"{"card_id": null}"#).unwrap(), None);
+        assert_eq!(parse(r#"{}"#).unwrap(), None);
+    }
+
+    #[test]
+    fn test_number_and_numeric_string() {
+        assert_eq!(parse(r#"{"card_id": 3}"#).unwrap(), Some(3));
+        assert_eq!(parse(r#"{"card_id": "3"}"#).unwrap(), Some(3));
+        assert!(parse(r#"{"card_id": "visa"}"#).is_err());
+    }
+}
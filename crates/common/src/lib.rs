@@ -1,7 +1,17 @@
 use clap::Parser;
 use database::Database;
+use std::sync::Arc;
+
+pub use database::events;
 
 pub mod auth;
+pub mod errors;
+pub mod forms;
+pub mod money;
+pub mod month;
+pub mod palette;
+pub mod period;
+pub mod week;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -9,6 +19,18 @@ pub struct AppState {
     pub config: Config,
 }
 
+impl AppState {
+    pub fn new(db: Database, config: Config) -> Self {
+        Self { db, config }
+    }
+}
+
+impl axum::extract::FromRef<Arc<AppState>> for month::MonthBounds {
+    fn from_ref(state: &Arc<AppState>) -> Self {
+        state.config.month_bounds()
+    }
+}
+
 #[derive(Clone, Debug, Parser)]
 #[command(author, version, about, long_about = None)]
 pub struct Config {
@@ -20,9 +42,166 @@ pub struct Config {
 
     #[arg(long, env = "APP_PASSWORD")]
     pub app_password: Option<String>,
+
+    /// Path prefix the app is served under behind a reverse proxy, e.g. `/money` for
+    /// `https://home.example.com/money/` (none by default). Routes, links and redirects all carry it.
+    #[arg(long, env = "BASE_PATH", default_value = "", value_parser = parse_base_path)]
+    pub base_path: String,
+
+    /// Name shown in page titles and on the login page.
+    #[arg(long, env = "APP_NAME", default_value = "Budget")]
+    pub app_name: String,
+
+    /// How budget limits in dollars are rounded to cents (default: half-up, i.e. halves away from zero).
+    /// Transaction amounts are never rounded; more than two decimals is rejected.
+    #[arg(long, env = "ROUNDING_MODE", value_enum, default_value_t = money::RoundingMode::HalfUp)]
+    pub rounding_mode: money::RoundingMode,
+
+    /// How negative amounts are written on pages (default: minus, e.g. -$12.50). The JSON API always uses a minus.
+    #[arg(long, env = "NEGATIVE_STYLE", value_enum, default_value_t = money::NegativeStyle::Minus)]
+    pub negative_style: money::NegativeStyle,
+
+    /// How transaction and net amounts show direction on pages: signed (default, -$45.50) or magnitude ($45.50 out / $45.50 in).
+    #[arg(long, env = "EXPENSE_DISPLAY", value_enum, default_value_t = money::ExpenseDisplay::Signed)]
+    pub expense_display: money::ExpenseDisplay,
+
+    /// First day of the week for weekly breakdowns.
+    #[arg(long, env = "WEEK_START", value_enum, default_value_t = week::WeekStart::Monday)]
+    pub week_start: week::WeekStart,
+
+    /// List income categories before expenses in the month view's budget rows (each still by name).
+    #[arg(long, env = "INCOME_FIRST", default_value_t = true, action = clap::ArgAction::Set)]
+    pub income_first: bool,
+
+    /// Mark the session cookie `Secure` (on by default in release builds). Requests arriving with
+    /// `X-Forwarded-Proto: https` get a `Secure` cookie regardless, for TLS-terminating proxies.
+    #[arg(long, env = "SECURE_COOKIES", default_value_t = cfg!(not(debug_assertions)), action = clap::ArgAction::Set)]
+    pub secure_cookies: bool,
+
+    /// Name of the session cookie. Instances on sibling subdomains need distinct names, or their
+    /// cookies overwrite each other when scoped to the shared parent domain.
+    #[arg(long, env = "COOKIE_NAME", default_value = "id")]
+    pub cookie_name: String,
+
+    /// Log a session out after this many minutes without a request. Each request pushes the
+    /// deadline back. Unset means sessions never idle out.
+    #[arg(long, env = "IDLE_TIMEOUT_MINUTES")]
+    pub idle_timeout_minutes: Option<u64>,
+
+    /// Log a session out this many minutes after login, however active it is. Unset means no limit.
+    #[arg(long, env = "SESSION_TTL_MINUTES")]
+    pub session_ttl_minutes: Option<u64>,
+
+    /// Register `POST /admin/reset`, which deletes all data. Off by default; when off the route does not exist.
+    #[arg(long, env = "ALLOW_RESET", default_value_t = false)]
+    pub allow_reset: bool,
+
+    /// Include the underlying cause in 500 responses. For local development only.
+    #[arg(long, env = "DEBUG_ERRORS", default_value_t = false)]
+    pub debug_errors: bool,
+
+    /// Comma-separated `#RRGGBB` colors new categories are given at random and the management page
+    /// offers, replacing the built-in pastels. A malformed entry stops startup.
+    #[arg(long, env = "COLOR_PALETTE")]
+    pub color_palette: Option<palette::Palette>,
+
+    /// Earliest month (YYYY-MM) that can be browsed; earlier ones are refused with 400.
+    #[arg(long, env = "EARLIEST_MONTH", default_value = "2000-01")]
+    pub earliest_month: month::Month,
+
+    /// How many months past the current one can be browsed; later ones are refused with 400.
+    #[arg(long, env = "MAX_FUTURE_MONTHS", default_value_t = 24)]
+    pub max_future_months: u32,
+
+    /// Month (1-12) fiscal years start in, for reports grouped by fiscal year.
+    #[arg(long, env = "FISCAL_YEAR_START", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=12))]
+    pub fiscal_year_start: u32,
+
+    /// Any payday (YYYY-MM-DD), for reports grouped by pay cycle; unset disables that grouping.
+    #[arg(long, env = "PAY_CYCLE_ANCHOR")]
+    pub pay_cycle_anchor: Option<chrono::NaiveDate>,
+
+    /// Days from one payday to the next (14 for biweekly pay).
+    #[arg(long, env = "PAY_CYCLE_DAYS", default_value_t = 14, value_parser = clap::value_parser!(u32).range(1..=366))]
+    pub pay_cycle_days: u32,
+
+    /// Target share of income, in percent, for spending on categories classified as needs.
+    #[arg(long, env = "NEED_TARGET_PERCENT", default_value_t = 50, value_parser = clap::value_parser!(u32).range(0..=100))]
+    pub need_target_percent: u32,
+
+    /// Target share of income, in percent, for spending on categories classified as wants.
+    #[arg(long, env = "WANT_TARGET_PERCENT", default_value_t = 30, value_parser = clap::value_parser!(u32).range(0..=100))]
+    pub want_target_percent: u32,
+
+    /// Target share of income, in percent, for categories classified as savings.
+    #[arg(long, env = "SAVING_TARGET_PERCENT", default_value_t = 20, value_parser = clap::value_parser!(u32).range(0..=100))]
+    pub saving_target_percent: u32,
+
+    /// Log statements that take longer than this many milliseconds, at warn level with the elapsed
+    /// time (sqlx's one-second default when unset).
+    #[arg(long, env = "SLOW_QUERY_MS")]
+    pub slow_query_ms: Option<u64>,
+
+    /// Upper bound on the number of categories (unlimited when unset).
+    #[arg(long, env = "MAX_CATEGORIES")]
+    pub max_categories: Option<u32>,
+
+    /// Upper bound on the number of transactions dated in any one month (unlimited when unset).
+    #[arg(long, env = "MAX_TRANSACTIONS_PER_MONTH")]
+    pub max_transactions_per_month: Option<u32>,
+}
+
+impl Default for Config {
+    /// The clap defaults, as if the binary was started with no arguments.
+    fn default() -> Self {
+        <Self as clap::Parser>::parse_from(["budget"])
+    }
+}
+
+/// `/money/` and `money` both become `/money`; empty or `/` means no prefix.
+fn parse_base_path(value: &str) -> Result<String, String> {
+    let trimmed = value.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        return Ok(String::new());
+    }
+    if trimmed.contains(['?', '#', '\\']) || trimmed.contains("//") {
+        return Err(format!("\"{}\" is not a path", value));
+    }
+    Ok(format!("/{}", trimmed))
 }
 
 impl Config {
+    /// `path` (absolute, e.g. `/budget/2026-01`) under `base_path`. The root is the bare prefix,
+    /// since that is the route a nested `/` answers on.
+    pub fn url_for(&self, path: &str) -> String {
+        match (self.base_path.is_empty(), path) {
+            (true, _) => path.to_string(),
+            (false, "/") => self.base_path.clone(),
+            (false, _) => format!("{}{}", self.base_path, path),
+        }
+    }
+
+    /// The months requests may ask for, from `earliest_month` and `max_future_months`.
+    pub fn month_bounds(&self) -> month::MonthBounds {
+        month::MonthBounds { earliest: self.earliest_month, max_future: self.max_future_months }
+    }
+
+    /// Colors for new categories and the color picker: `color_palette`, or the built-in one.
+    pub fn palette(&self) -> palette::Palette {
+        self.color_palette.clone().unwrap_or_default()
+    }
+
+    /// The report grouping a `granularity` parameter asks for, with fiscal years and pay cycles as configured.
+    pub fn period(&self, granularity: &str) -> Result<period::Period, String> {
+        let pay_cycle = self.pay_cycle_anchor.map(|anchor| period::PayCycle { anchor, days: self.pay_cycle_days });
+        period::Period::from_param(granularity, self.fiscal_year_start, pay_cycle)
+    }
+
+    /// How pages format amounts, from `negative_style` and `expense_display`.
+    pub fn money_format(&self) -> money::MoneyFormat {
+        money::MoneyFormat { negative_style: self.negative_style, expense_display: self.expense_display }
+    }
+
     pub fn parse() -> Self {
         let config = <Self as clap::Parser>::parse();
         config.check_security();
@@ -39,3 +218,21 @@ impl Config {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_for_with_base_path() {
+        let config = Config::parse_from(["budget", "--base-path", "money/"]);
+        assert_eq!(config.base_path, "/money");
+        assert_eq!(config.url_for("/"), "/money");
+        assert_eq!(config.url_for("/budget/2026-01"), "/money/budget/2026-01");
+
+        let config = Config::default();
+        assert_eq!(config.url_for("/"), "/");
+        assert_eq!(config.url_for("/login"), "/login");
+        assert!(Config::try_parse_from(["budget", "--base-path", "/a//b"]).is_err());
+    }
+}
@@ -0,0 +1,463 @@
+use serde::{ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+
+/// How fractional cents are resolved when converting dollars to cents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum RoundingMode {
+    /// Halves round away from zero: 0.005 -> 1 cent, 0.015 -> 2 cents. The default.
+    #[default]
+    HalfUp,
+    /// Halves round to the even cent (banker's rounding): 0.005 -> 0 cents, 0.015 -> 2 cents.
+    HalfEven,
+    /// Fractions are truncated toward zero: 0.005 -> 0 cents, 0.015 -> 1 cent.
+    Down,
+}
+
+/// How negative amounts are written on pages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum NegativeStyle {
+    /// A leading minus: `-$1,234.50`. The default.
+    #[default]
+    Minus,
+    /// Accounting style: `($1,234.50)`.
+    Parentheses,
+}
+
+/// How transaction and summary amounts show their direction on pages. Storage is unaffected:
+/// expenses are always negative cents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExpenseDisplay {
+    /// Expenses negative, in the configured negative style: `-$45.50`. The default.
+    #[default]
+    Signed,
+    /// Always positive, with the direction spelled out: `$45.50 out`, `$45.50 in`.
+    Magnitude,
+}
+
+/// Cents as dollars with thousands separators, e.g. `$12,345.67`, writing negatives in `style`.
+pub fn format_cents_with(cents: i64, style: NegativeStyle) -> String {
+    let amount = format_magnitude(cents.unsigned_abs());
+    match (cents < 0, style) {
+        (false, _) => amount,
+        (true, NegativeStyle::Minus) => format!("-{}", amount),
+        (true, NegativeStyle::Parentheses) => format!("({})", amount),
+    }
+}
+
+/// Cents formatted for display with the sign dropped, for rows whose colour shows it.
+/// Unlike `format_cents(cents.abs())`, can't overflow on `i64::MIN`.
+pub fn format_cents_unsigned(cents: i64) -> String {
+    format_magnitude(cents.unsigned_abs())
+}
+
+fn format_magnitude(abs: u64) -> String {
+    let whole = (abs / 100).to_string();
+
+    let mut grouped = String::with_capacity(whole.len() + whole.len() / 3);
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    format!("${}.{:02}", grouped, abs % 100)
+}
+
+/// A signed amount (negative = money out) written per `display`; `style` applies to signed output only.
+pub fn format_amount_with(cents: i64, display: ExpenseDisplay, style: NegativeStyle) -> String {
+    match display {
+        ExpenseDisplay::Signed => format_cents_with(cents, style),
+        ExpenseDisplay::Magnitude => {
+            let direction = if cents < 0 { "out" } else { "in" };
+            format!("{} {}", format_magnitude(cents.unsigned_abs()), direction)
+        }
+    }
+}
+
+/// How amounts are written on pages, built from the config with `Config::money_format` and
+/// handed to the view models that format amounts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MoneyFormat {
+    pub negative_style: NegativeStyle,
+    pub expense_display: ExpenseDisplay,
+}
+
+impl MoneyFormat {
+    /// Cents as dollars with thousands separators, negatives in the configured style.
+    pub fn cents(&self, cents: i64) -> String {
+        format_cents_with(cents, self.negative_style)
+    }
+
+    /// A signed amount, using the configured expense display and negative style.
+    pub fn amount(&self, cents: i64) -> String {
+        format_amount_with(cents, self.expense_display, self.negative_style)
+    }
+}
+
+/// Converts dollars to cents with an explicit rounding mode.
+pub fn to_cents(dollars: f64, mode: RoundingMode) -> i64 {
+    // Snap away binary noise first so 0.015 (stored as 0.01499999...) is treated as an exact half
+    let scaled = (dollars * 100.0 * 1e6).round() / 1e6;
+    let cents = match mode {
+        RoundingMode::HalfUp => scaled.round(),
+        RoundingMode::HalfEven => scaled.round_ties_even(),
+        RoundingMode::Down => scaled.trunc(),
+    };
+    cents as i64
+}
+
+/// True when the amount has a fraction of a cent, e.g. `12.345`. Two decimal places are canonical
+/// for entered amounts, so these are rejected at entry instead of being silently rounded.
+pub fn has_fractional_cents(dollars: f64) -> bool {
+    let scaled = (dollars * 100.0 * 1e6).round() / 1e6;
+    scaled.fract() != 0.0
+}
+
+/// Parses a user-typed dollar amount.
+///
+/// Accepted forms:
+/// - `1234.56`, `-12.5`, `$12.50`, ` $ 12.50 ` (the currency symbol, and spaces at the ends or
+///   around the symbol, are ignored; spaces inside the number are not, so `45 50` is refused)
+/// - `$1,234.56` (commas before a decimal point are thousands separators)
+/// - `12,50` (a single comma followed by one or two digits is a decimal comma)
+/// - `1,234` (any other commas are thousands separators)
+pub fn parse_dollars(input: &str) -> Result<f64, String> {
+    let trimmed = input.trim();
+    let cleaned = match trimmed.split_once('$') {
+        Some((before, after)) => format!("{}{}", before.trim_end(), after.trim_start()),
+        None => trimmed.to_string(),
+    };
+    let invalid = || format!("'{}' is not a valid dollar amount", trimmed);
+    if cleaned.chars().any(char::is_whitespace) {
+        return Err(invalid());
+    }
+
+    if cleaned.is_empty() {
+        return Err("Amount is required".to_string());
+    }
+
+    let normalized = if cleaned.contains('.') {
+        cleaned.replace(',', "")
+    } else {
+        match cleaned.split_once(',') {
+            Some((whole, cents)) if !cents.contains(',') && (1..=2).contains(&cents.len()) => {
+                format!("{}.{}", whole, cents)
+            }
+            _ => cleaned.replace(',', ""),
+        }
+    };
+
+    // f64's parser also takes "inf", "NaN" and exponents, none of which are amounts
+    let digits = normalized.strip_prefix('-').unwrap_or(&normalized);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return Err(invalid());
+    }
+
+    normalized.parse::<f64>().map_err(|_| invalid())
+}
+
+/// Evaluates a typed amount that may be a sum, e.g. `45.50 + 12.25` for a split bill. Numbers take
+/// any form [`parse_dollars`] accepts; the only operators are `+ - * /`, with the usual precedence
+/// and a leading minus allowed on a number. Anything else (letters, parentheses) is refused.
+pub fn parse_dollar_expression(input: &str) -> Result<f64, String> {
+    let mut tokens = Vec::new();
+    let mut number = String::new();
+    for c in input.chars() {
+        match c {
+            '0'..='9' | '.' | ',' | '$' => number.push(c),
+            '+' | '-' | '*' | '/' => {
+                if !number.trim().is_empty() {
+                    tokens.push(ExprToken::Number(parse_dollars(&number)?));
+                }
+                number.clear();
+                tokens.push(ExprToken::Op(c));
+            }
+            c if c.is_whitespace() => number.push(c),
+            _ => return Err(format!("'{}' is not a valid dollar amount", input.trim())),
+        }
+    }
+    if !number.trim().is_empty() {
+        tokens.push(ExprToken::Number(parse_dollars(&number)?));
+    }
+    if tokens.is_empty() {
+        return Err("Amount is required".to_string());
+    }
+
+    let mut parser = ExprParser { tokens: &tokens, pos: 0, input: input.trim() };
+    let value = parser.sum()?;
+    if parser.pos != tokens.len() || !value.is_finite() {
+        return Err(parser.invalid());
+    }
+    Ok(value)
+}
+
+#[derive(Clone, Copy)]
+enum ExprToken {
+    Number(f64),
+    Op(char),
+}
+
+/// Recursive descent over `sum := product (('+'|'-') product)*`,
+/// `product := factor (('*'|'/') factor)*`, `factor := '-'? number`.
+struct ExprParser<'a> {
+    tokens: &'a [ExprToken],
+    pos: usize,
+    input: &'a str,
+}
+
+impl ExprParser<'_> {
+    fn invalid(&self) -> String {
+        format!("'{}' is not a valid amount expression", self.input)
+    }
+
+    fn next_op(&mut self, ops: &[char]) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(ExprToken::Op(op)) if ops.contains(op) => {
+                self.pos += 1;
+                Some(*op)
+            }
+            _ => None,
+        }
+    }
+
+    fn sum(&mut self) -> Result<f64, String> {
+        let mut value = self.product()?;
+        while let Some(op) = self.next_op(&['+', '-']) {
+            let rhs = self.product()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Ok(value)
+    }
+
+    fn product(&mut self) -> Result<f64, String> {
+        let mut value = self.factor()?;
+        while let Some(op) = self.next_op(&['*', '/']) {
+            let rhs = self.factor()?;
+            if op == '/' && rhs == 0.0 {
+                return Err("Cannot divide an amount by zero".to_string());
+            }
+            value = if op == '*' { value * rhs } else { value / rhs };
+        }
+        Ok(value)
+    }
+
+    fn factor(&mut self) -> Result<f64, String> {
+        if self.next_op(&['-']).is_some() {
+            return Ok(-self.factor()?);
+        }
+        match self.tokens.get(self.pos) {
+            Some(ExprToken::Number(n)) => {
+                self.pos += 1;
+                Ok(*n)
+            }
+            _ => Err(self.invalid()),
+        }
+    }
+}
+
+/// An amount in cents as API clients see it: `{"cents": -4550, "formatted": "-$45.50"}`, so they
+/// never have to guess whether a number is cents or dollars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Money(pub i64);
+
+impl Money {
+    /// Dollars with a leading minus for negatives and thousands separators, e.g. `-$1,234.50`.
+    /// Always minus, whatever the page style, so API output does not depend on configuration.
+    pub fn formatted(&self) -> String {
+        format_cents_with(self.0, NegativeStyle::Minus)
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Money", 2)?;
+        state.serialize_field("cents", &self.0)?;
+        state.serialize_field("formatted", &self.formatted())?;
+        state.end()
+    }
+}
+
+/// Serde `serialize_with` helper for `i64` cents fields, writing them as [`Money`].
+pub fn serialize_cents<S: Serializer>(cents: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+    Money(*cents).serialize(serializer)
+}
+
+/// Serde `deserialize_with` helper accepting either a number or a string handled by [`parse_dollars`].
+pub fn deserialize_dollars<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_amount(deserializer, parse_dollars)
+}
+
+/// Like [`deserialize_dollars`], but strings may be sums handled by [`parse_dollar_expression`].
+pub fn deserialize_dollar_expression<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_amount(deserializer, parse_dollar_expression)
+}
+
+fn deserialize_amount<'de, D>(deserializer: D, parse: fn(&str) -> Result<f64, String>) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(f64),
+        Text(String),
+    }
+
+    match Raw::deserialize(deserializer)? {
+        Raw::Number(n) => Ok(n),
+        Raw::Text(s) => parse(&s).map_err(|e| serde::de::Error::custom(format!("amount_dollars: {}", e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_cents_half_up() {
+        assert_eq!(to_cents(0.005, RoundingMode::HalfUp), 1);
+        assert_eq!(to_cents(0.015, RoundingMode::HalfUp), 2);
+        assert_eq!(to_cents(-0.015, RoundingMode::HalfUp), -2);
+    }
+
+    #[test]
+    fn test_to_cents_half_even() {
+        assert_eq!(to_cents(0.005, RoundingMode::HalfEven), 0);
+        assert_eq!(to_cents(0.015, RoundingMode::HalfEven), 2);
+        assert_eq!(to_cents(0.025, RoundingMode::HalfEven), 2);
+    }
+
+    #[test]
+    fn test_to_cents_down() {
+        assert_eq!(to_cents(0.005, RoundingMode::Down), 0);
+        assert_eq!(to_cents(0.015, RoundingMode::Down), 1);
+        assert_eq!(to_cents(12.349, RoundingMode::Down), 1234);
+    }
+
+    #[test]
+    fn test_to_cents_whole_amounts_are_exact() {
+        for mode in [RoundingMode::HalfUp, RoundingMode::HalfEven, RoundingMode::Down] {
+            assert_eq!(to_cents(19.99, mode), 1999);
+            assert_eq!(to_cents(0.29, mode), 29);
+        }
+    }
+
+    #[test]
+    fn test_parse_dollars_thousands_and_symbol() {
+        assert_eq!(parse_dollars("$1,234.56"), Ok(1234.56));
+        assert_eq!(parse_dollars("1,234"), Ok(1234.0));
+    }
+
+    #[test]
+    fn test_parse_dollars_plain() {
+        assert_eq!(parse_dollars("1234.56"), Ok(1234.56));
+        assert_eq!(parse_dollars(" -12.5 "), Ok(-12.5));
+        assert_eq!(parse_dollars(" $ 12.50 "), Ok(12.5));
+        assert_eq!(parse_dollars("- $12.50"), Ok(-12.5));
+    }
+
+    #[test]
+    fn test_parse_dollars_decimal_comma() {
+        assert_eq!(parse_dollars("12,50"), Ok(12.5));
+        assert_eq!(parse_dollars("$7,5"), Ok(7.5));
+    }
+
+    #[test]
+    fn test_parse_dollars_rejects_invalid() {
+        assert!(parse_dollars("").is_err());
+        assert!(parse_dollars("abc").is_err());
+        assert!(parse_dollars("1.2.3").is_err());
+        assert!(parse_dollars("inf").is_err());
+        assert!(parse_dollars("1e5").is_err());
+        assert_eq!(parse_dollars("45 50"), Err("'45 50' is not a valid dollar amount".to_string()));
+        assert!(parse_dollars("1 2").is_err());
+        assert!(parse_dollars("$1 234.56").is_err());
+    }
+
+    #[test]
+    fn test_deserialize_dollars_from_string_or_number() {
+        #[derive(Deserialize)]
+        struct Form {
+            #[serde(deserialize_with = "deserialize_dollars")]
+            amount_dollars: f64,
+        }
+
+        let form: Form = serde_json::from_str(r#"{"amount_dollars": "$1,234.56"}"#).unwrap();
+        assert_eq!(form.amount_dollars, 1234.56);
+        let form: Form = serde_json::from_str(r#"{"amount_dollars": 12.5}"#).unwrap();
+        assert_eq!(form.amount_dollars, 12.5);
+
+        let err = serde_json::from_str::<Form>(r#"{"amount_dollars": "twelve"}"#).err().unwrap();
+        assert!(err.to_string().contains("amount_dollars"));
+    }
+
+    #[test]
+    fn test_has_fractional_cents() {
+        assert!(has_fractional_cents(12.345));
+        assert!(has_fractional_cents(0.001));
+        assert!(!has_fractional_cents(12.34));
+        assert!(!has_fractional_cents(12.340));
+        assert!(!has_fractional_cents(0.07)); // 0.07 * 100 is 7.000000000000001 in f64
+        assert!(!has_fractional_cents(-45.5));
+    }
+
+    #[test]
+    fn test_money_serializes_cents_and_formatted() {
+        assert_eq!(
+            serde_json::to_value(Money(-4550)).unwrap(),
+            serde_json::json!({ "cents": -4550, "formatted": "-$45.50" })
+        );
+        assert_eq!(Money(123456789).formatted(), "$1,234,567.89");
+        assert_eq!(Money(5).formatted(), "$0.05");
+        assert_eq!(Money(-100000).formatted(), "-$1,000.00");
+    }
+
+    #[test]
+    fn test_format_cents_thousands_and_negative_styles() {
+        assert_eq!(format_cents_with(1234567, NegativeStyle::Minus), "$12,345.67");
+        assert_eq!(format_cents_with(-1234567, NegativeStyle::Minus), "-$12,345.67");
+        assert_eq!(format_cents_with(-1234567, NegativeStyle::Parentheses), "($12,345.67)");
+        assert_eq!(format_cents_with(99, NegativeStyle::Parentheses), "$0.99");
+        assert_eq!(format_cents_with(0, NegativeStyle::Parentheses), "$0.00");
+        assert_eq!(format_cents_unsigned(-1234567), "$12,345.67");
+        assert_eq!(format_cents_with(i64::MIN, NegativeStyle::Minus), "-$92,233,720,368,547,758.08");
+    }
+
+    #[test]
+    fn test_format_amount_signed_mode() {
+        assert_eq!(format_amount_with(-4550, ExpenseDisplay::Signed, NegativeStyle::Minus), "-$45.50");
+        assert_eq!(format_amount_with(-4550, ExpenseDisplay::Signed, NegativeStyle::Parentheses), "($45.50)");
+        assert_eq!(format_amount_with(4550, ExpenseDisplay::Signed, NegativeStyle::Minus), "$45.50");
+    }
+
+    #[test]
+    fn test_format_amount_magnitude_mode() {
+        assert_eq!(format_amount_with(-4550, ExpenseDisplay::Magnitude, NegativeStyle::Minus), "$45.50 out");
+        assert_eq!(format_amount_with(-4550, ExpenseDisplay::Magnitude, NegativeStyle::Parentheses), "$45.50 out");
+        assert_eq!(format_amount_with(4550, ExpenseDisplay::Magnitude, NegativeStyle::Minus), "$45.50 in");
+    }
+
+    #[test]
+    fn test_parse_dollar_expression_evaluates_sums() {
+        assert_eq!(parse_dollar_expression("45.50+12.25"), Ok(57.75));
+        assert_eq!(parse_dollar_expression(" $45.50 + $12.25 "), Ok(57.75));
+        assert_eq!(parse_dollar_expression("10 + 3 * 2"), Ok(16.0));
+        assert_eq!(parse_dollar_expression("90 / 4 - 2.5"), Ok(20.0));
+        assert_eq!(parse_dollar_expression("-12.5"), Ok(-12.5));
+        assert_eq!(parse_dollar_expression("20 * -1"), Ok(-20.0));
+        assert_eq!(parse_dollar_expression("1,234.56"), Ok(1234.56));
+    }
+
+    #[test]
+    fn test_parse_dollar_expression_rejects_anything_else() {
+        for bad in ["drop table transactions", "1; DROP TABLE transactions", "12 +", "* 3", "(1 + 2)", "2 ^ 3", "1e5", "", "5 / 0", "45 50 + 1"] {
+            assert!(parse_dollar_expression(bad).is_err(), "{:?} should be rejected", bad);
+        }
+    }
+}
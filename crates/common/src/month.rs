@@ -0,0 +1,274 @@
+use axum::{
+    extract::{FromRef, FromRequestParts, Path, Query},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Datelike, Months, NaiveDate};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A calendar month, always rendered as `YYYY-MM`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Month {
+    year: i32,
+    month: u32,
+}
+
+impl Month {
+    pub fn new(year: i32, month: u32) -> Result<Self, String> {
+        if !(1..=12).contains(&month) {
+            return Err(format!("Invalid month {:02}, expected 01-12", month));
+        }
+        if !(1..=9999).contains(&year) {
+            return Err(format!("Invalid year {}", year));
+        }
+        Ok(Self { year, month })
+    }
+
+    pub fn from_date(date: NaiveDate) -> Self {
+        Self { year: date.year(), month: date.month() }
+    }
+
+    /// The month containing today's local date.
+    pub fn current() -> Self {
+        Self::from_date(chrono::Local::now().date_naive())
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    pub fn first_day(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year, self.month, 1).expect("validated year and month")
+    }
+
+    /// The given day of this month, clamped to the month's last day (e.g. day 31 of February is the 28th or 29th).
+    pub fn day_clamped(&self, day: u32) -> NaiveDate {
+        let last_day = (self.next().first_day() - chrono::Days::new(1)).day();
+        NaiveDate::from_ymd_opt(self.year, self.month, day.clamp(1, last_day)).expect("day within month")
+    }
+
+    pub fn previous(&self) -> Self {
+        Self::from_date(self.first_day() - Months::new(1))
+    }
+
+    pub fn next(&self) -> Self {
+        Self::from_date(self.first_day() + Months::new(1))
+    }
+
+    /// Months since year 0, so months can be compared by subtraction.
+    fn index(&self) -> i32 {
+        self.year * 12 + self.month as i32 - 1
+    }
+
+    /// Rejects months before `earliest` or more than `max_future` months after `current`.
+    pub fn check_bounds(&self, earliest: Month, max_future: u32, current: Month) -> Result<(), String> {
+        if *self < earliest {
+            return Err(format!("Month {} is before the earliest month, {}", self, earliest));
+        }
+        if i64::from(self.index()) - i64::from(current.index()) > i64::from(max_future) {
+            return Err(format!("Month {} is more than {} months in the future", self, max_future));
+        }
+        Ok(())
+    }
+}
+
+/// The earliest month and the most months ahead of the current one that `ValidMonth` accepts,
+/// from `Config::month_bounds`. `ValidMonth` takes them from the router state via `FromRef`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonthBounds {
+    pub earliest: Month,
+    pub max_future: u32,
+}
+
+impl FromStr for Month {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid month '{}', expected YYYY-MM", s);
+
+        let (year, month) = s.split_once('-').ok_or_else(invalid)?;
+        if year.len() != 4 || month.len() != 2 || !year.bytes().chain(month.bytes()).all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let year: i32 = year.parse().map_err(|_| invalid())?;
+        let month: u32 = month.parse().map_err(|_| invalid())?;
+        Month::new(year, month).map_err(|e| format!("Invalid month '{}': {}", s, e))
+    }
+}
+
+impl fmt::Display for Month {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+/// Extracts and validates the `month` path parameter (or `?month=` query parameter when the
+/// route has none), rejecting anything that is not a real `YYYY-MM` month with `400`. Months
+/// outside the state's `MonthBounds` are rejected too, so nobody can send the database off scanning 1900.
+#[derive(Debug, Clone, Copy)]
+pub struct ValidMonth(pub Month);
+
+#[derive(Debug)]
+pub struct InvalidMonth(pub String);
+
+impl IntoResponse for InvalidMonth {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, Json(json!({ "error": self.0 }))).into_response()
+    }
+}
+
+#[derive(Deserialize)]
+struct MonthQuery {
+    month: Option<String>,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ValidMonth
+where
+    MonthBounds: FromRef<S>,
+{
+    type Rejection = InvalidMonth;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let from_path = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+            .await
+            .ok()
+            .and_then(|Path(params)| params.get("month").cloned());
+
+        let raw = match from_path {
+            Some(month) => month,
+            None => Query::<MonthQuery>::from_request_parts(parts, state)
+                .await
+                .ok()
+                .and_then(|Query(q)| q.month)
+                .ok_or_else(|| InvalidMonth("Missing month parameter, expected YYYY-MM".to_string()))?,
+        };
+
+        let month: Month = raw.parse().map_err(InvalidMonth)?;
+        let bounds = MonthBounds::from_ref(state);
+        month.check_bounds(bounds.earliest, bounds.max_future, Month::current()).map_err(InvalidMonth)?;
+        Ok(ValidMonth(month))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_day_clamped() {
+        let feb: Month = "2026-02".parse().unwrap();
+        assert_eq!(feb.day_clamped(31).to_string(), "2026-02-28");
+        assert_eq!(feb.day_clamped(15).to_string(), "2026-02-15");
+        let leap: Month = "2028-02".parse().unwrap();
+        assert_eq!(leap.day_clamped(30).to_string(), "2028-02-29");
+    }
+
+    #[test]
+    fn test_parse_valid_month() {
+        let month: Month = "2026-01".parse().unwrap();
+        assert_eq!(month.year(), 2026);
+        assert_eq!(month.month(), 1);
+        assert_eq!(month.to_string(), "2026-01");
+    }
+
+    #[test]
+    fn test_parse_month_out_of_range() {
+        assert!("2026-13".parse::<Month>().is_err());
+        assert!("2026-00".parse::<Month>().is_err());
+    }
+
+    #[test]
+    fn test_parse_month_missing_leading_zero() {
+        assert!("2026-1".parse::<Month>().is_err());
+    }
+
+    #[test]
+    fn test_parse_month_garbage() {
+        assert!("garbage".parse::<Month>().is_err());
+        assert!("2026-01-01".parse::<Month>().is_err());
+        assert!("+026-01".parse::<Month>().is_err());
+    }
+
+    #[test]
+    fn test_check_bounds() {
+        let month = |s: &str| s.parse::<Month>().unwrap();
+        let current = month("2026-10");
+        let earliest = month("2000-01");
+
+        assert!(month("2000-01").check_bounds(earliest, 24, current).is_ok());
+        assert!(month("2028-10").check_bounds(earliest, 24, current).is_ok());
+        assert!(month("1999-12").check_bounds(earliest, 24, current).unwrap_err().contains("earliest"));
+        assert!(month("2028-11").check_bounds(earliest, 24, current).unwrap_err().contains("future"));
+    }
+
+    #[test]
+    fn test_previous_and_next_cross_year() {
+        let january: Month = "2026-01".parse().unwrap();
+        assert_eq!(january.previous().to_string(), "2025-12");
+        assert_eq!(january.previous().next(), january);
+    }
+
+    fn month_app(bounds: MonthBounds) -> Router {
+        async fn show(ValidMonth(month): ValidMonth) -> String {
+            month.to_string()
+        }
+
+        Router::new()
+            .route("/budget/{month}", get(show))
+            .route("/budget", get(show))
+            .with_state(bounds)
+    }
+
+    async fn status_with(bounds: MonthBounds, uri: &str) -> StatusCode {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        month_app(bounds).oneshot(request).await.unwrap().status()
+    }
+
+    /// Against the config defaults.
+    async fn status_for(uri: &str) -> StatusCode {
+        status_with(crate::Config::default().month_bounds(), uri).await
+    }
+
+    #[tokio::test]
+    async fn test_valid_month_extractor() {
+        assert_eq!(status_for("/budget/2026-01").await, StatusCode::OK);
+        assert_eq!(status_for("/budget?month=2026-01").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_valid_month_extractor_rejects_invalid() {
+        assert_eq!(status_for("/budget/2026-13").await, StatusCode::BAD_REQUEST);
+        assert_eq!(status_for("/budget/2026-1").await, StatusCode::BAD_REQUEST);
+        assert_eq!(status_for("/budget?month=garbage").await, StatusCode::BAD_REQUEST);
+        assert_eq!(status_for("/budget").await, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_valid_month_extractor_rejects_out_of_bounds() {
+        // Too old for the default earliest month, and too far beyond the default 24 months ahead
+        assert_eq!(status_for("/budget/1900-01").await, StatusCode::BAD_REQUEST);
+        assert_eq!(status_for("/budget?month=9999-12").await, StatusCode::BAD_REQUEST);
+        assert_eq!(status_for(&format!("/budget/{}", Month::current().next())).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_valid_month_extractor_uses_state_bounds() {
+        let bounds = MonthBounds { earliest: "2020-01".parse().unwrap(), max_future: 0 };
+        assert_eq!(status_with(bounds, "/budget/2019-12").await, StatusCode::BAD_REQUEST);
+        assert_eq!(status_with(bounds, "/budget/2020-01").await, StatusCode::OK);
+        assert_eq!(status_with(bounds, &format!("/budget/{}", Month::current())).await, StatusCode::OK);
+        assert_eq!(status_with(bounds, &format!("/budget/{}", Month::current().next())).await, StatusCode::BAD_REQUEST);
+    }
+}
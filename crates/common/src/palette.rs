@@ -0,0 +1,72 @@
+use std::str::FromStr;
+
+/// Colors new categories are picked from when none is given, and that the management page offers.
+pub const DEFAULT_PALETTE: [&str; 20] = [
+    "#FFB3BA", "#FFDFBA", "#FFFFBA", "#BAFFC9", "#BAE1FF",
+    "#E2F0CB", "#FDFD96", "#FFC3A0", "#FFD1DC", "#D4F0F0",
+    "#CCE2CB", "#B6CFB6", "#97C1A9", "#FCB7AF", "#FFDAC1",
+    "#E7FFAC", "#FFABAB", "#D5AAFF", "#85E3FF", "#B9F6CA",
+];
+
+/// True for `#RRGGBB`.
+pub fn is_hex_color(value: &str) -> bool {
+    value.len() == 7
+        && value.starts_with('#')
+        && value[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// A non-empty list of `#RRGGBB` colors, parsed from a comma-separated list like
+/// `#112233, #445566`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette(Vec<String>);
+
+impl Palette {
+    pub fn colors(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self(DEFAULT_PALETTE.iter().map(|c| c.to_string()).collect())
+    }
+}
+
+impl FromStr for Palette {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let colors: Vec<String> = s.split(',').map(str::trim).filter(|c| !c.is_empty()).map(str::to_string).collect();
+        if colors.is_empty() {
+            return Err("palette needs at least one color".to_string());
+        }
+        if let Some(bad) = colors.iter().find(|c| !is_hex_color(c)) {
+            return Err(format!("{:?} is not a #RRGGBB color", bad));
+        }
+        Ok(Self(colors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_parses_and_rejects_bad_entries() {
+        let palette: Palette = " #112233, #aabbcc ,".parse().unwrap();
+        assert_eq!(palette.colors(), ["#112233", "#aabbcc"]);
+
+        for bad in ["#112233,teal", "#12345", "112233", "#GGHHII", " , "] {
+            assert!(bad.parse::<Palette>().is_err(), "{:?} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn test_configured_palette_replaces_default() {
+        assert_eq!(crate::Config::default().palette(), Palette::default());
+        assert_eq!(Palette::default().colors().len(), DEFAULT_PALETTE.len());
+
+        let config = <crate::Config as clap::Parser>::parse_from(["budget", "--color-palette", "#010203"]);
+        assert_eq!(config.palette().colors(), ["#010203"]);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::month::Month;
+use chrono::{Datelike, Duration, Months, NaiveDate};
+
+/// How reports group dates into periods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// Calendar months, keyed `YYYY-MM`.
+    Month,
+    /// ISO 8601 weeks (Monday to Sunday), keyed `YYYY-Www` by ISO week-numbering year.
+    IsoWeek,
+    /// Twelve months beginning on the first of `start_month`, keyed `FYyyyy` by the calendar year
+    /// the fiscal year starts in. A `start_month` of 1 matches calendar years.
+    FiscalYear { start_month: u32 },
+    /// Runs of `days` days from one payday to the day before the next, keyed by the cycle's first
+    /// day (`YYYY-MM-DD`).
+    PayCycle(PayCycle),
+}
+
+/// Paydays every `days` days, one of them on `anchor`; cycles extend both ways from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PayCycle {
+    pub anchor: NaiveDate,
+    pub days: u32,
+}
+
+impl PayCycle {
+    /// The payday starting the cycle that contains `date`.
+    fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        let offset = (date - self.anchor).num_days().rem_euclid(i64::from(self.days));
+        date - Duration::days(offset)
+    }
+}
+
+impl Period {
+    /// Parses a `granularity` query value: `month`, `week`, `fiscal-year` or `pay-cycle`. Fiscal
+    /// years start on `fiscal_year_start` (1-12) and pay cycles follow `pay_cycle`, normally from
+    /// `Config::period`; `pay-cycle` is refused when no pay cycle is configured.
+    pub fn from_param(value: &str, fiscal_year_start: u32, pay_cycle: Option<PayCycle>) -> Result<Self, String> {
+        match value {
+            "month" => Ok(Period::Month),
+            "week" => Ok(Period::IsoWeek),
+            "fiscal-year" => Period::fiscal_year(fiscal_year_start),
+            "pay-cycle" => pay_cycle
+                .map(Period::PayCycle)
+                .ok_or_else(|| "No pay cycle is configured; set PAY_CYCLE_ANCHOR".to_string()),
+            other => Err(format!("Unknown granularity {:?}, expected month, week, fiscal-year or pay-cycle", other)),
+        }
+    }
+
+    pub fn fiscal_year(start_month: u32) -> Result<Self, String> {
+        if !(1..=12).contains(&start_month) {
+            return Err(format!("Invalid fiscal year start month {}, expected 1-12", start_month));
+        }
+        Ok(Period::FiscalYear { start_month })
+    }
+
+    /// The key of the period containing `date`. Keys of one granularity sort chronologically.
+    pub fn key(&self, date: NaiveDate) -> String {
+        match *self {
+            Period::Month => Month::from_date(date).to_string(),
+            Period::IsoWeek => {
+                let week = date.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Period::FiscalYear { start_month } => {
+                let year = if date.month() >= start_month { date.year() } else { date.year() - 1 };
+                format!("FY{}", year)
+            }
+            Period::PayCycle(cycle) => cycle.start_of(date).format("%Y-%m-%d").to_string(),
+        }
+    }
+
+    /// The first and last day (inclusive) of the period containing `date`.
+    pub fn bounds(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let (first, next) = match *self {
+            Period::Month => {
+                let month = Month::from_date(date);
+                (month.first_day(), month.next().first_day())
+            }
+            Period::IsoWeek => {
+                let first = date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+                (first, first + Duration::days(7))
+            }
+            Period::FiscalYear { start_month } => {
+                let year = if date.month() >= start_month { date.year() } else { date.year() - 1 };
+                let first = NaiveDate::from_ymd_opt(year, start_month, 1).unwrap_or(date);
+                (first, first.checked_add_months(Months::new(12)).unwrap_or(first))
+            }
+            Period::PayCycle(cycle) => {
+                let first = cycle.start_of(date);
+                (first, first + Duration::days(i64::from(cycle.days)))
+            }
+        };
+        (first, next.pred_opt().unwrap_or(first))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_fiscal_year_starting_in_april() {
+        let period = Period::fiscal_year(4).unwrap();
+
+        assert_eq!(period.key(date("2026-03-31")), "FY2025");
+        assert_eq!(period.key(date("2026-04-01")), "FY2026");
+        assert_eq!(period.key(date("2027-03-15")), "FY2026");
+    }
+
+    #[test]
+    fn test_month_and_iso_week_keys() {
+        assert_eq!(Period::Month.key(date("2026-03-09")), "2026-03");
+        // 2027-01-01 is a Friday, so it belongs to the last ISO week of 2026
+        assert_eq!(Period::IsoWeek.key(date("2027-01-01")), "2026-W53");
+        assert_eq!(Period::IsoWeek.key(date("2026-03-09")), "2026-W11");
+    }
+
+    #[test]
+    fn test_from_param() {
+        assert_eq!(Period::from_param("fiscal-year", 4, None).unwrap(), Period::FiscalYear { start_month: 4 });
+        assert_eq!(Period::from_param("week", 4, None).unwrap(), Period::IsoWeek);
+        assert!(Period::from_param("fiscal-year", 13, None).is_err());
+        assert!(Period::from_param("decade", 1, None).is_err());
+        assert!(Period::from_param("pay-cycle", 1, None).is_err());
+
+        let cycle = PayCycle { anchor: date("2026-01-09"), days: 14 };
+        assert_eq!(Period::from_param("pay-cycle", 1, Some(cycle)).unwrap(), Period::PayCycle(cycle));
+    }
+
+    #[test]
+    fn test_biweekly_pay_cycle_keys_and_bounds() {
+        let period = Period::PayCycle(PayCycle { anchor: date("2026-01-09"), days: 14 });
+
+        assert_eq!(period.key(date("2026-01-09")), "2026-01-09");
+        assert_eq!(period.key(date("2026-01-22")), "2026-01-09");
+        assert_eq!(period.key(date("2026-01-23")), "2026-01-23");
+        // Cycles before the anchor line up with it too
+        assert_eq!(period.key(date("2026-01-08")), "2025-12-26");
+        assert_eq!(period.bounds(date("2026-03-09")), (date("2026-03-06"), date("2026-03-19")));
+    }
+
+    #[test]
+    fn test_bounds_of_calendar_periods() {
+        assert_eq!(Period::Month.bounds(date("2024-02-10")), (date("2024-02-01"), date("2024-02-29")));
+        assert_eq!(Period::IsoWeek.bounds(date("2026-03-11")), (date("2026-03-09"), date("2026-03-15")));
+        let fiscal = Period::fiscal_year(4).unwrap();
+        assert_eq!(fiscal.bounds(date("2026-03-31")), (date("2025-04-01"), date("2026-03-31")));
+        assert_eq!(fiscal.bounds(date("2026-04-01")), (date("2026-04-01"), date("2027-03-31")));
+    }
+}
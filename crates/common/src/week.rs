@@ -0,0 +1,47 @@
+use chrono::{Datelike, Duration, NaiveDate};
+
+/// The day weekly breakdowns treat as the first of the week.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum WeekStart {
+    /// ISO weeks, Monday to Sunday. The default.
+    #[default]
+    Monday,
+    /// Sunday to Saturday.
+    Sunday,
+}
+
+impl WeekStart {
+    /// First day of the week containing `date`.
+    pub fn week_of(self, date: NaiveDate) -> NaiveDate {
+        let offset = match self {
+            WeekStart::Monday => date.weekday().num_days_from_monday(),
+            WeekStart::Sunday => date.weekday().num_days_from_sunday(),
+        };
+        date - Duration::days(offset as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_week_of_depends_on_week_start() {
+        // 2026-03-01 is a Sunday
+        let sunday = date("2026-03-01");
+        let monday = date("2026-03-02");
+
+        assert_eq!(WeekStart::Monday.week_of(sunday), date("2026-02-23"));
+        assert_eq!(WeekStart::Monday.week_of(monday), date("2026-03-02"));
+        assert_ne!(WeekStart::Monday.week_of(sunday), WeekStart::Monday.week_of(monday));
+
+        assert_eq!(WeekStart::Sunday.week_of(sunday), date("2026-03-01"));
+        assert_eq!(WeekStart::Sunday.week_of(monday), date("2026-03-01"));
+        assert_eq!(WeekStart::Sunday.week_of(date("2026-03-07")), date("2026-03-01"));
+        assert_eq!(WeekStart::Sunday.week_of(date("2026-03-08")), date("2026-03-08"));
+    }
+}
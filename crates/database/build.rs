@@ -0,0 +1,4 @@
+// Recompile `sqlx::migrate!` when a migration is added or changed.
+fn main() {
+    println!("cargo:rerun-if-changed=../../migrations");
+}
@@ -0,0 +1,57 @@
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// Buffered events per subscriber before it starts lagging.
+const CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Created => "created",
+            ChangeKind::Updated => "updated",
+            ChangeKind::Deleted => "deleted",
+        }
+    }
+}
+
+/// A committed change to a transaction dated in `month` (YYYY-MM), or to the month's note or budgets.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonthEvent {
+    pub month: String,
+    pub kind: ChangeKind,
+    pub transaction_id: Option<i64>, // None for bulk changes
+}
+
+/// Fan-out of committed changes to everyone watching a month. Every clone of a `Database` shares
+/// one, and the services publish to it after each commit, so no writer can skip it.
+#[derive(Clone)]
+pub struct MonthEvents {
+    sender: broadcast::Sender<MonthEvent>,
+}
+
+impl Default for MonthEvents {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl MonthEvents {
+    /// Publish only after the change has been committed.
+    pub fn publish(&self, month: impl Into<String>, kind: ChangeKind, transaction_id: Option<i64>) {
+        // Sending fails only when nobody is subscribed, which is fine
+        let _ = self.sender.send(MonthEvent { month: month.into(), kind, transaction_id });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<MonthEvent> {
+        self.sender.subscribe()
+    }
+}
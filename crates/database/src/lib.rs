@@ -1,17 +1,25 @@
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions, SqliteConnectOptions};
-use sqlx::{Transaction, Sqlite};
+use sqlx::migrate::Migrator;
+use sqlx::{ConnectOptions, Transaction, Sqlite};
+use std::fmt;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+pub mod events;
 
 pub use sqlx::Error;
 pub use sqlx::Result;
 
+static MIGRATOR: Migrator = sqlx::migrate!("../../migrations");
+
 static TEST_DB_COUNTER: AtomicU64 = AtomicU64::new(0);
 
 // --- Driver Adapter Pattern ---
 pub type Driver = Sqlite;
 pub type Connection = sqlx::SqliteConnection;
 pub type Pool = SqlitePool;
+pub type PooledConnection = sqlx::pool::PoolConnection<Driver>;
 
 #[derive(Debug, thiserror::Error)]
 pub enum RepositoryError {
@@ -23,28 +31,32 @@ pub enum RepositoryError {
     UniqueViolation(String),
     #[error("Check constraint violation: {0}")]
     CheckViolation(String),
+    /// Every pooled connection stayed checked out past the acquire timeout.
+    #[error("Database busy: no connection available")]
+    Busy,
 }
 
 impl From<sqlx::Error> for RepositoryError {
     fn from(err: sqlx::Error) -> Self {
         match err {
             sqlx::Error::RowNotFound => RepositoryError::NotFound,
+            sqlx::Error::PoolTimedOut => RepositoryError::Busy,
             _ => {
-                if let Some(db_err) = err.as_database_error() {
-                    if let Some(code) = db_err.code() {
-                        match code.as_ref() {
-                            "2067" | "1555" => {
-                                return RepositoryError::UniqueViolation(
-                                    db_err.message().to_string(),
-                                );
-                            }
-                            "275" => {
-                                return RepositoryError::CheckViolation(
-                                    db_err.message().to_string(),
-                                );
-                            }
-                            _ => {}
+                if let Some(db_err) = err.as_database_error()
+                    && let Some(code) = db_err.code()
+                {
+                    match code.as_ref() {
+                        "2067" | "1555" => {
+                            return RepositoryError::UniqueViolation(
+                                db_err.message().to_string(),
+                            );
+                        }
+                        "275" => {
+                            return RepositoryError::CheckViolation(
+                                db_err.message().to_string(),
+                            );
                         }
+                        _ => {}
                     }
                 }
                 RepositoryError::Infrastructure(err)
@@ -53,36 +65,213 @@ impl From<sqlx::Error> for RepositoryError {
     }
 }
 
+/// How the database's applied migrations compare to the ones embedded in this binary.
+#[derive(Debug, Default, PartialEq)]
+pub struct MigrationStatus {
+    /// Embedded migrations already applied, by version.
+    pub applied: Vec<i64>,
+    /// Embedded migrations not yet applied; `run_migrations` will apply these.
+    pub pending: Vec<(i64, String)>,
+    /// Applied migrations this binary does not know about (the database is ahead, e.g. after a downgrade).
+    pub unknown: Vec<i64>,
+    /// Applied migrations whose file has been edited since they ran.
+    pub modified: Vec<i64>,
+}
+
+impl MigrationStatus {
+    /// True when migrating cannot safely proceed.
+    pub fn has_drift(&self) -> bool {
+        !self.unknown.is_empty() || !self.modified.is_empty()
+    }
+}
+
+impl fmt::Display for MigrationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} applied, {} pending", self.applied.len(), self.pending.len())?;
+        for (version, description) in &self.pending {
+            write!(f, "\n  pending: {} {}", version, description)?;
+        }
+        if !self.unknown.is_empty() {
+            write!(
+                f,
+                "\n  database has migrations {:?} that this binary does not include; it was probably \
+                 created by a newer version. Upgrade the binary or restore a matching backup.",
+                self.unknown
+            )?;
+        }
+        if !self.modified.is_empty() {
+            write!(
+                f,
+                "\n  migrations {:?} were changed after being applied; restore the original files \
+                 instead of editing applied migrations.",
+                self.modified
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Snapshot of connection pool usage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: usize,
+    pub in_use: usize,
+}
+
 #[derive(Clone)]
 pub struct Database {
     pub pool: Pool,
+    events: events::MonthEvents,
 }
 
 impl Database {
-    pub async fn new(connection_string: &str) -> sqlx::Result<Self> {
-        let options = SqliteConnectOptions::from_str(connection_string)?
+    /// Wraps an already configured pool; clones share the pool and the event channel.
+    pub fn from_pool(pool: Pool) -> Self {
+        Self { pool, events: events::MonthEvents::default() }
+    }
+
+    /// Committed transaction changes, published by the services that made them.
+    pub fn events(&self) -> &events::MonthEvents {
+        &self.events
+    }
+
+    /// Statements slower than `slow_query_threshold` are logged at warn level with their elapsed
+    /// time; `None` keeps sqlx's default threshold.
+    pub async fn new(connection_string: &str, slow_query_threshold: Option<Duration>) -> sqlx::Result<Self> {
+        let mut options = SqliteConnectOptions::from_str(connection_string)?
             .create_if_missing(true);
+        if let Some(threshold) = slow_query_threshold {
+            options = options.log_slow_statements(log::LevelFilter::Warn, threshold);
+        }
 
         let pool = SqlitePoolOptions::new()
             .connect_with(options)
             .await?;
         
-        Ok(Self { pool })
+        Ok(Self::from_pool(pool))
     }
 
     pub async fn run_migrations(&self) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Running migrations...");
-        sqlx::migrate!("../../migrations")
-            .run(&self.pool)
+        MIGRATOR.run(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Compares the `_sqlx_migrations` table with the embedded migrations without changing anything.
+    pub async fn migration_status(&self) -> Result<MigrationStatus, sqlx::Error> {
+        let table_exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let applied_rows: Vec<(i64, Vec<u8>)> = if table_exists {
+            sqlx::query_as("SELECT version, checksum FROM _sqlx_migrations WHERE success = 1 ORDER BY version")
+                .fetch_all(&self.pool)
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        let mut status = MigrationStatus::default();
+        for migration in MIGRATOR.iter() {
+            match applied_rows.iter().find(|(version, _)| *version == migration.version) {
+                Some((version, checksum)) => {
+                    if checksum.as_slice() != &*migration.checksum {
+                        status.modified.push(*version);
+                    }
+                    status.applied.push(*version);
+                }
+                None => status.pending.push((migration.version, migration.description.to_string())),
+            }
+        }
+        status.unknown = applied_rows
+            .iter()
+            .map(|(version, _)| *version)
+            .filter(|version| !MIGRATOR.iter().any(|m| m.version == *version))
+            .collect();
+
+        Ok(status)
+    }
+
+    /// Closes the pool, waiting for checked-out connections to come back first. Afterwards this and
+    /// every clone fail fast with a pool-closed error instead of waiting for a connection.
+    pub async fn close(self) {
+        self.pool.close().await;
+    }
+
+    /// Deletes every transaction, budget, rule, month note, category and card in one transaction,
+    /// for starting over. The "Uncategorized" category is recreated since imports and rules rely on it.
+    /// Every month that had data gets a `Deleted` event.
+    pub async fn truncate_all(&self) -> Result<(), RepositoryError> {
+        let mut tx = self.pool.begin().await?;
+        let months: Vec<String> = sqlx::query_scalar(
+            "SELECT substr(transaction_date, 1, 7) FROM transactions \
+             UNION SELECT month FROM monthly_budgets \
+             UNION SELECT month FROM month_notes",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        // Children before parents, so foreign keys never point at a deleted row
+        for table in ["transactions", "monthly_budgets", "categorization_rules", "month_notes", "categories", "cards"] {
+            sqlx::query(&format!("DELETE FROM {}", table)).execute(&mut *tx).await?;
+        }
+        sqlx::query("INSERT INTO categories (name, color, is_income) VALUES ('Uncategorized', '#f8f9fa', 0)")
+            .execute(&mut *tx)
             .await?;
-        println!("Migrations complete.");
+        tx.commit().await?;
+
+        for month in months {
+            self.events.publish(month, events::ChangeKind::Deleted, None);
+        }
         Ok(())
     }
 
+    pub fn pool_stats(&self) -> PoolStats {
+        let size = self.pool.size();
+        let idle = self.pool.num_idle();
+        PoolStats {
+            size,
+            idle,
+            in_use: (size as usize).saturating_sub(idle),
+        }
+    }
+
+    /// A pooled connection with no transaction open, for read-only paths. Writes go through
+    /// `begin` or `transaction`.
+    pub async fn connection(&self) -> Result<PooledConnection, RepositoryError> {
+        Ok(self.pool.acquire().await?)
+    }
+
+    /// Opens a unit of work. Write paths end it with `commit`; read paths that only want a
+    /// consistent snapshot end it with `rollback`. Dropping it also rolls back, but says nothing
+    /// about whether skipping the commit was intended.
     pub async fn begin(&self) -> Result<UnitOfWork<'_>, RepositoryError> {
         let tx = self.pool.begin().await?;
         Ok(UnitOfWork { tx })
     }
+
+    /// Runs `f` inside a transaction, committing when it returns `Ok` and rolling back on `Err`,
+    /// so a write path cannot forget its commit. `E` is usually a service error type.
+    pub async fn transaction<F, T, E>(&self, f: F) -> Result<T, E>
+    where
+        F: AsyncFnOnce(&mut Connection) -> Result<T, E>,
+        E: From<RepositoryError>,
+    {
+        let mut tx = self.pool.begin().await.map_err(RepositoryError::from)?;
+
+        match f(&mut tx).await {
+            Ok(value) => {
+                tx.commit().await.map_err(RepositoryError::from)?;
+                Ok(value)
+            }
+            Err(e) => {
+                // The closure's error is the one worth reporting; a failed rollback still rolls back on drop
+                let _ = tx.rollback().await;
+                Err(e)
+            }
+        }
+    }
 } 
 
 pub struct UnitOfWork<'a> {
@@ -95,18 +284,89 @@ impl<'a> UnitOfWork<'a> {
         Ok(())
     }
 
+    /// Ends the unit of work without saving anything, returning the connection to the pool.
+    pub async fn rollback(self) -> Result<(), RepositoryError> {
+        self.tx.rollback().await?;
+        Ok(())
+    }
+
     pub fn connection(&mut self) -> &mut Connection {
-        &mut *self.tx
+        &mut self.tx
+    }
+}
+
+/// A temp-dir database path unique within this process (counter) and across processes (pid).
+fn test_db_path() -> std::path::PathBuf {
+    let n = TEST_DB_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!("test_budget_{}_{}.db", std::process::id(), n))
+}
+
+/// A test database that, when dropped, closes its pool and deletes its file (with the `-wal`,
+/// `-shm` and `-journal` files beside it), so test runs don't pile up files in the temp dir.
+/// Derefs to the `Database`; clones taken from it stop working once the guard is dropped, so keep
+/// it alive for as long as they are used.
+pub struct TestDb {
+    db: Database,
+    path: std::path::PathBuf,
+}
+
+impl std::ops::Deref for TestDb {
+    type Target = Database;
+
+    fn deref(&self) -> &Database {
+        &self.db
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        let pool = self.db.pool.clone();
+        let path = self.path.clone();
+        let all_idle = pool.size() as usize == pool.num_idle();
+        let remove_files = move || {
+            for suffix in ["", "-wal", "-shm", "-journal"] {
+                let mut file = path.clone().into_os_string();
+                file.push(suffix);
+                let _ = std::fs::remove_file(file);
+            }
+        };
+
+        // Drop can't await, so close on a runtime of our own, then delete the files
+        let cleanup = std::thread::spawn({
+            let remove_files = remove_files.clone();
+            move || {
+                if let Ok(runtime) = tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                    let _ = runtime.block_on(async { tokio::time::timeout(Duration::from_secs(10), pool.close()).await });
+                }
+                remove_files();
+            }
+        });
+
+        // A connection just released is handed back to the pool by a task on the test's runtime,
+        // which can't run while this blocks; closing then finishes once that runtime drops the task.
+        // The process may exit before that, so unlink the files now rather than leave them behind.
+        if all_idle {
+            let _ = cleanup.join();
+        } else {
+            remove_files();
+        }
     }
 }
 
 // do not add #[cfg(test)] here because it hides this method from libraries.
-pub async fn get_test_db() -> Database {
-    use std::time::{SystemTime, UNIX_EPOCH};
+pub async fn get_test_db() -> TestDb {
+    let db = unmigrated_test_db().await;
+    db.run_migrations().await.expect("Failed to run migrations");
     
+    db
+}
+
+/// A single-connection pool on a fresh, unmigrated database file.
+async fn unmigrated_test_db() -> TestDb {
     // Create a unique database file in the temp directory for each test
-    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
-    let db_path = std::env::temp_dir().join(format!("test_budget_{}.db", now));
+    let db_path = test_db_path();
+    // A file left by an earlier run that had the same pid would otherwise leak its rows into this test
+    let _ = std::fs::remove_file(&db_path);
     let connection_string = format!("sqlite:{}", db_path.display());
 
     let options = SqliteConnectOptions::from_str(&connection_string).unwrap()
@@ -118,8 +378,314 @@ pub async fn get_test_db() -> Database {
         .await
         .expect("Failed to create test database pool");
 
-    let db = Database { pool };
-    db.run_migrations().await.expect("Failed to run migrations");
-    
-    db
+    TestDb { db: Database::from_pool(pool), path: db_path }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A test database with only the migrations up to and including `version` applied.
+    async fn db_migrated_to(version: i64) -> TestDb {
+        let db = unmigrated_test_db().await;
+        let migrations: Vec<_> = MIGRATOR.iter().filter(|m| m.version <= version).cloned().collect();
+        Migrator { migrations: std::borrow::Cow::Owned(migrations), ..Migrator::DEFAULT }
+            .run(&db.pool)
+            .await
+            .expect("Failed to run migrations");
+        db
+    }
+
+    async fn scalar(db: &Database, sql: &str) -> i64 {
+        sqlx::query_scalar(sql).fetch_one(&db.pool).await.unwrap()
+    }
+
+    #[test]
+    fn test_test_db_paths_are_unique_across_threads() {
+        let handles: Vec<_> = (0..8)
+            .map(|_| std::thread::spawn(|| (0..250).map(|_| test_db_path()).collect::<Vec<_>>()))
+            .collect();
+
+        let mut paths = std::collections::HashSet::new();
+        for handle in handles {
+            for path in handle.join().unwrap() {
+                assert!(paths.insert(path), "duplicate test database path");
+            }
+        }
+        assert_eq!(paths.len(), 2000);
+    }
+
+    #[tokio::test]
+    async fn test_migration_status_up_to_date() {
+        let db = get_test_db().await;
+        let status = db.migration_status().await.unwrap();
+
+        assert!(status.pending.is_empty());
+        assert!(!status.has_drift());
+        assert_eq!(status.applied.len(), MIGRATOR.iter().count());
+    }
+
+    #[tokio::test]
+    async fn test_category_case_duplicates_are_merged_before_unique_index() {
+        let db = db_migrated_to(1).await;
+        for sql in [
+            "DELETE FROM monthly_budgets",
+            "INSERT INTO categories (id, name) VALUES (100, 'Food'), (101, 'food'), (102, 'FOOD')",
+            "INSERT INTO transactions (category_id, transaction_date, amount) VALUES (100, '2026-01-02', -100), (101, '2026-01-03', -200), (102, '2026-01-04', -300)",
+            // 100 and 101 both budget January; 101 and 102 both budget February
+            "INSERT INTO monthly_budgets (category_id, month, limit_amount) VALUES (100, '2026-01', 1000), (101, '2026-01', 2000), (101, '2026-02', 3000), (102, '2026-02', 4000)",
+        ] {
+            sqlx::query(sql).execute(&db.pool).await.unwrap();
+        }
+
+        db.run_migrations().await.unwrap();
+
+        assert_eq!(scalar(&db, "SELECT COUNT(*) FROM categories WHERE lower(name) = 'food'").await, 1);
+        assert_eq!(scalar(&db, "SELECT COUNT(*) FROM transactions WHERE category_id = 100").await, 3);
+        assert_eq!(scalar(&db, "SELECT limit_amount FROM monthly_budgets WHERE category_id = 100 AND month = '2026-01'").await, 1000);
+        assert_eq!(scalar(&db, "SELECT limit_amount FROM monthly_budgets WHERE category_id = 100 AND month = '2026-02'").await, 3000);
+        assert_eq!(scalar(&db, "SELECT COUNT(*) FROM monthly_budgets").await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_card_duplicates_are_merged_before_unique_index() {
+        let db = db_migrated_to(4).await;
+        for sql in [
+            "DELETE FROM cards",
+            "INSERT INTO cards (id, name, is_active) VALUES (1, 'Visa', 0), (2, 'Visa ', 1), (3, ' visa', 1), (4, 'Amex', 1)",
+            "INSERT INTO transactions (category_id, card_id, transaction_date, amount) SELECT id, 2, '2026-01-02', -100 FROM categories LIMIT 1",
+            "INSERT INTO transactions (category_id, card_id, transaction_date, amount) SELECT id, 3, '2026-01-03', -200 FROM categories LIMIT 1",
+        ] {
+            sqlx::query(sql).execute(&db.pool).await.unwrap();
+        }
+
+        db.run_migrations().await.unwrap();
+
+        let cards: Vec<(i64, String, bool)> = sqlx::query_as("SELECT id, name, is_active FROM cards ORDER BY id").fetch_all(&db.pool).await.unwrap();
+        assert_eq!(cards, vec![(1, "Visa".to_string(), true), (4, "Amex".to_string(), true)]);
+        assert_eq!(scalar(&db, "SELECT COUNT(*) FROM transactions WHERE card_id = 1").await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_transaction_dates_are_repaired_before_date_check() {
+        let db = db_migrated_to(9).await;
+        sqlx::query(
+            "INSERT INTO transactions (id, category_id, transaction_date, amount) SELECT 100, id, '2026-1-5', -100 FROM categories LIMIT 1",
+        )
+        .execute(&db.pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO transactions (id, category_id, transaction_date, amount) SELECT 101, id, '2026-01-05 10:30:00', -200 FROM categories LIMIT 1",
+        )
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+        db.run_migrations().await.unwrap();
+
+        let dates: Vec<String> = sqlx::query_scalar("SELECT transaction_date FROM transactions WHERE id IN (100, 101) ORDER BY id")
+            .fetch_all(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(dates, vec!["2026-01-05", "2026-01-05"]);
+    }
+
+    #[tokio::test]
+    async fn test_unrepairable_transaction_date_fails_the_migration_clearly() {
+        let db = db_migrated_to(9).await;
+        sqlx::query("INSERT INTO transactions (category_id, transaction_date, amount) SELECT id, '2026-02-30', -100 FROM categories LIMIT 1")
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let err = db.run_migrations().await.unwrap_err().to_string();
+        assert!(err.contains("not a YYYY-MM-DD date"), "{}", err);
+        // The whole migration rolled back, so the row is still there to fix
+        assert_eq!(scalar(&db, "SELECT COUNT(*) FROM transactions WHERE transaction_date = '2026-02-30'").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_migration_status_reports_pending() {
+        let db = get_test_db().await;
+        let latest = MIGRATOR.iter().last().unwrap().version;
+        sqlx::query("DELETE FROM _sqlx_migrations WHERE version = $1")
+            .bind(latest)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let status = db.migration_status().await.unwrap();
+        assert_eq!(status.pending.len(), 1);
+        assert_eq!(status.pending[0].0, latest);
+        assert!(!status.has_drift());
+    }
+
+    #[tokio::test]
+    async fn test_migration_status_reports_unknown_applied_migration() {
+        let db = get_test_db().await;
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (9999, 'future', 1, x'00', 0)",
+        )
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+        let status = db.migration_status().await.unwrap();
+        assert_eq!(status.unknown, vec![9999]);
+        assert!(status.has_drift());
+        assert!(status.to_string().contains("9999"));
+    }
+
+    #[tokio::test]
+    async fn test_pool_stats_reflect_connections() {
+        let db = get_test_db().await;
+        sqlx::query("SELECT 1").execute(&db.pool).await.unwrap();
+
+        assert!(db.pool_stats().size >= 1);
+
+        let uow = db.begin().await.unwrap();
+        let busy = db.pool_stats();
+        assert!(busy.in_use >= 1);
+        drop(uow);
+    }
+
+    #[tokio::test]
+    async fn test_rollback_ends_the_unit_of_work() {
+        let db = get_test_db().await;
+        let cards = async || -> i64 { sqlx::query_scalar("SELECT COUNT(*) FROM cards").fetch_one(&db.pool).await.unwrap() };
+        let before = cards().await;
+
+        let mut uow = db.begin().await.unwrap();
+        sqlx::query("INSERT INTO cards (name) VALUES ('Visa')").execute(&mut *uow.connection()).await.unwrap();
+        uow.rollback().await.unwrap();
+        assert_eq!(cards().await, before);
+
+        // The test pool has one connection, so this only works if the rollback released it
+        let mut uow = db.begin().await.unwrap();
+        sqlx::query("INSERT INTO cards (name) VALUES ('Visa')").execute(&mut *uow.connection()).await.unwrap();
+        uow.commit().await.unwrap();
+        assert_eq!(cards().await, before + 1);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_commits_on_ok() {
+        let db = get_test_db().await;
+
+        let id = db.transaction(async |conn: &mut Connection| -> Result<i64, RepositoryError> {
+            Ok(sqlx::query_scalar("INSERT INTO cards (name) VALUES ('Visa') RETURNING id").fetch_one(&mut *conn).await?)
+        }).await.unwrap();
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM cards WHERE id = $1").bind(id).fetch_one(&db.pool).await.unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_rolls_back_on_err() {
+        let db = get_test_db().await;
+
+        let result = db.transaction(async |conn: &mut Connection| -> Result<(), RepositoryError> {
+            sqlx::query("INSERT INTO cards (name) VALUES ('Visa')").execute(&mut *conn).await?;
+            Err(RepositoryError::NotFound)
+        }).await;
+        assert!(matches!(result, Err(RepositoryError::NotFound)));
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM cards WHERE name = 'Visa'").fetch_one(&db.pool).await.unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_read_connection_does_not_block_writers() {
+        use sqlx::Connection as _;
+
+        let db = get_test_db().await;
+        let mut reader = db.connection().await.unwrap();
+        let _: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM cards").fetch_one(&mut *reader).await.unwrap();
+
+        // A second connection to the same file writes while the reader is still checked out
+        let options = (*db.pool.connect_options()).clone().busy_timeout(std::time::Duration::ZERO);
+        let mut writer = sqlx::SqliteConnection::connect_with(&options).await.unwrap();
+        let mut tx = writer.begin().await.unwrap();
+        sqlx::query("INSERT INTO cards (name) VALUES ('Visa')").execute(&mut *tx).await.unwrap();
+        tx.commit().await.unwrap();
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM cards WHERE name = 'Visa'").fetch_one(&mut *reader).await.unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_test_db_files_are_removed_on_drop() {
+        let db = get_test_db().await;
+        sqlx::query("INSERT INTO cards (name) VALUES ('Visa')").execute(&db.pool).await.unwrap();
+        let path = db.path.clone();
+        let clone = db.clone();
+        assert!(path.exists());
+        // Let the runtime hand the insert's connection back, so the drop can close and delete at once
+        while db.pool.num_idle() < db.pool.size() as usize {
+            tokio::task::yield_now().await;
+        }
+
+        drop(db);
+
+        assert!(!path.exists());
+        assert!(clone.pool.is_closed());
+    }
+
+    #[tokio::test]
+    async fn test_closed_database_errors_instead_of_hanging() {
+        let db = get_test_db().await;
+        let clone = db.clone();
+        db.clone().close().await;
+
+        let result = tokio::time::timeout(std::time::Duration::from_secs(5), clone.connection())
+            .await
+            .expect("acquire on a closed pool should not hang");
+        assert!(matches!(result, Err(RepositoryError::Infrastructure(sqlx::Error::PoolClosed))));
+    }
+
+    /// Collects formatted log output so a test can assert on it.
+    #[derive(Clone, Default)]
+    struct CapturedLogs(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_queries_are_logged_at_warn() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::WARN)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        // SQLite statements run on sqlx's worker thread, so a thread-local default would miss them.
+        // Nothing else in this test binary installs a subscriber.
+        tracing::subscriber::set_global_default(subscriber).unwrap();
+
+        let path = test_db_path();
+        let db = Database::new(&format!("sqlite:{}", path.display()), Some(Duration::from_millis(1))).await.unwrap();
+        let count: i64 = sqlx::query_scalar(
+            "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 1000000) SELECT COUNT(*) FROM n",
+        )
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+        assert_eq!(count, 1_000_000);
+        db.close().await;
+        let _ = std::fs::remove_file(&path);
+
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("WARN"), "{}", output);
+        assert!(output.contains("slow statement"), "{}", output);
+        assert!(output.contains("elapsed"), "{}", output);
+    }
 }
@@ -1,47 +1,56 @@
-use crate::models::{RawCreateTransactionRequest};
+use crate::models::{AmountHistogram, WeekTotal, CategorizationRule, CreateTransactionCommand, FullMonthView, MonthlySummary, NewCategory, PeriodSummary, RawCreateRuleRequest, RawCreateTransactionRequest, Reconciliation, SetClearedRequest, SyncChanges, Transaction, TransactionSort};
 use crate::service::{TransactionError, TransactionService};
+use crate::import::external::{self, FieldMap, RecordResult};
+use crate::import::paste::{self, PasteResult};
+use crate::reports::{PeriodTotal, ReportService};
+use crate::rules::RuleService;
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::{Html, IntoResponse, Response},
-    routing::{get, post, delete},
-    Form, Json, Router,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{sse::{Event, KeepAlive, Sse}, Html, IntoResponse, Response},
+    routing::{get, post, delete, patch},
+    Extension, Form, Json, Router,
 };
-use common::AppState;
+use common::{AppState, auth::{LAST_MONTH_SESSION_KEY, UserContext}, money::{format_cents_unsigned, MoneyFormat}, month::{Month, ValidMonth}};
+use futures_util::stream::{self, Stream};
+use std::convert::Infallible;
+use tokio::sync::broadcast::error::RecvError;
+use tower_sessions::Session;
 use std::sync::Arc;
 use askama::Template;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use categories::models::{BudgetBalance, BudgetGroupView, CategoryShare, ClassificationReport, ClassificationTargets, MonthNote, SetMonthNoteRequest};
+use categories::service::{CategoryError, CategoryService, MonthNoteService};
 use categories::virtual_budget::VirtualBudgetService;
 
-impl IntoResponse for TransactionError {
-    fn into_response(self) -> Response {
-        let (status, msg) = match self {
-            TransactionError::InvalidInput(msg) => (StatusCode::BAD_REQUEST, msg),
-            TransactionError::NotFound => (StatusCode::NOT_FOUND, "Transaction not found".to_string()),
-            TransactionError::Infrastructure(_) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Internal server error".to_string(),
-            ),
-        };
-        
-        (status, Json(json!({ "error": msg }))).into_response()
-    }
-}
-
 #[derive(Template)]
 #[template(path = "month_view.html")]
 pub struct MonthViewTemplate {
+    pub app_name: String,
+    pub base_path: String,
     pub month: String,
+    pub filter: String,
+    pub sort: String,
     pub month_display: String,
     pub overview: FinancialOverview,
     pub budget_rows: Vec<BudgetRowView>,
+    pub budget_groups: Vec<BudgetGroupRows>,
+    /// Group headers and subtotals only appear once some category has a group.
+    pub show_groups: bool,
     pub virtual_rows: Vec<VirtualCategoryView>,
     pub transactions: Vec<TransactionView>,
     pub categories: Vec<categories::models::Category>,
     pub cards: Vec<cards::models::Card>,
+    pub note: Option<MonthNote>,
+    pub plan_warning: Option<PlanWarningView>,
+    /// The month containing today (local time).
+    pub is_current_month: bool,
+    /// A month after the current one, shown as planning.
+    pub is_future_month: bool,
 }
 
+/// Totals already formatted for display, e.g. `$1,234.50`.
 pub struct FinancialOverview {
     pub total_income: String,
     pub total_expenses: String,
@@ -49,21 +58,156 @@ pub struct FinancialOverview {
     pub net_is_positive: bool,
 }
 
-#[derive(Clone)]
+/// Share of an expense limit spent at which a row is flagged as a warning.
+const WARNING_PERCENT: f64 = 80.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetStatus {
+    Ok,
+    Warning,
+    Over,
+}
+
+/// Which budget rows the month view shows; transactions and the summary always cover the full month.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetFilter {
+    #[default]
+    All,
+    /// Rows at warning or over.
+    Warning,
+    Over,
+}
+
+impl BudgetFilter {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetFilter::All => "all",
+            BudgetFilter::Warning => "warning",
+            BudgetFilter::Over => "over",
+        }
+    }
+
+    pub fn keeps(&self, status: BudgetStatus) -> bool {
+        match self {
+            BudgetFilter::All => true,
+            BudgetFilter::Warning => status != BudgetStatus::Ok,
+            BudgetFilter::Over => status == BudgetStatus::Over,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MonthViewQuery {
+    #[serde(default)]
+    pub filter: BudgetFilter,
+    /// Parsed with `TransactionSort::from_param`, so an unknown value shows the default order.
+    #[serde(default)]
+    pub sort: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
 pub struct BudgetRowView {
     pub category_id: i64,
     pub category_name: String,
     pub category_color: String,
-    pub limit_dollars: String,
+    pub category_icon: Option<String>,
+    pub has_budget: bool, // False shows "No budget set" instead of a $0.00 limit
+    pub limit: Option<i64>,        // Cents, None without a budget
+    pub spent: i64,                // Cents, signed for the category like `actual`
+    pub remaining: i64,            // Cents, negative when over
+    pub limit_dollars: String,     // Plain, for the limit input
     pub spent_dollars: String,
     pub remaining_dollars: String,
-    pub percent_spent: String,
-    pub percent_remaining: String,
+    pub limit_display: String,     // Formatted, e.g. "$1,200.00"
+    pub spent_display: String,
+    pub remaining_display: String,
+    pub percent_spent: String,     // Rounded for display
+    pub percent_remaining: String, // Rounded for display
+    pub percent_spent_exact: f64,
+    pub percent_remaining_exact: f64,
     pub is_over_budget: bool,
+    pub status: BudgetStatus,
     pub is_income: bool,
     pub is_active: bool,
 }
 
+impl BudgetRowView {
+    /// Builds the row for a category given its actual (signed-for-category) total in cents.
+    pub fn new(view: &categories::models::CategoryBudgetView, actual: i64, format: MoneyFormat) -> Self {
+        let limit = view.budget.as_ref().map(|b| b.limit_amount).unwrap_or(0);
+        let remaining = view.remaining_for(actual);
+
+        let (p_spent, p_rem) = if limit == 0 {
+            (0.0, 0.0)
+        } else {
+            // Against the size of the limit, so a paydown target reads like any other
+            let spent = (actual as f64 / limit.unsigned_abs() as f64) * 100.0;
+            let rem = (remaining as f64 / limit.unsigned_abs() as f64) * 100.0;
+            (spent, rem)
+        };
+
+        let is_over_budget = remaining < 0;
+        let status = if is_over_budget {
+            BudgetStatus::Over
+        } else if !view.category.is_income && limit > 0 && p_spent >= WARNING_PERCENT {
+            BudgetStatus::Warning
+        } else {
+            BudgetStatus::Ok
+        };
+
+        Self {
+            category_id: view.category.id,
+            category_name: view.category.name.clone(),
+            category_color: view.category.color.clone(),
+            category_icon: view.category.icon.clone(),
+            has_budget: view.budget.is_some(),
+            limit: view.budget.as_ref().map(|b| b.limit_amount),
+            spent: actual,
+            remaining,
+            limit_dollars: format!("{:.2}", limit as f64 / 100.0),
+            spent_dollars: format!("{:.2}", actual as f64 / 100.0),
+            remaining_dollars: format!("{:.2}", remaining as f64 / 100.0),
+            limit_display: format.cents(limit),
+            spent_display: format.cents(actual),
+            remaining_display: format.cents(remaining),
+            percent_spent: format!("{:.0}", p_spent),
+            percent_remaining: format!("{:.0}", p_rem),
+            percent_spent_exact: p_spent,
+            percent_remaining_exact: p_rem,
+            // Uses the exact cents, not the rounded percent, so a tiny overspend still counts.
+            // For both income and expenses this means we are "behind" target
+            is_over_budget,
+            status,
+            is_income: view.category.is_income,
+            is_active: view.category.is_active,
+        }
+    }
+}
+
+/// One category group's visible rows, with subtotals over the whole group.
+pub struct BudgetGroupRows {
+    pub name: Option<String>,
+    pub rows: Vec<BudgetRowView>,
+    pub limit_display: String,
+    pub spent_display: String,
+    pub remaining_display: String,
+}
+
+impl BudgetGroupRows {
+    pub fn new(group: BudgetGroupView, filter: BudgetFilter, format: MoneyFormat) -> Self {
+        let rows = group.categories.iter().map(|v| BudgetRowView::new(v, v.spent, format)).collect();
+        Self {
+            name: group.name,
+            rows: filter_budget_rows(rows, filter),
+            limit_display: format.cents(group.total_limit),
+            spent_display: format.cents(group.total_spent),
+            remaining_display: format.cents(group.total_remaining),
+        }
+    }
+}
+
 pub struct VirtualCategoryView {
     pub name: String,
     pub amount_dollars: String,
@@ -76,138 +220,227 @@ pub struct TransactionRowTemplate {
     pub t: TransactionView,
 }
 
+/// Edit-form cells for a single transaction, swapped into its row by `editRow`.
+#[derive(Template)]
+#[template(path = "edit_row_snippet.html")]
+pub struct TransactionEditTemplate {
+    pub t: TransactionView,
+}
+
+/// JSON shape of a single transaction, with its category and card resolved to names.
+#[derive(Serialize)]
+pub struct TransactionDetail {
+    #[serde(flatten)]
+    pub transaction: Transaction,
+    pub category_name: String,
+    pub card_name: String,
+}
+
 pub struct TransactionView {
     pub id: i64,
     pub category_id: i64,
     pub card_id: i64, 
     pub category_name: String,
     pub category_color: String,
+    pub category_icon: Option<String>,
     pub card_name: String,
     pub transaction_date: String,
     pub transaction_date_display: String,
-    pub amount_dollars: String,
+    pub amount: i64,                   // Signed cents
+    pub amount_dollars: String,        // Unsigned and plain, for the edit form
+    pub amount_display: String,        // Unsigned and formatted; the row colour shows the sign
+    pub signed_amount_display: String, // With its direction, per the configured expense display
     pub is_income: bool,
     pub notes: String,
+    pub excluded: bool,
+    pub cleared: bool,
 }
 
-#[derive(Deserialize)]
-pub struct MonthParam {
-    pub month: String, // YYYY-MM
+impl TransactionView {
+    /// How a transaction's card is shown, here and in exports: "Cash" without a card, and
+    /// "(deleted card)" for a card that no longer exists.
+    pub fn card_label(card_id: Option<i64>, cards: &[cards::models::Card]) -> String {
+        match card_id {
+            None => "Cash".to_string(),
+            Some(card_id) => cards.iter()
+                .find(|c| c.id == card_id)
+                .map(|c| c.name.clone())
+                .unwrap_or_else(|| "(deleted card)".to_string()),
+        }
+    }
+
+    /// The one place a transaction is formatted for display. The card is labelled by `card_label`,
+    /// and an unknown category is "Unknown".
+    pub fn from_transaction(
+        t: &Transaction,
+        categories: &[categories::models::Category],
+        cards: &[cards::models::Card],
+        format: MoneyFormat,
+    ) -> Self {
+        let cat = categories.iter()
+            .find(|c| c.id == t.category_id);
+        let cat_name = cat.map(|c| c.name.clone()).unwrap_or_else(|| "Unknown".to_string());
+        let cat_color = cat.map(|c| c.color.clone()).unwrap_or_else(|| "#ffffff".to_string());
+        let cat_icon = cat.and_then(|c| c.icon.clone());
+            
+        let card_name = Self::card_label(t.card_id, cards);
+            
+        let date_display = chrono::NaiveDate::parse_from_str(&t.transaction_date, "%Y-%m-%d")
+            .map(|d| d.format("%e %b %Y").to_string())
+            .unwrap_or_else(|_| t.transaction_date.clone());
+            
+        TransactionView {
+            id: t.id,
+            category_id: t.category_id,
+            card_id: t.card_id.unwrap_or(0),
+            category_name: cat_name,
+            category_color: cat_color,
+            category_icon: cat_icon,
+            card_name,
+            transaction_date: t.transaction_date.clone(),
+            transaction_date_display: date_display,
+            amount: t.amount,
+            amount_dollars: format!("{:.2}", t.amount.unsigned_abs() as f64 / 100.0),
+            amount_display: format_cents_unsigned(t.amount),
+            signed_amount_display: format.amount(t.amount),
+            is_income: t.amount > 0,
+            notes: t.notes.clone().unwrap_or_default(),
+            excluded: t.excluded,
+            cleared: t.cleared,
+        }
+    }
 }
 
 #[derive(Deserialize)]
 pub struct UpdateTransactionRequest {
     pub category_id: i64,
+    #[serde(default, deserialize_with = "common::forms::deserialize_optional_id")]
     pub card_id: Option<i64>,
     pub transaction_date: String,
+    #[serde(deserialize_with = "common::money::deserialize_dollars")]
     pub amount_dollars: f64,
     pub notes: Option<String>,
+    #[serde(default)]
+    pub excluded: bool,
+}
+
+#[derive(Deserialize)]
+pub struct CopyFixedRequest {
+    pub category_ids: Vec<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct QuickAddRequest {
+    pub input: String,
 }
 
 pub fn transactions_router(state: Arc<AppState>) -> Router<Arc<AppState>> {
     Router::new()
         // Specific routes first
         .route("/add", post(create_transaction))
+        .route("/quick-add", post(quick_add_transaction))
+        .route("/import/external", post(import_external))
+        .route("/paste", post(import_pasted))
+        .route("/periods", get(period_totals))
+        .route("/periods/summary", get(period_summary))
+        .route("/sync", get(sync_changes))
+        .route("/rules", get(list_rules).post(create_rule))
+        .route("/rules/{id}", delete(delete_rule))
         // Then parameterized routes
         .route("/{month}", get(get_month_view))
-        .route("/transaction/{id}", delete(delete_transaction).put(update_transaction))
+        .route("/{month}/print", get(get_print_view))
+        .route("/{month}/mobile.json", get(get_mobile_month))
+        .route("/{month}/overspend-alerts", get(overspend_alerts))
+        .route("/{month}/summary.txt", get(get_month_summary_text))
+        .route("/{month}/histogram", get(amount_histogram))
+        .route("/{month}/weeks", get(weekly_breakdown))
+        .route("/{month}/shares", get(spend_shares))
+        .route("/{month}/ratios", get(classification_ratios))
+        .route("/{month}/plan-check", get(plan_check))
+        .route("/{month}/uncleared", get(reconciliation))
+        .route("/{month}/note", get(get_month_note).put(set_month_note))
+        .route("/{month}/events", get(month_events))
+        .route("/{month}/apply-rules", post(apply_rules))
+        .route("/{month}/copy-fixed", post(copy_fixed_transactions))
+        .route("/transaction/{id}", get(get_transaction).delete(delete_transaction).put(update_transaction))
+        .route("/transaction/{id}/cleared", patch(set_cleared))
         .with_state(state)
 }
 
-async fn get_month_view(
-    State(state): State<Arc<AppState>>,
-    Path(params): Path<MonthParam>,
-) -> Result<impl IntoResponse, TransactionError> {
-    tracing::info!("Fetching month view for: {}", params.month);
+/// Routes that belong under `/categories` but need transactions; merged into the categories router.
+pub fn category_export_router(state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/{id}/export.csv", get(export_category_csv))
+        .with_state(state)
+}
+
+/// The month page's display data, assembled once for the interactive and the printable views.
+pub struct MonthViewData {
+    pub month: String,
+    pub month_display: String,
+    pub overview: FinancialOverview,
+    pub budget_rows: Vec<BudgetRowView>,
+    pub budget_groups: Vec<BudgetGroupRows>,
+    /// Group headers and subtotals only appear once some category has a group.
+    pub show_groups: bool,
+    pub virtual_rows: Vec<VirtualCategoryView>,
+    pub transactions: Vec<TransactionView>,
+    pub categories: Vec<categories::models::Category>,
+    pub cards: Vec<cards::models::Card>,
+    pub note: Option<MonthNote>,
+    /// Set only when the month's expense budgets exceed its income targets.
+    pub plan_warning: Option<PlanWarningView>,
+}
+
+/// The over-budget banner's amounts, formatted.
+pub struct PlanWarningView {
+    pub planned_income: String,
+    pub planned_expenses: String,
+    pub gap: String,
+}
+
+async fn month_view_data(
+    state: &AppState,
+    valid_month: Month,
+    filter: BudgetFilter,
+    sort: TransactionSort,
+) -> Result<MonthViewData, TransactionError> {
+    let month = valid_month.to_string();
+    let format = state.config.money_format();
 
     // 0. Ensure budgets exist for this month (Auto-Copy logic)
-    if let Ok(date) = chrono::NaiveDate::parse_from_str(&format!("{}-01", params.month), "%Y-%m-%d") {
-        let prev_date = date - chrono::Months::new(1);
-        let previous_month = prev_date.format("%Y-%m").to_string();
-        
-        if let Err(e) = categories::service::CategoryService::ensure_budgets_exist(&state.db, &params.month, &previous_month).await {
-            tracing::warn!("Auto-copy budgets failed: {}. Continuing anyway.", e);
-        }
+    let previous_month = valid_month.previous().to_string();
+    if let Err(e) = categories::service::CategoryService::ensure_budgets_exist(&state.db, &month, &previous_month).await {
+        tracing::warn!("Auto-copy budgets failed: {}. Continuing anyway.", e);
     }
 
-    // 1. Get transactions and basic summary
-    let (transactions, summary) = TransactionService::get_month_view(&state.db, &params.month).await.map_err(|e| {
-        tracing::error!("get_month_view error: {:?}", e);
-        e
-    })?;
-    
-    // 2. Get categories and monthly budgets
-    let budget_views = categories::service::CategoryService::get_budget_view(&state.db, &params.month)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to get budget view: {}", e);
-            TransactionError::Infrastructure(e.to_string())
-        })?;
-        
-    // 3. Get cards
-    let all_cards = cards::service::CardService::list_cards(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to list cards: {}", e);
-            TransactionError::Infrastructure(e.to_string())
+    // 1. Transactions, summary, categories with budgets, and cards in one snapshot
+    let FullMonthView { transactions, summary, mut budget_views, cards: all_cards, note } =
+        TransactionService::get_full_month_view(&state.db, &month, sort).await.map_err(|e| {
+            tracing::error!("get_full_month_view error: {:?}", e);
+            e
         })?;
+    if state.config.income_first {
+        // Stable, so each side keeps its name order
+        budget_views.sort_by_key(|v| !v.category.is_income);
+    }
     
-    // 4. Enrich budget views with actual 'spent' data
-    let mut enriched_budget_rows = Vec::new();
+    // 2. Enrich budget views with actual 'spent' data
     let mut transactions_for_virtual = Vec::new();
 
-    for view_ref in &budget_views {
-        let mut view = view_ref.clone();
-        let actual: i64 = if view.category.is_income {
-            // For income, sum positive amounts
-            transactions.iter()
-                .filter(|t| t.category_id == view.category.id && t.amount > 0)
-                .map(|t| t.amount)
-                .sum()
-        } else {
-            // For expenses, sum absolute negative amounts
-            transactions.iter()
-                .filter(|t| t.category_id == view.category.id && t.amount < 0)
-                .map(|t| t.amount.abs())
-                .sum()
-        };
-        
-        view.spent = actual;
-        let limit = view.budget.as_ref().map(|b| b.limit_amount).unwrap_or(0);
-        
-        if view.category.is_income {
-            // For income: good if actual > budget
-            view.remaining = actual - limit;
-        } else {
-            // For expenses: good if limit > actual
-            view.remaining = limit - actual;
-        }
-
-        let (p_spent, p_rem) = if limit == 0 {
-            (0.0, 0.0)
-        } else {
-            let spent = (actual as f64 / limit as f64) * 100.0;
-            let rem = (view.remaining as f64 / limit as f64) * 100.0;
-            (spent, rem)
-        };
-
-        enriched_budget_rows.push(BudgetRowView {
-            category_id: view.category.id,
-            category_name: view.category.name.clone(),
-            category_color: view.category.color.clone(),
-            limit_dollars: format!("{:.2}", limit as f64 / 100.0),
-            spent_dollars: format!("{:.2}", actual as f64 / 100.0),
-            remaining_dollars: format!("{:.2}", view.remaining as f64 / 100.0),
-            percent_spent: format!("{:.0}", p_spent),
-            percent_remaining: format!("{:.0}", p_rem),
-            is_over_budget: if view.category.is_income { view.remaining < 0 } else { view.remaining < 0 }, // Both mean we are "behind" target
-            is_income: view.category.is_income,
-            is_active: view.category.is_active,
-        });
+    for view in &mut budget_views {
+        let actual = TransactionService::category_actual(&transactions, view.category.id, view.category.is_income);
+        view.set_actual(actual);
     }
+    let enriched_budget_rows: Vec<BudgetRowView> = budget_views.iter().map(|v| BudgetRowView::new(v, v.spent, format)).collect();
+    let budget_groups: Vec<BudgetGroupRows> = BudgetGroupView::group(budget_views.clone())
+        .into_iter()
+        .map(|g| BudgetGroupRows::new(g, filter, format))
+        .filter(|g| !g.rows.is_empty())
+        .collect();
 
-    // 5. Calculate Virtual Rows
-    for t in &transactions {
+    // 3. Calculate Virtual Rows
+    for t in transactions.iter().filter(|t| !t.excluded) {
         transactions_for_virtual.push((t.category_id, t.amount));
     }
     // Re-calculating with the updated 'spent' data if needed for splits
@@ -220,7 +453,12 @@ async fn get_month_view(
                 name: r.category_name.clone(), 
                 color: r.category_color.clone(),
                 is_income: r.is_income,
-                is_active: true // Budget rows in this view are always active or have budget
+                is_active: true, // Budget rows in this view are always active or have budget
+                group: None,
+                allows_negative_budget: false,
+                default_note: None,
+                classification: categories::models::Classification::Ignore,
+                icon: r.category_icon.clone(),
             },
             budget: None,
             spent: (r.spent_dollars.parse::<f64>().unwrap_or(0.0) * 100.0) as i64,
@@ -235,153 +473,1325 @@ async fn get_month_view(
         is_income: v.is_income,
     }).collect();
 
-    // 6. Map transactions for view
-    let transaction_views = transactions.into_iter().map(|t| {
-        let cat = enriched_budget_rows.iter()
-            .find(|r| r.category_id == t.category_id);
-            
-        let cat_name = cat.map(|r| r.category_name.clone()).unwrap_or_else(|| "Unknown".to_string());
-        let cat_color = cat.map(|r| r.category_color.clone()).unwrap_or_else(|| "#ffffff".to_string());
-            
-        let card_name = all_cards.iter()
-            .find(|c| Some(c.id) == t.card_id)
-            .map(|c| c.name.clone())
-            .unwrap_or_else(|| "Cash".to_string());
-            
-        let date_display = chrono::NaiveDate::parse_from_str(&t.transaction_date, "%Y-%m-%d")
-            .map(|d| d.format("%e %b %Y").to_string())
-            .unwrap_or_else(|_| t.transaction_date.clone());
-            
-        TransactionView {
-            id: t.id,
-            category_id: t.category_id,
-            card_id: t.card_id.unwrap_or(0),
-            category_name: cat_name,
-            category_color: cat_color,
-            card_name,
-            transaction_date: t.transaction_date,
-            transaction_date_display: date_display,
-            amount_dollars: format!("{:.2}", t.amount.abs() as f64 / 100.0),
-            is_income: t.amount > 0,
-            notes: t.notes.unwrap_or_default(),
-        }
-    }).collect();
+    let balance = BudgetBalance::from_views(&month, &budget_views);
+    let plan_warning = balance.over_budget.then(|| PlanWarningView {
+        planned_income: format.cents(balance.planned_income),
+        planned_expenses: format.cents(balance.planned_expenses),
+        gap: format.cents(balance.gap),
+    });
+
+    // 4. Map transactions for view
+    let categories_for_template: Vec<categories::models::Category> = budget_views.into_iter().map(|v| v.category).collect();
+    let transaction_views = transactions.iter()
+        .map(|t| TransactionView::from_transaction(t, &categories_for_template, &all_cards, format))
+        .collect();
 
     let overview = FinancialOverview {
-        total_income: format!("{:.2}", summary.total_income as f64 / 100.0),
-        total_expenses: format!("{:.2}", summary.total_expenses as f64 / 100.0),
-        net_balance: format!("{:.2}", summary.net as f64 / 100.0),
+        total_income: format.cents(summary.total_income),
+        total_expenses: format.cents(summary.total_expenses),
+        net_balance: format.amount(summary.net),
         net_is_positive: summary.net >= 0,
     };
 
-    let month_display = chrono::NaiveDate::parse_from_str(&format!("{}-01", params.month), "%Y-%m-%d")
-        .map(|d| d.format("%B %Y").to_string())
-        .unwrap_or_else(|_| params.month.clone());
-
-    let categories_for_template: Vec<categories::models::Category> = budget_views.into_iter().map(|v| v.category).collect();
-
-    let template = MonthViewTemplate {
-        month: params.month,
-        month_display,
+    Ok(MonthViewData {
+        month,
+        month_display: valid_month.first_day().format("%B %Y").to_string(),
         overview,
-        budget_rows: enriched_budget_rows.clone(),
+        show_groups: budget_groups.iter().any(|g| g.name.is_some()),
+        budget_groups,
+        budget_rows: filter_budget_rows(enriched_budget_rows, filter),
         virtual_rows,
         transactions: transaction_views,
         categories: categories_for_template,
         cards: all_cards,
+        note,
+        plan_warning,
+    })
+}
+
+async fn get_month_view(
+    State(state): State<Arc<AppState>>,
+    session: Session,
+    ValidMonth(valid_month): ValidMonth,
+    Query(query): Query<MonthViewQuery>,
+) -> Result<impl IntoResponse, TransactionError> {
+    tracing::info!("Fetching month view for: {}", valid_month);
+
+    // Remembered so the next visit to `/` reopens this month
+    let _ = session.insert(LAST_MONTH_SESSION_KEY, &valid_month.to_string()).await;
+
+    let sort = query.sort.as_deref().map(TransactionSort::from_param).unwrap_or_default();
+    let data = month_view_data(&state, valid_month, query.filter, sort).await?;
+    let (is_current_month, is_future_month) = month_position(valid_month, Month::current());
+
+    let template = MonthViewTemplate {
+        app_name: state.config.app_name.clone(),
+        base_path: state.config.base_path.clone(),
+        month: data.month,
+        filter: query.filter.as_str().to_string(),
+        sort: sort.as_str().to_string(),
+        month_display: data.month_display,
+        overview: data.overview,
+        show_groups: data.show_groups,
+        budget_groups: data.budget_groups,
+        budget_rows: data.budget_rows,
+        virtual_rows: data.virtual_rows,
+        transactions: data.transactions,
+        categories: data.categories,
+        cards: data.cards,
+        note: data.note,
+        plan_warning: data.plan_warning,
+        is_current_month,
+        is_future_month,
     };
 
     Ok(Html(template.render().map_err(|e| TransactionError::Infrastructure(e.to_string()))?))
 }
 
+/// Whether `month` is the current month and whether it is a future one, relative to `current`.
+fn month_position(month: Month, current: Month) -> (bool, bool) {
+    (month == current, month > current)
+}
+
+#[derive(Template)]
+#[template(path = "print_view.html")]
+pub struct PrintViewTemplate {
+    pub app_name: String,
+    pub data: MonthViewData,
+}
+
+/// The month without edit controls, navigation or scripts, styled for paper. Honors the same
+/// `filter` and `sort` as the month view.
+async fn get_print_view(
+    State(state): State<Arc<AppState>>,
+    ValidMonth(valid_month): ValidMonth,
+    Query(query): Query<MonthViewQuery>,
+) -> Result<impl IntoResponse, TransactionError> {
+    let sort = query.sort.as_deref().map(TransactionSort::from_param).unwrap_or_default();
+    let data = month_view_data(&state, valid_month, query.filter, sort).await?;
+
+    let template = PrintViewTemplate { app_name: state.config.app_name.clone(), data };
+    Ok(Html(template.render().map_err(|e| TransactionError::Infrastructure(e.to_string()))?))
+}
+
+/// The month for the phone app: ids and raw cents only, so the client formats amounts for its
+/// own locale. Built from the same `MonthViewData` as the pages.
+#[derive(Debug, Serialize)]
+pub struct MobileMonth {
+    pub month: String,
+    pub categories: Vec<MobileCategory>,
+    pub transactions: Vec<MobileTransaction>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MobileCategory {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+    pub spent: i64,         // Cents
+    pub limit: Option<i64>, // Cents, None without a budget
+}
+
+#[derive(Debug, Serialize)]
+pub struct MobileTransaction {
+    pub id: i64,
+    pub date: String, // YYYY-MM-DD
+    pub category_id: i64,
+    pub amount: i64, // Signed cents
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+}
+
+impl From<MonthViewData> for MobileMonth {
+    fn from(data: MonthViewData) -> Self {
+        MobileMonth {
+            month: data.month,
+            categories: data.budget_rows.into_iter().map(|r| MobileCategory {
+                id: r.category_id,
+                name: r.category_name,
+                color: r.category_color,
+                spent: r.spent,
+                limit: r.limit,
+            }).collect(),
+            transactions: data.transactions.into_iter().map(|t| MobileTransaction {
+                id: t.id,
+                date: t.transaction_date,
+                category_id: t.category_id,
+                amount: t.amount,
+                notes: t.notes,
+            }).collect(),
+        }
+    }
+}
+
+async fn get_mobile_month(
+    State(state): State<Arc<AppState>>,
+    ValidMonth(valid_month): ValidMonth,
+) -> Result<Json<MobileMonth>, TransactionError> {
+    let data = month_view_data(&state, valid_month, BudgetFilter::All, TransactionSort::default()).await?;
+    Ok(Json(data.into()))
+}
+
+/// Expense categories with a budget that are at the warning tier or over it, for dashboards and
+/// notifications polling the month.
+#[derive(Debug, Serialize)]
+pub struct OverspendAlerts {
+    pub month: String,
+    pub alerts: Vec<OverspendAlert>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OverspendAlert {
+    pub category_id: i64,
+    pub name: String,
+    pub status: BudgetStatus,
+    pub spent: i64,   // Cents
+    pub limit: i64,   // Cents
+    pub overage: i64, // Cents past the limit, 0 at the warning tier
+    pub percent_spent: f64,
+}
+
+impl From<MonthViewData> for OverspendAlerts {
+    fn from(data: MonthViewData) -> Self {
+        OverspendAlerts {
+            month: data.month,
+            alerts: data.budget_rows.into_iter()
+                // Income short of its target and unbudgeted spending are not overspending
+                .filter(|r| !r.is_income && r.status != BudgetStatus::Ok)
+                .filter_map(|r| Some(OverspendAlert {
+                    category_id: r.category_id,
+                    name: r.category_name,
+                    status: r.status,
+                    spent: r.spent,
+                    limit: r.limit?,
+                    overage: (-r.remaining).max(0),
+                    percent_spent: r.percent_spent_exact,
+                }))
+                .collect(),
+        }
+    }
+}
+
+async fn overspend_alerts(
+    State(state): State<Arc<AppState>>,
+    ValidMonth(valid_month): ValidMonth,
+) -> Result<Json<OverspendAlerts>, TransactionError> {
+    let data = month_view_data(&state, valid_month, BudgetFilter::Warning, TransactionSort::default()).await?;
+    Ok(Json(data.into()))
+}
+
+/// Drops rows the filter hides. Runs after virtual rows and transaction lookups used the full set.
+fn filter_budget_rows(rows: Vec<BudgetRowView>, filter: BudgetFilter) -> Vec<BudgetRowView> {
+    rows.into_iter().filter(|r| filter.keeps(r.status)).collect()
+}
+
+/// Server-sent events for committed transaction changes in the month. Clients should refetch on any event;
+/// a `resync` event means some changes were missed.
+async fn month_events(
+    State(state): State<Arc<AppState>>,
+    ValidMonth(month): ValidMonth,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let receiver = state.db.events().subscribe();
+    let month = month.to_string();
+
+    let events = stream::unfold((receiver, month), |(mut receiver, month)| async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(change) if change.month == month => Event::default()
+                    .event(change.kind.as_str())
+                    .data(json!(change).to_string()),
+                Ok(_) => continue,
+                Err(RecvError::Lagged(_)) => Event::default().event("resync").data(""),
+                Err(RecvError::Closed) => return None,
+            };
+            return Some((Ok(event), (receiver, month)));
+        }
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+async fn get_month_summary_text(
+    State(state): State<Arc<AppState>>,
+    ValidMonth(month): ValidMonth,
+) -> Result<impl IntoResponse, TransactionError> {
+    let text = ReportService::render_month_summary(&state.db, &month.to_string()).await?;
+    Ok(([(axum::http::header::CONTENT_TYPE, "text/plain; charset=utf-8")], text))
+}
+
+#[derive(Deserialize)]
+struct DateRangeQuery {
+    from: String,
+    to: String,
+}
+
+/// One category's transactions in a date range as a CSV download; 404 for an unknown category.
+async fn export_category_csv(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(query): Query<DateRangeQuery>,
+) -> Result<Response, Response> {
+    let category = CategoryService::get_category(&state.db, id).await.map_err(IntoResponse::into_response)?;
+    let csv = ReportService::category_csv(&state.db, category.id, &query.from, &query.to)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    let disposition = format!("attachment; filename=\"category-{}-{}-to-{}.csv\"", category.id, query.from, query.to);
+    Ok((
+        [(header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()), (header::CONTENT_DISPOSITION, disposition)],
+        axum::body::Body::from_stream(csv),
+    ).into_response())
+}
+
+#[derive(Deserialize)]
+struct PeriodTotalsQuery {
+    from: String,
+    to: String,
+    /// `month`, `week` (ISO), `fiscal-year` or `pay-cycle`.
+    #[serde(default = "default_granularity")]
+    granularity: String,
+}
+
+fn default_granularity() -> String {
+    "month".to_string()
+}
+
+async fn period_totals(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PeriodTotalsQuery>,
+) -> Result<Json<Vec<PeriodTotal>>, TransactionError> {
+    let period = state.config.period(&query.granularity).map_err(TransactionError::InvalidInput)?;
+    let totals = ReportService::period_totals(&state.db, &query.from, &query.to, period).await?;
+    Ok(Json(totals))
+}
+
+#[derive(Deserialize)]
+struct PeriodSummaryQuery {
+    /// Any day in the period, YYYY-MM-DD.
+    date: String,
+    /// As for `/periods`.
+    #[serde(default = "default_granularity")]
+    granularity: String,
+}
+
+/// Totals for the month, ISO week, fiscal year or pay cycle containing `?date=`.
+async fn period_summary(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PeriodSummaryQuery>,
+) -> Result<Json<PeriodSummary>, TransactionError> {
+    let period = state.config.period(&query.granularity).map_err(TransactionError::InvalidInput)?;
+    let date = chrono::NaiveDate::parse_from_str(&query.date, "%Y-%m-%d")
+        .map_err(|_| TransactionError::InvalidInput(format!("Invalid date {:?}, expected YYYY-MM-DD", query.date)))?;
+    Ok(Json(TransactionService::get_period_summary(&state.db, period, date).await?))
+}
+
+async fn amount_histogram(
+    State(state): State<Arc<AppState>>,
+    ValidMonth(month): ValidMonth,
+) -> Result<Json<AmountHistogram>, TransactionError> {
+    let histogram = TransactionService::amount_histogram(&state.db, &month.to_string()).await?;
+    Ok(Json(histogram))
+}
+
+async fn weekly_breakdown(
+    State(state): State<Arc<AppState>>,
+    ValidMonth(month): ValidMonth,
+) -> Result<Json<Vec<WeekTotal>>, TransactionError> {
+    let weeks = TransactionService::weekly_breakdown(&state.db, &month.to_string(), state.config.week_start).await?;
+    Ok(Json(weeks))
+}
+
+async fn spend_shares(
+    State(state): State<Arc<AppState>>,
+    ValidMonth(month): ValidMonth,
+) -> Result<Json<Vec<CategoryShare>>, CategoryError> {
+    let shares = CategoryService::spend_shares(&state.db, &month.to_string()).await?;
+    Ok(Json(shares))
+}
+
+async fn classification_ratios(
+    State(state): State<Arc<AppState>>,
+    ValidMonth(month): ValidMonth,
+) -> Result<Json<ClassificationReport>, CategoryError> {
+    let targets = ClassificationTargets {
+        need: state.config.need_target_percent,
+        want: state.config.want_target_percent,
+        saving: state.config.saving_target_percent,
+    };
+    let report = CategoryService::classification_report(&state.db, &month.to_string(), targets).await?;
+    Ok(Json(report))
+}
+
+/// Whether the month's expense budgets exceed its income targets, and by how much.
+async fn plan_check(
+    State(state): State<Arc<AppState>>,
+    ValidMonth(month): ValidMonth,
+) -> Result<Json<BudgetBalance>, CategoryError> {
+    Ok(Json(CategoryService::budget_balance_check(&state.db, &month.to_string()).await?))
+}
+
+async fn get_month_note(
+    State(state): State<Arc<AppState>>,
+    ValidMonth(month): ValidMonth,
+) -> Result<Json<Option<MonthNote>>, CategoryError> {
+    let note = MonthNoteService::get(&state.db, &month.to_string()).await?;
+    Ok(Json(note))
+}
+
+/// Sets or (with blank text) clears the month's note.
+async fn set_month_note(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    ValidMonth(month): ValidMonth,
+    Json(payload): Json<SetMonthNoteRequest>,
+) -> Result<Json<Option<MonthNote>>, CategoryError> {
+    let month = month.to_string();
+    let note = MonthNoteService::set(ctx.as_deref(), &state.db, &month, &payload.text).await?;
+    Ok(Json(note))
+}
+
+/// Copies the previous month's transactions in the requested categories into this month.
+async fn copy_fixed_transactions(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    ValidMonth(month): ValidMonth,
+    Json(payload): Json<CopyFixedRequest>,
+) -> Result<impl IntoResponse, TransactionError> {
+    let copied = TransactionService::copy_fixed_transactions(
+        ctx.as_deref(),
+        &state.db,
+        &month.previous().to_string(),
+        &month.to_string(),
+        &payload.category_ids,
+        state.config.max_transactions_per_month,
+    ).await?;
+    Ok(Json(json!({ "copied": copied })))
+}
+
+#[derive(Deserialize)]
+struct CreateTransactionQuery {
+    /// Respond with JSON including soft warnings instead of redirecting.
+    #[serde(default)]
+    warn: bool,
+}
+
 async fn create_transaction(
     State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Query(query): Query<CreateTransactionQuery>,
+    headers: HeaderMap,
     Form(payload): Form<RawCreateTransactionRequest>,
-) -> Result<impl IntoResponse, TransactionError> {
+) -> Result<Response, TransactionError> {
     let month = if payload.transaction_date.len() >= 7 {
         payload.transaction_date[0..7].to_string()
     } else {
         chrono::Local::now().format("%Y-%m").to_string()
     };
 
-    let card_id = payload.card_id
-        .as_ref()
-        .and_then(|s| if s.is_empty() { None } else { s.parse::<i64>().ok() });
+    let new_category = match (payload.category_id, payload.category_name) {
+        (Some(_), _) => None,
+        (None, Some(name)) if !name.trim().is_empty() => Some(NewCategory {
+            name,
+            is_income: payload.category_is_income.as_deref() == Some("on"),
+            max_categories: state.config.max_categories,
+            palette: state.config.palette(),
+        }),
+        (None, _) => return Err(TransactionError::InvalidInput("Choose a category or type a category name".into())),
+    };
+    let command = CreateTransactionCommand {
+        category_id: payload.category_id.unwrap_or_default(),
+        card_id: payload.card_id,
+        transaction_date: payload.transaction_date,
+        amount_dollars: payload.amount_dollars,
+        notes: payload.notes,
+        excluded: payload.excluded.as_deref() == Some("on"),
+        new_category,
+    };
+
+    // Computed before the insert so the new transaction doesn't skew its own comparison
+    let warnings = if query.warn {
+        TransactionService::warnings_for(&state.db, &command).await?
+    } else {
+        Vec::new()
+    };
 
-    TransactionService::create_transaction(
+    let transaction = TransactionService::create_transaction_returning(
+        ctx.as_deref(),
         &state.db,
-        payload.category_id,
-        card_id,
-        payload.transaction_date,
-        payload.amount_dollars,
-        payload.notes,
+        command,
+        state.config.max_transactions_per_month,
     ).await.map_err(|e| {
         tracing::error!("create_transaction error: {:?}", e);
         e
     })?;
-    
-    Ok(axum::response::Redirect::to(&format!("/budget/{}", month)))
+    let id = transaction.id;
+
+    // HTMX appends the new row in place instead of reloading the month
+    if headers.contains_key("HX-Request") && !query.warn {
+        let template = TransactionRowTemplate { t: transaction_view(&state, &transaction).await? };
+        let html = template.render().map_err(|e| TransactionError::Infrastructure(e.to_string()))?;
+        return Ok((StatusCode::CREATED, Html(html)).into_response());
+    }
+
+    if query.warn {
+        return Ok((StatusCode::CREATED, Json(json!({ "id": id, "warnings": warnings }))).into_response());
+    }
+    Ok(axum::response::Redirect::to(&state.config.url_for(&format!("/budget/{}", month))).into_response())
+}
+
+#[derive(Deserialize)]
+struct ExternalImportRequest {
+    field_map: FieldMap,
+    records: Vec<serde_json::Value>,
+}
+
+/// Imports another app's JSON export; see `import::external::map_and_import`.
+async fn import_external(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Json(payload): Json<ExternalImportRequest>,
+) -> Result<Json<Vec<RecordResult>>, TransactionError> {
+    let results = external::map_and_import(
+        ctx.as_deref(),
+        &state.db,
+        &payload.records,
+        &payload.field_map,
+        state.config.max_transactions_per_month,
+    ).await?;
+    Ok(Json(results))
+}
+
+#[derive(Deserialize)]
+struct PasteRequest {
+    text: String,
+    category_id: i64,
+    card_id: Option<i64>,
+}
+
+/// Bulk entry from rows copied off a bank's website; see `import::paste::import_pasted`.
+async fn import_pasted(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Json(payload): Json<PasteRequest>,
+) -> Result<Json<PasteResult>, TransactionError> {
+    let result = paste::import_pasted(
+        ctx.as_deref(),
+        &state.db,
+        &payload.text,
+        payload.category_id,
+        payload.card_id,
+        state.config.max_transactions_per_month,
+    ).await?;
+    Ok(Json(result))
+}
+
+async fn quick_add_transaction(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Form(payload): Form<QuickAddRequest>,
+) -> Result<impl IntoResponse, TransactionError> {
+    let cmd = TransactionService::parse_quick_add(&state.db, &payload.input).await?;
+    let month = cmd.transaction_date[..7].to_string();
+
+    TransactionService::create_transaction(ctx.as_deref(), &state.db, cmd, state.config.max_transactions_per_month).await?;
+
+    Ok(axum::response::Redirect::to(&state.config.url_for(&format!("/budget/{}", month))))
 }
 
 async fn update_transaction(
     State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
     Path(id): Path<i64>,
     Json(payload): Json<UpdateTransactionRequest>,
 ) -> Result<impl IntoResponse, TransactionError> {
+    let previous_month = TransactionService::get_transaction(&state.db, id).await?.transaction_date[..7].to_string();
     let transaction = TransactionService::update_transaction(
+        ctx.as_deref(),
         &state.db,
         id,
-        payload.category_id,
-        payload.card_id,
-        payload.transaction_date,
-        payload.amount_dollars,
-        payload.notes,
+        CreateTransactionCommand {
+            category_id: payload.category_id,
+            card_id: payload.card_id,
+            transaction_date: payload.transaction_date,
+            amount_dollars: payload.amount_dollars,
+            notes: payload.notes,
+            excluded: payload.excluded,
+            new_category: None,
+        },
+        state.config.max_transactions_per_month,
     ).await?;
+    // The page the edit came from shows the month the transaction was in before
+    let summary = TransactionService::get_month_summary(&state.db, &previous_month).await?;
     
+    let view = transaction_view(&state, &transaction).await?;
+    
+    let template = TransactionRowTemplate { t: view };
+    let html = template.render().map_err(|e| TransactionError::Infrastructure(e.to_string()))?;
+    Ok(([summary_trigger(&summary)?], Html(html)))
+}
+
+/// `HX-Trigger` header firing a `monthSummary` event with the month's new totals, so the page can
+/// refresh its overview after an edit without reloading.
+fn summary_trigger(summary: &MonthlySummary) -> Result<(header::HeaderName, HeaderValue), TransactionError> {
+    let value = json!({ "monthSummary": summary }).to_string();
+    let value = HeaderValue::from_str(&value).map_err(|e| TransactionError::Infrastructure(e.to_string()))?;
+    Ok((header::HeaderName::from_static("hx-trigger"), value))
+}
+
+async fn get_transaction(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
+) -> Result<Response, TransactionError> {
+    let transaction = TransactionService::get_transaction(&state.db, id).await?;
+
+    let wants_html = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"));
+
+    if wants_html {
+        let template = TransactionEditTemplate { t: transaction_view(&state, &transaction).await? };
+        return Ok(Html(template.render().map_err(|e| TransactionError::Infrastructure(e.to_string()))?).into_response());
+    }
+
+    let view = transaction_view(&state, &transaction).await?;
+    Ok(Json(TransactionDetail {
+        transaction,
+        category_name: view.category_name,
+        card_name: view.card_name,
+    }).into_response())
+}
+
+/// Resolves a transaction's category and card for display.
+async fn transaction_view(state: &AppState, transaction: &Transaction) -> Result<TransactionView, TransactionError> {
     let categories = categories::service::CategoryService::list_categories(&state.db)
         .await
-        .map_err(|e| TransactionError::Infrastructure(e.to_string()))?;
+        .map_err(CategoryError::recast)?;
         
     let all_cards = cards::service::CardService::list_cards(&state.db)
         .await
-        .map_err(|e| TransactionError::Infrastructure(e.to_string()))?;
+        .map_err(cards::service::CardError::recast)?;
 
-    let cat = categories.iter()
-        .find(|c| c.id == transaction.category_id);
-    let cat_name = cat.map(|c| c.name.clone()).unwrap_or_else(|| "Unknown".to_string());
-    let cat_color = cat.map(|c| c.color.clone()).unwrap_or_else(|| "#ffffff".to_string());
-        
-    let card_name = all_cards.iter()
-        .find(|c| Some(c.id) == transaction.card_id)
-        .map(|c| c.name.clone())
-        .unwrap_or_else(|| "Cash".to_string());
-        
-    let date_display = chrono::NaiveDate::parse_from_str(&transaction.transaction_date, "%Y-%m-%d")
-        .map(|d| d.format("%e %b %Y").to_string())
-        .unwrap_or_else(|_| transaction.transaction_date.clone());
-        
-    let view = TransactionView {
-        id: transaction.id,
-        category_id: transaction.category_id,
-        card_id: transaction.card_id.unwrap_or(0),
-        category_name: cat_name,
-        category_color: cat_color,
-        card_name,
-        transaction_date: transaction.transaction_date,
-        transaction_date_display: date_display,
-        amount_dollars: format!("{:.2}", transaction.amount.abs() as f64 / 100.0),
-        is_income: transaction.amount > 0,
-        notes: transaction.notes.unwrap_or_default(),
-    };
-    
-    let template = TransactionRowTemplate { t: view };
-    Ok(Html(template.render().map_err(|e| TransactionError::Infrastructure(e.to_string()))?))
+    Ok(TransactionView::from_transaction(transaction, &categories, &all_cards, state.config.money_format()))
 }
 
 async fn delete_transaction(
     State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, TransactionError> {
+    let month = TransactionService::get_transaction(&state.db, id).await?.transaction_date[..7].to_string();
+    TransactionService::delete_transaction(ctx.as_deref(), &state.db, id).await?;
+    let summary = TransactionService::get_month_summary(&state.db, &month).await?;
+    Ok((StatusCode::NO_CONTENT, [summary_trigger(&summary)?]))
+}
+
+async fn set_cleared(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Path(id): Path<i64>,
+    Json(payload): Json<SetClearedRequest>,
+) -> Result<Json<Transaction>, TransactionError> {
+    let transaction = TransactionService::set_cleared(ctx.as_deref(), &state.db, id, payload.cleared).await?;
+    Ok(Json(transaction))
+}
+
+/// The month's uncleared transactions with cleared-vs-uncleared totals, for reconciling against a statement.
+async fn reconciliation(
+    State(state): State<Arc<AppState>>,
+    ValidMonth(month): ValidMonth,
+) -> Result<Json<Reconciliation>, TransactionError> {
+    Ok(Json(TransactionService::reconciliation(&state.db, &month.to_string()).await?))
+}
+
+#[derive(Deserialize)]
+struct SyncQuery {
+    #[serde(default)]
+    since: i64,
+}
+
+/// Transactions changed after the `?since=` cursor for incremental sync; the reply's `cursor` is the next `since`.
+async fn sync_changes(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SyncQuery>,
+) -> Result<Json<SyncChanges>, TransactionError> {
+    Ok(Json(TransactionService::changes_since(&state.db, query.since).await?))
+}
+
+async fn list_rules(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<CategorizationRule>>, TransactionError> {
+    let rules = RuleService::list_rules(&state.db).await?;
+    Ok(Json(rules))
+}
+
+async fn create_rule(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    Json(payload): Json<RawCreateRuleRequest>,
+) -> Result<impl IntoResponse, TransactionError> {
+    let id = RuleService::create_rule(ctx.as_deref(), &state.db, payload.match_text, payload.category_id, payload.priority).await?;
+    Ok((StatusCode::CREATED, Json(json!({ "id": id }))))
+}
+
+async fn delete_rule(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
     Path(id): Path<i64>,
 ) -> Result<impl IntoResponse, TransactionError> {
-    TransactionService::delete_transaction(&state.db, id).await?;
+    RuleService::delete_rule(ctx.as_deref(), &state.db, id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+async fn apply_rules(
+    State(state): State<Arc<AppState>>,
+    ctx: Option<Extension<UserContext>>,
+    ValidMonth(month): ValidMonth,
+) -> Result<impl IntoResponse, TransactionError> {
+    let updated = RuleService::apply_rules(ctx.as_deref(), &state.db, &month.to_string()).await?;
+    Ok(Json(json!({ "updated": updated })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use common::{money::{ExpenseDisplay, NegativeStyle, RoundingMode}, period::Period, Config};
+    use database::{get_test_db, TestDb};
+    use tower::ServiceExt;
+    use tower_sessions::{MemoryStore, SessionManagerLayer};
+
+    async fn app() -> (Router, Arc<AppState>, TestDb) {
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(db.clone(), Config::default()));
+        let router = transactions_router(state.clone())
+            .with_state(state.clone())
+            .layer(SessionManagerLayer::new(MemoryStore::default()));
+        (router, state, db)
+    }
+
+    fn budget_view(is_income: bool, limit_amount: i64) -> categories::models::CategoryBudgetView {
+        categories::models::CategoryBudgetView {
+            category: categories::models::Category {
+                id: 1,
+                name: "Groceries".to_string(),
+                color: "#fff".to_string(),
+                is_income,
+                is_active: true,
+                group: None,
+                allows_negative_budget: false,
+                default_note: None,
+                classification: categories::models::Classification::Ignore,
+                icon: None,
+            },
+            budget: Some(categories::models::MonthlyBudget {
+                id: 1,
+                category_id: 1,
+                month: "2026-01".to_string(),
+                limit_amount,
+                mode: categories::models::BudgetMode::Fixed,
+                percent: None,
+            }),
+            spent: 0,
+            remaining: 0,
+        }
+    }
+
+    fn transaction(category_id: i64, card_id: Option<i64>) -> Transaction {
+        Transaction {
+            id: 7,
+            category_id,
+            card_id,
+            transaction_date: "2026-01-05".to_string(),
+            amount: -1250,
+            notes: None,
+            excluded: false,
+            cleared: false,
+        }
+    }
+
+    #[test]
+    fn test_transaction_view_resolves_names_and_keeps_raw_date() {
+        let categories = vec![budget_view(false, 0).category];
+        let cards = vec![cards::models::Card { id: 3, name: "Visa".to_string(), is_active: true }];
+
+        let view = TransactionView::from_transaction(&transaction(1, Some(3)), &categories, &cards, MoneyFormat::default());
+
+        assert_eq!(view.category_name, "Groceries");
+        assert_eq!(view.category_color, "#fff");
+        assert_eq!(view.card_name, "Visa");
+        assert_eq!(view.card_id, 3);
+        assert_eq!(view.transaction_date, "2026-01-05");
+        assert_eq!(view.transaction_date_display, " 5 Jan 2026");
+        assert_eq!(view.amount_dollars, "12.50");
+        assert!(!view.is_income);
+        assert_eq!(view.notes, "");
+    }
+
+    #[test]
+    fn test_transaction_view_follows_expense_display() {
+        let signed = TransactionView::from_transaction(&transaction(1, None), &[], &[], MoneyFormat::default());
+        assert_eq!(signed.signed_amount_display, "-$12.50");
+
+        let magnitude = MoneyFormat { expense_display: ExpenseDisplay::Magnitude, ..MoneyFormat::default() };
+        let view = TransactionView::from_transaction(&transaction(1, None), &[], &[], magnitude);
+        assert_eq!(view.signed_amount_display, "$12.50 out");
+    }
+
+    #[test]
+    fn test_transaction_view_fallbacks() {
+        let view = TransactionView::from_transaction(&transaction(99, None), &[], &[], MoneyFormat::default());
+
+        assert_eq!(view.category_name, "Unknown");
+        assert_eq!(view.category_color, "#ffffff");
+        assert_eq!(view.card_name, "Cash");
+        assert_eq!(view.card_id, 0);
+    }
+
+    #[test]
+    fn test_transaction_view_labels_deleted_card() {
+        let view = TransactionView::from_transaction(&transaction(1, Some(42)), &[], &[], MoneyFormat::default());
+        assert_eq!(view.card_name, "(deleted card)");
+    }
+
+    #[tokio::test]
+    async fn test_views_survive_i64_min_amounts() {
+        let (_, state, _db) = app().await;
+        let dining = CategoryService::create_category(None, &state.db, "Dining".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_limit(None, &state.db, dining, "2026-01".to_string(), 200.0, RoundingMode::HalfUp).await.unwrap();
+        let id = TransactionService::create_transaction(None, &state.db, CreateTransactionCommand {
+            category_id: dining,
+            card_id: None,
+            transaction_date: "2026-01-05".to_string(),
+            amount_dollars: 12.0,
+            notes: Some("Bistro".to_string()),
+            excluded: false,
+            new_category: None,
+        }, None).await.unwrap();
+        sqlx::query("UPDATE transactions SET amount = $1 WHERE id = $2").bind(i64::MIN).bind(id).execute(&state.db.pool).await.unwrap();
+
+        // The whole month page: summary, per-category actuals and the transaction rows
+        let data = month_view_data(&state, "2026-01".parse().unwrap(), BudgetFilter::All, TransactionSort::default()).await.unwrap();
+        let view = data.transactions.iter().find(|t| t.id == id).unwrap();
+        assert_eq!(view.amount_display, "$92,233,720,368,547,758.08");
+        assert!(!view.is_income);
+        let row = data.budget_rows.iter().find(|r| r.category_id == dining).unwrap();
+        assert!(row.is_over_budget);
+
+        // The other aggregations over the same month saturate too
+        TransactionService::amount_histogram(&state.db, "2026-01").await.unwrap();
+        TransactionService::weekly_breakdown(&state.db, "2026-01", common::week::WeekStart::default()).await.unwrap();
+        let report = ReportService::build_month_report(&state.db, "2026-01").await.unwrap();
+        assert_eq!(report.top_merchants[0].spent, i64::MAX);
+        let totals = ReportService::period_totals(&state.db, "2026-01-01", "2026-01-31", Period::Month).await.unwrap();
+        assert_eq!(totals[0].expenses, i64::MAX);
+
+        let row = BudgetRowView::new(&budget_view(false, i64::MIN), i64::MIN, MoneyFormat::default());
+        assert_eq!(row.remaining, i64::MIN);
+        let row = BudgetRowView::new(&budget_view(false, 20000), i64::MIN, MoneyFormat::default());
+        assert_eq!(row.remaining, i64::MAX);
+        assert!(!row.is_over_budget);
+    }
+
+    #[test]
+    fn test_small_overspend_is_over_budget_despite_rounded_percent() {
+        // $200.00 limit, $201.00 spent: 100.5% reads as "100" once rounded
+        let row = BudgetRowView::new(&budget_view(false, 20000), 20100, MoneyFormat::default());
+
+        assert_eq!(row.percent_spent, "100");
+        assert!((row.percent_spent_exact - 100.5).abs() < 1e-9);
+        assert!((row.percent_remaining_exact + 0.5).abs() < 1e-9);
+        assert!(row.is_over_budget);
+    }
+
+    #[test]
+    fn test_filter_over_keeps_only_over_budget_rows() {
+        let rows = vec![
+            BudgetRowView::new(&budget_view(false, 10000), 5000, MoneyFormat::default()),  // ok
+            BudgetRowView::new(&budget_view(false, 10000), 9000, MoneyFormat::default()),  // warning
+            BudgetRowView::new(&budget_view(false, 10000), 12000, MoneyFormat::default()), // over
+        ];
+        assert_eq!(rows.iter().map(|r| r.status).collect::<Vec<_>>(), vec![BudgetStatus::Ok, BudgetStatus::Warning, BudgetStatus::Over]);
+
+        let over = filter_budget_rows(rows.clone(), BudgetFilter::Over);
+        assert_eq!(over.len(), 1);
+        assert!(over.iter().all(|r| r.is_over_budget));
+
+        assert_eq!(filter_budget_rows(rows.clone(), BudgetFilter::Warning).len(), 2);
+        assert_eq!(filter_budget_rows(rows, BudgetFilter::All).len(), 3);
+    }
+
+    #[test]
+    fn test_exactly_on_budget_is_not_over() {
+        let row = BudgetRowView::new(&budget_view(false, 20000), 20000, MoneyFormat::default());
+        assert!(!row.is_over_budget);
+        assert_eq!(row.remaining_dollars, "0.00");
+    }
+
+    #[test]
+    fn test_budget_row_view_formats_display_amounts() {
+        let row = BudgetRowView::new(&budget_view(false, 1234567), 1300000, MoneyFormat::default());
+
+        assert_eq!(row.limit_display, "$12,345.67");
+        assert_eq!(row.spent_display, "$13,000.00");
+        assert_eq!(row.remaining_display, "-$654.33");
+        // The limit input still gets a plain number
+        assert_eq!(row.limit_dollars, "12345.67");
+
+        let accounting = MoneyFormat { negative_style: NegativeStyle::Parentheses, ..MoneyFormat::default() };
+        let row = BudgetRowView::new(&budget_view(false, 1234567), 1300000, accounting);
+        assert_eq!(row.remaining_display, "($654.33)");
+    }
+
+    #[tokio::test]
+    async fn test_get_transaction_returns_resolved_json() {
+        let (app, state, _db) = app().await;
+        let category_id = categories::service::CategoryService::create_category(None, &state.db, "Dining".to_string(), false, None).await.unwrap();
+        let id = TransactionService::create_transaction(None, &state.db, CreateTransactionCommand {
+            category_id,
+            card_id: None,
+            transaction_date: "2026-01-05".to_string(),
+            amount_dollars: 12.5,
+            notes: Some("Lunch".to_string()),
+            excluded: false,
+            new_category: None,
+        }, None).await.unwrap();
+
+        let request = Request::builder()
+            .uri(format!("/transaction/{}", id))
+            .header(header::ACCEPT, "application/json")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["id"], id);
+        assert_eq!(json["category_id"], category_id);
+        assert_eq!(json["category_name"], "Dining");
+        assert_eq!(json["card_name"], "Cash");
+        assert_eq!(json["transaction_date"], "2026-01-05");
+        assert_eq!(json["amount"], json!({ "cents": -1250, "formatted": "-$12.50" }));
+        assert_eq!(json["notes"], "Lunch");
+    }
+
+    #[tokio::test]
+    async fn test_get_missing_transaction_is_404() {
+        let (app, _, _db) = app().await;
+        let request = Request::builder()
+            .uri("/transaction/9999")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_empty_card_is_null_on_create_and_update() {
+        let (app, state, _db) = app().await;
+        let category_id = categories::service::CategoryService::create_category(None, &state.db, "Dining".to_string(), false, None).await.unwrap();
+
+        let create = Request::builder()
+            .method("POST")
+            .uri("/add")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(format!("category_id={}&card_id=&transaction_date=2026-01-05&amount_dollars=12.50", category_id)))
+            .unwrap();
+        let response = app.clone().oneshot(create).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let (transactions, _) = TransactionService::get_month_view(&state.db, "2026-01", TransactionSort::default()).await.unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].card_id, None);
+
+        let card_id = cards::service::CardService::create_card(None, &state.db, "Visa".to_string()).await.unwrap();
+        let id = transactions[0].id;
+        let update = |card_id: serde_json::Value| {
+            Request::builder()
+                .method("PUT")
+                .uri(format!("/transaction/{}", id))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(json!({
+                    "category_id": category_id,
+                    "card_id": card_id,
+                    "transaction_date": "2026-01-05",
+                    "amount_dollars": "12.50",
+                }).to_string()))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(update(json!(card_id.to_string()))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(TransactionService::get_transaction(&state.db, id).await.unwrap().card_id, Some(card_id));
+
+        let response = app.oneshot(update(json!(""))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(TransactionService::get_transaction(&state.db, id).await.unwrap().card_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_update_and_delete_send_month_summary_trigger() {
+        let (app, state, _db) = app().await;
+        let dining = categories::service::CategoryService::create_category(None, &state.db, "Dining".to_string(), false, None).await.unwrap();
+        let paycheck = categories::service::CategoryService::create_category(None, &state.db, "Paycheck".to_string(), true, None).await.unwrap();
+        let command = |category_id: i64, amount_dollars: f64| CreateTransactionCommand {
+            category_id,
+            card_id: None,
+            transaction_date: "2026-01-05".to_string(),
+            amount_dollars,
+            notes: None,
+            excluded: false,
+            new_category: None,
+        };
+        TransactionService::create_transaction(None, &state.db, command(paycheck, 1000.0), None).await.unwrap();
+        let id = TransactionService::create_transaction(None, &state.db, command(dining, 20.0), None).await.unwrap();
+        let trigger = |response: &Response| -> serde_json::Value {
+            serde_json::from_str(response.headers().get("HX-Trigger").unwrap().to_str().unwrap()).unwrap()
+        };
+
+        let update = Request::builder()
+            .method("PUT")
+            .uri(format!("/transaction/{}", id))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json!({
+                "category_id": dining,
+                "transaction_date": "2026-01-05",
+                "amount_dollars": "45.50",
+            }).to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(update).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let summary = &trigger(&response)["monthSummary"];
+        assert_eq!(summary["month"], "2026-01");
+        assert_eq!(summary["total_income"]["cents"], 100000);
+        assert_eq!(summary["total_expenses"]["cents"], 4550);
+        assert_eq!(summary["net"], json!({ "cents": 95450, "formatted": "$954.50" }));
+        // The row fragment is still the body
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8(body.to_vec()).unwrap().contains(&format!("row-{}", id)));
+
+        let delete = Request::builder()
+            .method("DELETE")
+            .uri(format!("/transaction/{}", id))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(delete).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(trigger(&response)["monthSummary"]["total_expenses"]["cents"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_three_decimal_amount_is_rejected() {
+        let (app, state, _db) = app().await;
+        let category_id = categories::service::CategoryService::create_category(None, &state.db, "Dining".to_string(), false, None).await.unwrap();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/add")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(format!("category_id={}&transaction_date=2026-01-05&amount_dollars=12.345", category_id)))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let (transactions, _) = TransactionService::get_month_view(&state.db, "2026-01", TransactionSort::default()).await.unwrap();
+        assert!(transactions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_large_amount_warns_but_still_creates() {
+        let (app, state, _db) = app().await;
+        let category_id = categories::service::CategoryService::create_category(None, &state.db, "Dining".to_string(), false, None).await.unwrap();
+        for day in ["2026-01-05", "2026-01-06", "2026-01-07"] {
+            let cmd = CreateTransactionCommand {
+                category_id,
+                card_id: None,
+                transaction_date: day.to_string(),
+                amount_dollars: 10.0,
+                notes: None,
+                excluded: false,
+                new_category: None,
+            };
+            TransactionService::create_transaction(None, &state.db, cmd, None).await.unwrap();
+        }
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/add?warn=true")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(format!("category_id={}&transaction_date=2026-01-08&amount_dollars=150", category_id)))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let warnings = json["warnings"].as_array().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].as_str().unwrap().contains("average of $10.00"));
+
+        let created = TransactionService::get_transaction(&state.db, json["id"].as_i64().unwrap()).await.unwrap();
+        assert_eq!(created.amount, -15000);
+    }
+
+    #[tokio::test]
+    async fn test_no_roles_context_is_forbidden_from_creating() {
+        let (app, state, _db) = app().await;
+        let app = app.layer(Extension(UserContext { user_id: 2, roles: Vec::new() }));
+        let category_id = categories::service::CategoryService::create_category(None, &state.db, "Dining".to_string(), false, None).await.unwrap();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/add")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(format!("category_id={}&transaction_date=2026-01-05&amount_dollars=12", category_id)))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let (transactions, _) = TransactionService::get_month_view(&state.db, "2026-01", TransactionSort::default()).await.unwrap();
+        assert!(transactions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_sort_falls_back_to_default() {
+        let (app, _, _db) = app().await;
+        let request = Request::builder().uri("/2026-01?sort=bogus").body(Body::empty()).unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+        assert!(html.contains(r#"value="date_desc" selected"#));
+    }
+
+    #[tokio::test]
+    async fn test_month_events_stream_receives_create() {
+        use futures_util::StreamExt;
+
+        let (app, state, _db) = app().await;
+        let request = Request::builder().uri("/2026-01/events").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let mut body = response.into_body().into_data_stream();
+
+        let category_id = categories::service::CategoryService::create_category(None, &state.db, "Dining".to_string(), false, None).await.unwrap();
+        let other_month = Request::builder()
+            .method("POST")
+            .uri("/add")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(format!("category_id={}&transaction_date=2026-02-01&amount_dollars=3.00", category_id)))
+            .unwrap();
+        app.clone().oneshot(other_month).await.unwrap();
+        let create = Request::builder()
+            .method("POST")
+            .uri("/add")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(format!("category_id={}&transaction_date=2026-01-05&amount_dollars=12.50", category_id)))
+            .unwrap();
+        let created = app.oneshot(create).await.unwrap();
+        assert!(created.status().is_redirection());
+
+        let chunk = tokio::time::timeout(std::time::Duration::from_secs(5), body.next())
+            .await
+            .expect("no event within 5s")
+            .unwrap()
+            .unwrap();
+        let text = String::from_utf8(chunk.to_vec()).unwrap();
+        assert!(text.contains("event: created"), "{}", text);
+        assert!(text.contains(r#""month":"2026-01""#), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_print_view_has_no_edit_controls() {
+        let (app, state, _db) = app().await;
+        let dining = CategoryService::create_category(None, &state.db, "Dining".to_string(), false, None).await.unwrap();
+        TransactionService::create_transaction(None, &state.db, CreateTransactionCommand {
+            category_id: dining,
+            card_id: None,
+            transaction_date: "2026-01-05".to_string(),
+            amount_dollars: 12.0,
+            notes: Some("Bistro".to_string()),
+            excluded: false,
+            new_category: None,
+        }, None).await.unwrap();
+
+        let request = Request::builder().uri("/2026-01/print").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+        assert!(html.contains("January 2026"));
+        assert!(html.contains("Bistro"));
+        assert!(!html.contains("add-transaction-form"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("<button"));
+    }
+
+    #[test]
+    fn test_month_position_flags() {
+        let current: Month = "2026-03".parse().unwrap();
+
+        assert_eq!(month_position("2026-02".parse().unwrap(), current), (false, false));
+        assert_eq!(month_position(current, current), (true, false));
+        assert_eq!(month_position("2026-04".parse().unwrap(), current), (false, true));
+        assert_eq!(month_position("2025-12".parse().unwrap(), current), (false, false));
+    }
+
+    #[tokio::test]
+    async fn test_income_rows_come_first_when_configured() {
+        let (_, state, _db) = app().await;
+        CategoryService::create_category(None, &state.db, "Bonus".to_string(), true, None).await.unwrap();
+        let month: Month = "2026-01".parse().unwrap();
+
+        let data = month_view_data(&state, month, BudgetFilter::All, TransactionSort::default()).await.unwrap();
+        let income: Vec<bool> = data.budget_rows.iter().map(|r| r.is_income).collect();
+        let first_expense = income.iter().position(|is_income| !is_income).unwrap();
+        assert!(first_expense >= 2, "Bonus and Salary lead: {:?}", income);
+        assert!(income[first_expense..].iter().all(|is_income| !is_income));
+        assert_eq!(data.budget_rows[0].category_name, "Bonus");
+
+        let alphabetical = AppState::new(state.db.clone(), Config { income_first: false, ..Config::default() });
+        let data = month_view_data(&alphabetical, month, BudgetFilter::All, TransactionSort::default()).await.unwrap();
+        let names: Vec<&str> = data.budget_rows.iter().map(|r| r.category_name.as_str()).collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+    }
+
+    #[tokio::test]
+    async fn test_category_export_csv_download() {
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(db.clone(), Config::default()));
+        let app = category_export_router(state.clone()).with_state(state.clone());
+        let gym = CategoryService::create_category(None, &state.db, "Gym".to_string(), false, None).await.unwrap();
+
+        let uri = format!("/{}/export.csv?from=2026-01-01&to=2026-01-31", gym);
+        let response = app.clone().oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/csv"));
+
+        let missing = Request::builder().uri("/9999/export.csv?from=2026-01-01&to=2026-01-31").body(Body::empty()).unwrap();
+        assert_eq!(app.clone().oneshot(missing).await.unwrap().status(), StatusCode::NOT_FOUND);
+
+        let bad_range = Request::builder().uri(format!("/{}/export.csv?from=2026-02-01&to=2026-01-01", gym)).body(Body::empty()).unwrap();
+        assert_eq!(app.oneshot(bad_range).await.unwrap().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_period_summary_uses_configured_pay_cycle() {
+        let request = || Request::builder().uri("/periods/summary?granularity=pay-cycle&date=2026-04-01").body(Body::empty()).unwrap();
+
+        // Without an anchor there is no pay cycle to group by
+        let (app, _, _db) = app().await;
+        assert_eq!(app.oneshot(request()).await.unwrap().status(), StatusCode::BAD_REQUEST);
+
+        let config = Config { pay_cycle_anchor: Some("2026-03-20".parse().unwrap()), ..Config::default() };
+        let db = get_test_db().await;
+        let state = Arc::new(AppState::new(db.clone(), config));
+        let app = transactions_router(state.clone()).with_state(state);
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let summary: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(summary["key"], "2026-03-20");
+        assert_eq!(summary["to"], "2026-04-02");
+    }
+
+    #[tokio::test]
+    async fn test_mobile_month_is_trimmed() {
+        let (app, state, _db) = app().await;
+        let dining = CategoryService::create_category(None, &state.db, "Dining".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_limit(None, &state.db, dining, "2026-01".to_string(), 80.0, RoundingMode::HalfUp).await.unwrap();
+        TransactionService::create_transaction(None, &state.db, CreateTransactionCommand {
+            category_id: dining,
+            card_id: None,
+            transaction_date: "2026-01-12".to_string(),
+            amount_dollars: 12.5,
+            notes: Some("Tacos".to_string()),
+            excluded: false,
+            new_category: None,
+        }, None).await.unwrap();
+
+        let response = app.oneshot(Request::builder().uri("/2026-01/mobile.json").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let category = json["categories"].as_array().unwrap().iter().find(|c| c["id"] == dining).unwrap();
+        assert_eq!(category, &json!({ "id": dining, "name": "Dining", "color": category["color"], "spent": 1250, "limit": 8000 }));
+
+        let transaction = &json["transactions"][0];
+        assert_eq!(transaction["amount"], -1250);
+        assert_eq!(transaction["date"], "2026-01-12");
+        assert_eq!(transaction["notes"], "Tacos");
+        for heavy in ["amount_display", "amount_dollars", "transaction_date_display", "category_name", "card_name"] {
+            assert!(transaction.get(heavy).is_none(), "{} should be omitted", heavy);
+        }
+        assert!(json.get("overview").is_none() && json.get("budget_groups").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_overspend_alerts_list_only_overspent_categories() {
+        let (app, state, _db) = app().await;
+        let dining = CategoryService::create_category(None, &state.db, "Dining".to_string(), false, None).await.unwrap();
+        let books = CategoryService::create_category(None, &state.db, "Books".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_limit(None, &state.db, dining, "2026-01".to_string(), 50.0, RoundingMode::HalfUp).await.unwrap();
+        CategoryService::set_monthly_limit(None, &state.db, books, "2026-01".to_string(), 100.0, RoundingMode::HalfUp).await.unwrap();
+        for (category_id, amount_dollars) in [(dining, 62.25), (books, 20.0)] {
+            TransactionService::create_transaction(None, &state.db, CreateTransactionCommand {
+                category_id,
+                card_id: None,
+                transaction_date: "2026-01-12".to_string(),
+                amount_dollars,
+                notes: None,
+                excluded: false,
+                new_category: None,
+            }, None).await.unwrap();
+        }
+
+        let response = app.oneshot(Request::builder().uri("/2026-01/overspend-alerts").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(json["month"], "2026-01");
+        let alerts = json["alerts"].as_array().unwrap();
+        let alert = alerts.iter().find(|a| a["category_id"] == dining).unwrap();
+        assert_eq!(alert["status"], "over");
+        assert_eq!(alert["spent"], 6225);
+        assert_eq!(alert["limit"], 5000);
+        assert_eq!(alert["overage"], 1225);
+        assert!(alerts.iter().all(|a| a["category_id"] != books));
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_pool_returns_503_with_retry_after() {
+        let db = get_test_db().await;
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .acquire_timeout(std::time::Duration::from_millis(100))
+            .connect_with((*db.pool.connect_options()).clone())
+            .await
+            .unwrap();
+        let state = Arc::new(AppState::new(database::Database::from_pool(pool), Config::default()));
+        let app = transactions_router(state.clone())
+            .with_state(state.clone())
+            .layer(SessionManagerLayer::new(MemoryStore::default()));
+
+        // Another request holding the only connection
+        let held = state.db.connection().await.unwrap();
+        let response = app.clone().oneshot(Request::builder().uri("/2026-01/mobile.json").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[axum::http::header::RETRY_AFTER], common::errors::RETRY_AFTER_SECS.to_string());
+
+        drop(held);
+        let response = app.oneshot(Request::builder().uri("/2026-01/mobile.json").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
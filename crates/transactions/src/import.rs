@@ -0,0 +1,21 @@
+pub mod external;
+pub mod paste;
+
+use chrono::NaiveDate;
+
+/// Date formats the importers accept; the first is ours, the second is common in US exports.
+const DATE_FORMATS: [&str; 2] = ["%Y-%m-%d", "%m/%d/%Y"];
+
+/// An imported date in any accepted format, normalized to YYYY-MM-DD.
+pub(crate) fn parse_import_date(raw: &str) -> Result<String, String> {
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(raw.trim(), format).ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .ok_or_else(|| format!("Unrecognised date \"{}\"", raw))
+}
+
+/// An imported amount in dollars, ignoring `$` and thousands separators; `None` if it isn't a number.
+pub(crate) fn parse_import_amount(raw: &str) -> Option<f64> {
+    raw.trim().replace(['$', ','], "").parse::<f64>().ok()
+}
@@ -0,0 +1,162 @@
+use crate::models::CreateTransactionCommand;
+use crate::service::{TransactionError, TransactionService};
+use categories::models::UNCATEGORIZED_CATEGORY_NAME;
+use categories::service::{CategoryError, CategoryService};
+use crate::import::{parse_import_amount, parse_import_date};
+use common::auth::UserContext;
+use database::Database;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::instrument;
+
+/// Names the incoming keys that hold each transaction field, e.g. `{"date": "Date", "amount": "Amount",
+/// "category": "Category", "notes": "Description"}` for a Mint export.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldMap {
+    pub date: String,
+    pub amount: String,
+    pub category: String,
+    pub notes: Option<String>,
+}
+
+/// Outcome of one incoming record: the new transaction's id, or why it was skipped.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct RecordResult {
+    pub index: usize,
+    pub id: Option<i64>,
+    pub month: Option<String>, // YYYY-MM of the created transaction
+    pub uncategorized: bool,   // The category was missing or unknown
+    pub error: Option<String>,
+}
+
+/// Maps each record through `field_map` and creates a transaction for it. Categories are matched by
+/// name, ignoring case; unknown or missing ones go to "Uncategorized". The amount's sign is ignored,
+/// as on the add form: the category decides income or expense. Records are independent, so one bad
+/// record is reported in its result and does not stop the rest.
+#[instrument(skip(db, records))]
+pub async fn map_and_import(
+    ctx: Option<&UserContext>,
+    db: &Database,
+    records: &[Value],
+    field_map: &FieldMap,
+    max_per_month: Option<u32>,
+) -> Result<Vec<RecordResult>, TransactionError> {
+    let categories = CategoryService::list_categories(db)
+        .await
+        .map_err(CategoryError::recast)?;
+    let uncategorized_id = categories
+        .iter()
+        .find(|c| c.name == UNCATEGORIZED_CATEGORY_NAME)
+        .map(|c| c.id)
+        .ok_or_else(|| TransactionError::Infrastructure("Uncategorized category is missing".into()))?;
+
+    let mut results = Vec::with_capacity(records.len());
+    for (index, record) in records.iter().enumerate() {
+        let mut result = RecordResult { index, id: None, month: None, uncategorized: false, error: None };
+
+        let category_id = match text_field(record, &field_map.category) {
+            Some(name) => categories.iter().find(|c| c.name.eq_ignore_ascii_case(name.trim())).map(|c| c.id),
+            None => None,
+        };
+        result.uncategorized = category_id.is_none();
+
+        let command = map_record(record, field_map).map(|(transaction_date, amount_dollars, notes)| CreateTransactionCommand {
+            category_id: category_id.unwrap_or(uncategorized_id),
+            card_id: None,
+            transaction_date,
+            amount_dollars,
+            notes,
+            excluded: false,
+            new_category: None,
+        });
+
+        match command {
+            Ok(command) => {
+                let month = command.transaction_date[..7].to_string();
+                match TransactionService::create_transaction(ctx, db, command, max_per_month).await {
+                    Ok(id) => {
+                        result.id = Some(id);
+                        result.month = Some(month);
+                    }
+                    Err(TransactionError::InvalidInput(msg)) => result.error = Some(msg),
+                    Err(e) => return Err(e),
+                }
+            }
+            Err(msg) => result.error = Some(msg),
+        }
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
+/// The record's date (normalized to YYYY-MM-DD), amount in dollars and notes.
+pub(crate) fn map_record(record: &Value, field_map: &FieldMap) -> Result<(String, f64, Option<String>), String> {
+    let raw_date = text_field(record, &field_map.date).ok_or_else(|| format!("Missing date field \"{}\"", field_map.date))?;
+    let date = parse_import_date(raw_date)?;
+
+    let amount = match record.get(&field_map.amount) {
+        Some(Value::Number(n)) => n.as_f64(),
+        Some(Value::String(s)) => parse_import_amount(s),
+        _ => None,
+    }
+    .ok_or_else(|| format!("Missing or invalid amount field \"{}\"", field_map.amount))?;
+
+    let notes = field_map
+        .notes
+        .as_deref()
+        .and_then(|key| text_field(record, key))
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+
+    Ok((date, amount, notes))
+}
+
+fn text_field<'a>(record: &'a Value, key: &str) -> Option<&'a str> {
+    record.get(key).and_then(Value::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::get_test_db;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_import_with_custom_field_map() {
+        let db = get_test_db().await;
+        let records = json!([
+            {"Date": "03/14/2026", "Amount": "-1,234.50", "Category": "groceries", "Description": "Costco"},
+            {"Date": "2026-03-15", "Amount": 12.0, "Category": "Coffee Shops", "Description": "Cafe"},
+            {"Date": "yesterday", "Amount": 5, "Category": "Groceries"},
+            {"Date": "2026-03-16", "Category": "Groceries"}
+        ]);
+        let field_map = FieldMap {
+            date: "Date".to_string(),
+            amount: "Amount".to_string(),
+            category: "Category".to_string(),
+            notes: Some("Description".to_string()),
+        };
+
+        let results = map_and_import(None, &db, records.as_array().unwrap(), &field_map, None).await.unwrap();
+        assert_eq!(results.len(), 4);
+
+        let costco = TransactionService::get_transaction(&db, results[0].id.unwrap()).await.unwrap();
+        assert_eq!(costco.transaction_date, "2026-03-14");
+        assert_eq!(costco.amount, -123450);
+        assert_eq!(costco.notes.as_deref(), Some("Costco"));
+        assert!(!results[0].uncategorized);
+        assert_eq!(results[0].month.as_deref(), Some("2026-03"));
+
+        // Unknown category falls back to Uncategorized
+        assert!(results[1].uncategorized);
+        let cafe = TransactionService::get_transaction(&db, results[1].id.unwrap()).await.unwrap();
+        let categories = CategoryService::list_categories(&db).await.unwrap();
+        let category = categories.iter().find(|c| c.id == cafe.category_id).unwrap();
+        assert_eq!(category.name, UNCATEGORIZED_CATEGORY_NAME);
+
+        assert!(results[2].id.is_none());
+        assert!(results[2].error.as_deref().unwrap().contains("date"));
+        assert!(results[3].error.as_deref().unwrap().contains("amount"));
+    }
+}
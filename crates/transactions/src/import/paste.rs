@@ -0,0 +1,137 @@
+use crate::import::{parse_import_amount, parse_import_date};
+use crate::models::CreateTransactionRequest;
+use crate::repository::TransactionRepository;
+use crate::service::TransactionError;
+use categories::service::CategoryService;
+use common::auth::UserContext;
+use database::Database;
+use database::events::ChangeKind;
+use serde::Serialize;
+use tracing::instrument;
+
+/// Why one pasted line was rejected; `line` is 1-based, as the user sees it.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct LineError {
+    pub line: usize,
+    pub error: String,
+}
+
+/// Outcome of a paste: how many transactions were created, or every line that stopped them.
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct PasteResult {
+    pub created: usize,
+    pub months: Vec<String>,
+    pub errors: Vec<LineError>,
+}
+
+/// Creates one transaction per line of `"date<TAB>amount<TAB>notes"` text, as copied from a bank's
+/// website, all in `category_id` and on `card_id`. Lines are read with the external import's date
+/// and amount rules, so the amount's sign is ignored and blank lines are skipped. It's all or
+/// nothing: if any line is bad, the errors are returned and no transaction is created.
+#[instrument(skip(db, text))]
+pub async fn import_pasted(
+    ctx: Option<&UserContext>,
+    db: &Database,
+    text: &str,
+    category_id: i64,
+    card_id: Option<i64>,
+    max_per_month: Option<u32>,
+) -> Result<PasteResult, TransactionError> {
+    UserContext::authorize(ctx).map_err(TransactionError::Forbidden)?;
+
+    let category = CategoryService::get_category(db, category_id)
+        .await
+        .map_err(|_| TransactionError::InvalidInput("Invalid category ID".into()))?;
+
+    let mut result = PasteResult::default();
+    let mut requests = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let request = parse_line(line).and_then(|(date, amount, notes)| {
+            CreateTransactionRequest::new(category.id, card_id, date, amount, category.is_income, notes, false)
+        });
+        match request {
+            Ok(request) => requests.push((index + 1, request)),
+            Err(error) => result.errors.push(LineError { line: index + 1, error }),
+        }
+    }
+
+    if requests.is_empty() && result.errors.is_empty() {
+        return Err(TransactionError::InvalidInput("Nothing to import".into()));
+    }
+    if !result.errors.is_empty() {
+        return Ok(result);
+    }
+
+    let mut uow = db.begin().await?;
+    let mut repo = TransactionRepository::new(uow.connection());
+    for (line, request) in &requests {
+        let month = &request.transaction_date()[..7];
+        if let Some(max) = max_per_month
+            && repo.count_for_month(month).await? >= i64::from(max)
+        {
+            result.errors.push(LineError { line: *line, error: format!("Transaction limit of {} for {} reached", max, month) });
+            continue;
+        }
+
+        repo.create(request).await?;
+        if !result.months.iter().any(|m| m == month) {
+            result.months.push(month.to_string());
+        }
+        result.created += 1;
+    }
+
+    if !result.errors.is_empty() {
+        // Undo whatever the earlier lines inserted
+        uow.rollback().await?;
+        return Ok(PasteResult { errors: result.errors, ..PasteResult::default() });
+    }
+
+    uow.commit().await?;
+    for month in &result.months {
+        db.events().publish(month, ChangeKind::Created, None);
+    }
+    Ok(result)
+}
+
+/// A `"date<TAB>amount<TAB>notes"` line's date (normalized to YYYY-MM-DD), amount in dollars and notes.
+fn parse_line(line: &str) -> Result<(String, f64, Option<String>), String> {
+    let mut fields = line.splitn(3, '\t');
+    let date = parse_import_date(fields.next().unwrap_or_default())?;
+    let raw_amount = fields.next().unwrap_or_default();
+    let amount = parse_import_amount(raw_amount).ok_or_else(|| format!("Missing or invalid amount \"{}\"", raw_amount))?;
+    let notes = fields.next().map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    Ok((date, amount, notes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TransactionSort;
+    use crate::service::TransactionService;
+    use database::get_test_db;
+
+    #[tokio::test]
+    async fn test_malformed_line_commits_nothing() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".into(), false, None).await.unwrap();
+
+        let text = "2026-03-02\t12.50\tTaco place\n03/04/2026\tabc\tBroken\n2026-03-06\t$1,020.00\tCatering\n";
+        let result = import_pasted(None, &db, text, dining, None, None).await.unwrap();
+
+        assert_eq!(result.created, 0);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].line, 2);
+        assert!(result.errors[0].error.contains("amount"));
+        assert!(TransactionService::get_month_view(&db, "2026-03", TransactionSort::default()).await.unwrap().0.is_empty());
+
+        let fixed = text.replace("abc", "7");
+        let result = import_pasted(None, &db, &fixed, dining, None, None).await.unwrap();
+        assert_eq!(result.created, 3);
+        assert_eq!(result.months, vec!["2026-03".to_string()]);
+        assert!(result.errors.is_empty());
+    }
+}
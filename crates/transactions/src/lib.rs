@@ -1,4 +1,8 @@
 pub mod models;
 mod repository;
+mod rule_repository;
 pub mod service;
-pub mod handler;
\ No newline at end of file
+pub mod handler;
+pub mod import;
+pub mod reports;
+pub mod rules;
\ No newline at end of file
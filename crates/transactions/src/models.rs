@@ -1,14 +1,27 @@
 use serde::{Deserialize, Serialize};
 use chrono::NaiveDate;
+use common::palette::Palette;
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, PartialEq)]
 pub struct Transaction {
     pub id: i64,
     pub category_id: i64,
     pub card_id: Option<i64>,
     pub transaction_date: String, // 'YYYY-MM-DD'
+    #[serde(serialize_with = "common::money::serialize_cents")]
     pub amount: i64,             // Cents
     pub notes: Option<String>,
+    pub excluded: bool,          // Not counted towards budgets or the monthly summary
+    pub cleared: bool,           // Ticked off against a bank statement
+}
+
+/// A transaction whose card or category no longer exists.
+#[derive(Debug, Serialize)]
+pub struct OrphanedTransaction {
+    #[serde(flatten)]
+    pub transaction: Transaction,
+    pub missing_card: bool,
+    pub missing_category: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -18,15 +31,48 @@ pub struct CreateTransactionRequest {
     transaction_date: String,
     amount: i64,
     notes: Option<String>,
+    excluded: bool,
 }
 
 #[derive(Deserialize)]
 pub struct RawCreateTransactionRequest {
+    /// Blank or missing falls back to `category_name`.
+    #[serde(default, deserialize_with = "common::forms::deserialize_optional_id")]
+    pub category_id: Option<i64>,
+    /// Looked up case-insensitively, and created when no category has this name.
+    pub category_name: Option<String>,
+    pub category_is_income: Option<String>, // Checkbox: "on" makes a newly created category income
+    #[serde(default, deserialize_with = "common::forms::deserialize_optional_id")]
+    pub card_id: Option<i64>,
+    pub transaction_date: String,
+    /// A plain amount or a sum like `45.50 + 12.25`.
+    #[serde(deserialize_with = "common::money::deserialize_dollar_expression")]
+    pub amount_dollars: f64,
+    pub notes: Option<String>,
+    pub excluded: Option<String>, // Checkbox: "on" when set
+}
+
+/// Service input for creating or updating a transaction.
+#[derive(Debug, Clone)]
+pub struct CreateTransactionCommand {
     pub category_id: i64,
-    pub card_id: Option<String>,
+    pub card_id: Option<i64>,
     pub transaction_date: String,
     pub amount_dollars: f64,
     pub notes: Option<String>,
+    pub excluded: bool,
+    /// On create, file the transaction under this category instead of `category_id`, creating it
+    /// if needed. Ignored on update.
+    pub new_category: Option<NewCategory>,
+}
+
+/// A category named while entering a transaction.
+#[derive(Debug, Clone)]
+pub struct NewCategory {
+    pub name: String,
+    pub is_income: bool,            // Only used when the category has to be created
+    pub max_categories: Option<u32>, // The category cap to respect when creating it
+    pub palette: Palette,            // Colors to pick from when creating it
 }
 
 impl CreateTransactionRequest {
@@ -37,12 +83,20 @@ impl CreateTransactionRequest {
         amount_dollars: f64,
         is_income: bool,
         notes: Option<String>,
+        excluded: bool,
     ) -> Result<Self, String> {
-        if NaiveDate::parse_from_str(&transaction_date, "%Y-%m-%d").is_err() {
+        // chrono accepts unpadded fields ("2026-1-5"), which the column's CHECK constraint does not
+        if transaction_date.len() != 10 || NaiveDate::parse_from_str(&transaction_date, "%Y-%m-%d").is_err() {
             return Err("Invalid date format, expected YYYY-MM-DD".to_string());
         }
 
-        let mut amount = (amount_dollars.abs() * 100.0).round() as i64;
+        // Cents are the stored precision; a third decimal would be lost on the round trip
+        if common::money::has_fractional_cents(amount_dollars) {
+            return Err("Amount can have at most two decimal places".to_string());
+        }
+
+        // Whole cents after the check above, so the rounding mode can't change the result
+        let mut amount = common::money::to_cents(amount_dollars.abs(), common::money::RoundingMode::HalfUp);
         if !is_income {
             amount = -amount;
         }
@@ -53,9 +107,16 @@ impl CreateTransactionRequest {
             transaction_date,
             amount,
             notes,
+            excluded,
         })
     }
 
+    /// The same request filed under another category, with the amount's sign following its `is_income`.
+    pub fn for_category(self, category_id: i64, is_income: bool) -> Self {
+        let amount = if is_income { self.amount.abs() } else { -self.amount.abs() };
+        Self { category_id, amount, ..self }
+    }
+
     pub fn category_id(&self) -> i64 {
         self.category_id
     }
@@ -75,29 +136,228 @@ impl CreateTransactionRequest {
     pub fn notes(&self) -> Option<&str> {
         self.notes.as_deref()
     }
+
+    pub fn excluded(&self) -> bool {
+        self.excluded
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct CategorizationRule {
+    pub id: i64,
+    pub match_text: String,
+    pub category_id: i64,
+    pub priority: i64, // Lower values are tried first
+}
+
+#[derive(Debug)]
+pub struct CreateRuleRequest {
+    match_text: String,
+    category_id: i64,
+    priority: i64,
+}
+
+#[derive(Deserialize)]
+pub struct RawCreateRuleRequest {
+    pub match_text: String,
+    pub category_id: i64,
+    #[serde(default)]
+    pub priority: i64,
+}
+
+impl CreateRuleRequest {
+    pub fn new(match_text: String, category_id: i64, priority: i64) -> Result<Self, String> {
+        let match_text = match_text.trim().to_string();
+        if match_text.is_empty() {
+            return Err("Rule match text cannot be empty".to_string());
+        }
+
+        Ok(Self { match_text, category_id, priority })
+    }
+
+    pub fn match_text(&self) -> &str {
+        &self.match_text
+    }
+
+    pub fn category_id(&self) -> i64 {
+        self.category_id
+    }
+
+    pub fn priority(&self) -> i64 {
+        self.priority
+    }
+}
+
+/// Order of a month's transaction list.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TransactionSort {
+    #[default]
+    DateDesc,
+    DateAsc,
+    /// Largest absolute amount first, regardless of income or expense.
+    AmountDesc,
+    AmountAsc,
+}
+
+impl TransactionSort {
+    /// Parses a `sort` query value, falling back to the default for anything unrecognised.
+    pub fn from_param(value: &str) -> Self {
+        match value {
+            "date_asc" => TransactionSort::DateAsc,
+            "amount_desc" => TransactionSort::AmountDesc,
+            "amount_asc" => TransactionSort::AmountAsc,
+            _ => TransactionSort::DateDesc,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionSort::DateDesc => "date_desc",
+            TransactionSort::DateAsc => "date_asc",
+            TransactionSort::AmountDesc => "amount_desc",
+            TransactionSort::AmountAsc => "amount_asc",
+        }
+    }
+
+    /// The `ORDER BY` clause. Only these fixed strings ever reach the SQL.
+    pub(crate) fn order_by(&self) -> &'static str {
+        match self {
+            TransactionSort::DateDesc => "transaction_date DESC, id DESC",
+            TransactionSort::DateAsc => "transaction_date ASC, id ASC",
+            TransactionSort::AmountDesc => "ABS(amount) DESC, transaction_date DESC, id DESC",
+            TransactionSort::AmountAsc => "ABS(amount) ASC, transaction_date DESC, id DESC",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SetClearedRequest {
+    pub cleared: bool,
+}
+
+/// A month's transactions still to be ticked off against the bank statement, with the net
+/// (income minus expenses, excluded transactions included) of the cleared and uncleared ones.
+#[derive(Debug, Serialize)]
+pub struct Reconciliation {
+    pub month: String,
+    #[serde(serialize_with = "common::money::serialize_cents")]
+    pub cleared_total: i64,
+    #[serde(serialize_with = "common::money::serialize_cents")]
+    pub uncleared_total: i64,
+    pub uncleared: Vec<Transaction>,
+}
+
+/// Transactions created or changed after a sync cursor, oldest change first. Deletions aren't
+/// listed: transactions are hard-deleted, so nothing is left to report.
+#[derive(Debug, Serialize)]
+pub struct SyncChanges {
+    pub since: i64,
+    pub cursor: i64, // Pass back as `since` on the next sync
+    pub transactions: Vec<Transaction>,
+}
+
+/// Counted totals for the period (of any `Period` granularity) containing a date.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PeriodSummary {
+    pub key: String, // e.g. "2026-03", "FY2026", "2026-03-06" for a pay cycle
+    pub from: String,
+    pub to: String,
+    #[serde(serialize_with = "common::money::serialize_cents")]
+    pub total_income: i64,
+    #[serde(serialize_with = "common::money::serialize_cents")]
+    pub total_expenses: i64,
+    #[serde(serialize_with = "common::money::serialize_cents")]
+    pub net: i64,
 }
 
 #[derive(Debug, Serialize)]
 pub struct MonthlySummary {
     pub month: String,
+    #[serde(serialize_with = "common::money::serialize_cents")]
     pub total_income: i64,
+    #[serde(serialize_with = "common::money::serialize_cents")]
     pub total_expenses: i64,
+    #[serde(serialize_with = "common::money::serialize_cents")]
     pub net: i64,
 }
 
+/// Upper bounds, in cents, of the histogram buckets; amounts at or above the last go in an open-ended bucket.
+pub const HISTOGRAM_BOUNDS: [i64; 3] = [1_000, 5_000, 10_000];
+
+/// Count of transactions whose absolute amount falls in `[min, max)`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct AmountBucket {
+    pub label: String, // e.g. "$10-50", "$100+"
+    pub min: i64,
+    pub max: Option<i64>, // None for the open-ended top bucket
+    pub count: u32,
+}
+
+/// Distribution of a month's transaction amounts, with income and expenses bucketed separately.
+#[derive(Debug, Serialize)]
+pub struct AmountHistogram {
+    pub month: String,
+    pub income: Vec<AmountBucket>,
+    pub expenses: Vec<AmountBucket>,
+}
+
+/// Income and expenses for one week of a month, in cents. Weeks are clipped to the month, so the
+/// first and last may be shorter than seven days.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct WeekTotal {
+    pub start: String, // YYYY-MM-DD
+    pub end: String,   // YYYY-MM-DD, inclusive
+    pub label: String, // e.g. "Mar 1 - Mar 7"
+    pub income: i64,
+    pub expenses: i64,
+}
+
+/// The month page's data, loaded together by `TransactionService::get_full_month_view`.
+#[derive(Debug)]
+pub struct FullMonthView {
+    pub transactions: Vec<Transaction>,
+    pub summary: MonthlySummary,
+    pub budget_views: Vec<categories::models::CategoryBudgetView>,
+    pub cards: Vec<cards::models::Card>,
+    pub note: Option<categories::models::MonthNote>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_create_transaction_request_expense() {
-        let req = CreateTransactionRequest::new(1, Some(1), "2023-10-27".into(), 45.50, false, None).unwrap();
+        let req = CreateTransactionRequest::new(1, Some(1), "2023-10-27".into(), 45.50, false, None, false).unwrap();
         assert_eq!(req.amount(), -4550);
     }
 
+    #[test]
+    fn test_create_rule_request_trims_and_rejects_empty() {
+        let req = CreateRuleRequest::new("  Starbucks ".into(), 1, 0).unwrap();
+        assert_eq!(req.match_text(), "Starbucks");
+        assert!(CreateRuleRequest::new("   ".into(), 1, 0).is_err());
+    }
+
     #[test]
     fn test_create_transaction_request_income() {
-        let req = CreateTransactionRequest::new(1, Some(1), "2023-10-27".into(), 100.00, true, None).unwrap();
+        let req = CreateTransactionRequest::new(1, Some(1), "2023-10-27".into(), 100.00, true, None, false).unwrap();
         assert_eq!(req.amount(), 10000);
     }
+
+    #[test]
+    fn test_create_transaction_request_requires_padded_date() {
+        assert!(CreateTransactionRequest::new(1, None, "2023-1-5".into(), 1.0, false, None, false).is_err());
+        assert!(CreateTransactionRequest::new(1, None, "2023-01-05 10:00".into(), 1.0, false, None, false).is_err());
+        assert!(CreateTransactionRequest::new(1, None, "2023-01-05".into(), 1.0, false, None, false).is_ok());
+    }
+
+    #[test]
+    fn test_create_transaction_request_rejects_three_decimals() {
+        let err = CreateTransactionRequest::new(1, None, "2023-10-27".into(), 12.345, false, None, false).unwrap_err();
+        assert!(err.contains("two decimal places"));
+
+        let req = CreateTransactionRequest::new(1, None, "2023-10-27".into(), 12.340, false, None, false).unwrap();
+        assert_eq!(req.amount(), -1234);
+    }
 }
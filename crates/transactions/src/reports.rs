@@ -0,0 +1,480 @@
+use crate::handler::TransactionView;
+use crate::models::{Transaction, TransactionSort};
+use crate::repository::TransactionRepository;
+use crate::service::{TransactionError, TransactionService};
+use askama::Template;
+use cards::models::Card;
+use cards::service::{CardError, CardService};
+use categories::service::{CategoryError, CategoryService};
+use chrono::NaiveDate;
+use common::period::Period;
+use database::Database;
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Arc;
+use tracing::instrument;
+
+const TOP_MERCHANT_COUNT: usize = 5;
+
+/// Transactions read per chunk of a streamed category CSV.
+#[cfg(not(test))]
+const CSV_CHUNK_ROWS: i64 = 500;
+#[cfg(test)]
+const CSV_CHUNK_ROWS: i64 = 2; // Small, so tests cross chunk boundaries
+
+/// What a streamed category CSV reads from, owned so the stream can outlive the request handler.
+struct CategoryExport {
+    db: Database,
+    cards: Vec<Card>,
+    category_id: i64,
+    from: String,
+    to: String,
+}
+
+impl CategoryExport {
+    async fn page(&self, after: Option<(&str, i64)>) -> Result<Vec<Transaction>, TransactionError> {
+        let mut conn = self.db.connection().await?;
+        Ok(TransactionRepository::new(&mut conn)
+            .list_category_page(self.category_id, &self.from, &self.to, after, CSV_CHUNK_ROWS)
+            .await?)
+    }
+
+    fn render(&self, transactions: &[Transaction]) -> String {
+        let mut out = String::new();
+        for t in transactions {
+            // Writing to a String cannot fail
+            let _ = writeln!(
+                out,
+                "{},{},{},{}",
+                t.transaction_date,
+                signed_dollars(t.amount),
+                csv_field(&TransactionView::card_label(t.card_id, &self.cards)),
+                csv_field(t.notes.as_deref().unwrap_or_default()),
+            );
+        }
+        out
+    }
+}
+
+#[derive(Debug)]
+pub struct OverBudgetCategory {
+    pub name: String,
+    pub limit: i64, // Cents
+    pub spent: i64, // Cents
+}
+
+impl OverBudgetCategory {
+    pub fn limit_dollars(&self) -> String {
+        dollars(self.limit)
+    }
+
+    pub fn spent_dollars(&self) -> String {
+        dollars(self.spent)
+    }
+
+    pub fn over_dollars(&self) -> String {
+        dollars(self.spent - self.limit)
+    }
+}
+
+#[derive(Debug)]
+pub struct MerchantTotal {
+    pub name: String,
+    pub spent: i64, // Cents
+}
+
+impl MerchantTotal {
+    pub fn spent_dollars(&self) -> String {
+        dollars(self.spent)
+    }
+}
+
+/// Everything a month summary report shows, independent of how it is rendered.
+#[derive(Debug)]
+pub struct MonthReport {
+    pub month: String,
+    pub total_income: i64,
+    pub total_expenses: i64,
+    pub net: i64,
+    pub over_budget: Vec<OverBudgetCategory>,
+    pub top_merchants: Vec<MerchantTotal>,
+}
+
+impl MonthReport {
+    pub fn income_dollars(&self) -> String {
+        dollars(self.total_income)
+    }
+
+    pub fn expenses_dollars(&self) -> String {
+        dollars(self.total_expenses)
+    }
+
+    pub fn net_dollars(&self) -> String {
+        dollars(self.net)
+    }
+}
+
+/// Income and expenses for one period of a `ReportService::period_totals` report, in cents.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PeriodTotal {
+    pub key: String, // e.g. "2026-03", "2026-W11", "FY2026"
+    pub income: i64,
+    pub expenses: i64,
+    pub net: i64,
+}
+
+#[derive(Template)]
+#[template(path = "month_summary.html")]
+struct MonthSummaryHtmlTemplate<'a> {
+    report: &'a MonthReport,
+}
+
+fn dollars(cents: i64) -> String {
+    format!("{:.2}", cents as f64 / 100.0)
+}
+
+fn signed_dollars(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, cents.unsigned_abs() / 100, cents.unsigned_abs() % 100)
+}
+
+/// Quotes a CSV field when it holds a comma, quote or line break, doubling any quotes.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Both dates must be `YYYY-MM-DD`, and `from` no later than `to`.
+fn validate_range(from: &str, to: &str) -> Result<(), TransactionError> {
+    for date in [from, to] {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| TransactionError::InvalidInput(format!("Invalid date {:?}, expected YYYY-MM-DD", date)))?;
+    }
+    if from > to {
+        return Err(TransactionError::InvalidInput("Start date is after end date".to_string()));
+    }
+    Ok(())
+}
+
+pub struct ReportService;
+
+impl ReportService {
+    #[instrument(skip(db))]
+    pub async fn build_month_report(db: &Database, month: &str) -> Result<MonthReport, TransactionError> {
+        let (transactions, summary) = TransactionService::get_month_view(db, month, TransactionSort::default()).await?;
+        let budget_views = CategoryService::get_budget_view(db, month)
+            .await
+            .map_err(CategoryError::recast)?;
+
+        let mut over_budget = Vec::new();
+        for view in &budget_views {
+            // Income categories are never "over budget" in the spending sense
+            let Some(budget) = &view.budget else { continue };
+            // Nor are paydown targets, which are minimums rather than caps
+            if view.category.is_income || budget.limit_amount < 0 {
+                continue;
+            }
+
+            let spent = TransactionService::category_actual(&transactions, view.category.id, false);
+
+            if spent > budget.limit_amount {
+                over_budget.push(OverBudgetCategory {
+                    name: view.category.name.clone(),
+                    limit: budget.limit_amount,
+                    spent,
+                });
+            }
+        }
+
+        // Transactions have no merchant field, so the note is the closest thing to a payee
+        let mut merchants: HashMap<String, i64> = HashMap::new();
+        for t in transactions.iter().filter(|t| t.amount < 0 && !t.excluded) {
+            let name = t.notes.as_deref().map(str::trim).unwrap_or_default();
+            if name.is_empty() {
+                continue;
+            }
+            let spent = merchants.entry(name.to_string()).or_default();
+            *spent = spent.saturating_add(t.amount.saturating_abs());
+        }
+
+        let mut top_merchants: Vec<MerchantTotal> = merchants
+            .into_iter()
+            .map(|(name, spent)| MerchantTotal { name, spent })
+            .collect();
+        top_merchants.sort_by(|a, b| b.spent.cmp(&a.spent).then_with(|| a.name.cmp(&b.name)));
+        top_merchants.truncate(TOP_MERCHANT_COUNT);
+
+        Ok(MonthReport {
+            month: summary.month,
+            total_income: summary.total_income,
+            total_expenses: summary.total_expenses,
+            net: summary.net,
+            over_budget,
+            top_merchants,
+        })
+    }
+
+    /// Income and expenses between two dates (inclusive), grouped by `period`. Only periods with
+    /// counted transactions are listed, in chronological order. Excluded transactions are not counted.
+    #[instrument(skip(db))]
+    pub async fn period_totals(db: &Database, from: &str, to: &str, period: Period) -> Result<Vec<PeriodTotal>, TransactionError> {
+        validate_range(from, to)?;
+
+        let mut conn = db.connection().await?;
+        let transactions = TransactionRepository::new(&mut conn).list_between(from, to).await?;
+
+        let mut totals: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+        for t in transactions.iter().filter(|t| !t.excluded) {
+            let Ok(date) = NaiveDate::parse_from_str(&t.transaction_date, "%Y-%m-%d") else { continue };
+            let (income, expenses) = totals.entry(period.key(date)).or_default();
+            if t.amount > 0 {
+                *income = income.saturating_add(t.amount);
+            } else {
+                *expenses = expenses.saturating_add(t.amount.saturating_abs());
+            }
+        }
+
+        Ok(totals
+            .into_iter()
+            .map(|(key, (income, expenses))| PeriodTotal { key, income, expenses, net: income - expenses })
+            .collect())
+    }
+
+    /// One category's transactions between two dates (inclusive) as CSV with a header row: date,
+    /// signed amount in dollars, card and notes, oldest first. Excluded transactions are listed too,
+    /// since they still happened. The caller checks the category exists.
+    ///
+    /// The range is validated up front; rows are then read `CSV_CHUNK_ROWS` at a time as the stream
+    /// is polled, so a long range is never held in memory at once.
+    #[instrument(skip(db))]
+    pub async fn category_csv(
+        db: &Database,
+        category_id: i64,
+        from: &str,
+        to: &str,
+    ) -> Result<impl Stream<Item = Result<String, TransactionError>> + Send + 'static, TransactionError> {
+        validate_range(from, to)?;
+
+        let mut conn = db.connection().await?;
+        let cards = CardService::list_cards_on(&mut conn)
+            .await
+            .map_err(CardError::recast)?;
+        drop(conn);
+
+        let export = Arc::new(CategoryExport { db: db.clone(), cards, category_id, from: from.to_string(), to: to.to_string() });
+        let header = stream::once(async { Ok("date,amount,card,notes\n".to_string()) });
+        let rows = stream::try_unfold((export, None, false), |(export, after, done): (Arc<CategoryExport>, Option<(String, i64)>, bool)| async move {
+            if done {
+                return Ok(None);
+            }
+            let page = export.page(after.as_ref().map(|(date, id)| (date.as_str(), *id))).await?;
+            let Some(last) = page.last() else {
+                return Ok(None);
+            };
+            let cursor = (last.transaction_date.clone(), last.id);
+            let done = (page.len() as i64) < CSV_CHUNK_ROWS;
+            Ok(Some((export.render(&page), (export, Some(cursor), done))))
+        });
+        Ok(header.chain(rows))
+    }
+
+    /// Plain-text month summary, suitable for piping into a mailer.
+    #[instrument(skip(db))]
+    pub async fn render_month_summary(db: &Database, month: &str) -> Result<String, TransactionError> {
+        let report = Self::build_month_report(db, month).await?;
+        Ok(Self::render_text(&report))
+    }
+
+    #[instrument(skip(db))]
+    pub async fn render_month_summary_html(db: &Database, month: &str) -> Result<String, TransactionError> {
+        let report = Self::build_month_report(db, month).await?;
+        MonthSummaryHtmlTemplate { report: &report }
+            .render()
+            .map_err(|e| TransactionError::Infrastructure(e.to_string()))
+    }
+
+    pub fn render_text(report: &MonthReport) -> String {
+        let mut out = String::new();
+
+        // Writing to a String cannot fail
+        let _ = writeln!(out, "Budget Summary for {}", report.month);
+        let _ = writeln!(out);
+        let _ = writeln!(out, "Income:   ${}", report.income_dollars());
+        let _ = writeln!(out, "Expenses: ${}", report.expenses_dollars());
+        let _ = writeln!(out, "Net:      ${}", report.net_dollars());
+        let _ = writeln!(out);
+
+        let _ = writeln!(out, "Over Budget:");
+        if report.over_budget.is_empty() {
+            let _ = writeln!(out, "  (none)");
+        }
+        for c in &report.over_budget {
+            let _ = writeln!(
+                out,
+                "  {} - spent ${} of ${} (${} over)",
+                c.name,
+                c.spent_dollars(),
+                c.limit_dollars(),
+                c.over_dollars(),
+            );
+        }
+        let _ = writeln!(out);
+
+        let _ = writeln!(out, "Top Merchants:");
+        if report.top_merchants.is_empty() {
+            let _ = writeln!(out, "  (none)");
+        }
+        for m in &report.top_merchants {
+            let _ = writeln!(out, "  {} - ${}", m.name, m.spent_dollars());
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CreateTransactionCommand;
+    use common::period::PayCycle;
+    use common::money::RoundingMode;
+    use database::get_test_db;
+    use futures_util::TryStreamExt;
+
+    #[test]
+    fn test_signed_dollars_survives_i64_min() {
+        assert_eq!(signed_dollars(-4550), "-45.50");
+        assert_eq!(signed_dollars(i64::MIN), "-92233720368547758.08");
+    }
+
+    #[tokio::test]
+    async fn test_render_month_summary() {
+        let db = get_test_db().await;
+
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, dining, "2026-01".to_string(), 50.0, RoundingMode::HalfUp).await.unwrap();
+        let fuel = CategoryService::create_category(None, &db, "Fuel".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, fuel, "2026-01".to_string(), 100.0, RoundingMode::HalfUp).await.unwrap();
+        let pay = CategoryService::create_category(None, &db, "Paycheck".to_string(), true, None).await.unwrap();
+
+        for (category_id, date, amount_dollars, notes) in [
+            (pay, "2026-01-01", 1000.0, None),
+            (dining, "2026-01-05", 75.25, Some("Bistro")),
+            (fuel, "2026-01-06", 40.0, Some("Shell")),
+        ] {
+            TransactionService::create_transaction(None, &db, CreateTransactionCommand {
+                category_id,
+                card_id: None,
+                transaction_date: date.to_string(),
+                amount_dollars,
+                notes: notes.map(str::to_string),
+                excluded: false,
+                new_category: None,
+            }, None).await.unwrap();
+        }
+
+        let text = ReportService::render_month_summary(&db, "2026-01").await.unwrap();
+
+        assert!(text.contains("Net:      $884.75"));
+        assert!(text.contains("Dining - spent $75.25 of $50.00"));
+        assert!(!text.contains("Fuel - spent"));
+        assert!(text.contains("Bistro - $75.25"));
+    }
+
+    #[tokio::test]
+    async fn test_period_totals_and_summaries_by_granularity() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let pay = CategoryService::create_category(None, &db, "Paycheck".to_string(), true, None).await.unwrap();
+
+        for (category_id, date, amount_dollars) in [
+            (pay, "2026-03-01", 1000.0),
+            (dining, "2026-03-31", 20.0),
+            (dining, "2026-04-01", 30.0),
+            (dining, "2026-05-10", 5.0),
+        ] {
+            TransactionService::create_transaction(None, &db, CreateTransactionCommand {
+                category_id,
+                card_id: None,
+                transaction_date: date.to_string(),
+                amount_dollars,
+                notes: None,
+                excluded: false,
+                new_category: None,
+            }, None).await.unwrap();
+        }
+
+        let fiscal = ReportService::period_totals(&db, "2026-01-01", "2026-12-31", Period::fiscal_year(4).unwrap()).await.unwrap();
+        assert_eq!(fiscal, vec![
+            PeriodTotal { key: "FY2025".to_string(), income: 100000, expenses: 2000, net: 98000 },
+            PeriodTotal { key: "FY2026".to_string(), income: 0, expenses: 3500, net: -3500 },
+        ]);
+
+        let months = ReportService::period_totals(&db, "2026-03-15", "2026-04-30", Period::Month).await.unwrap();
+        let keys: Vec<&str> = months.iter().map(|p| p.key.as_str()).collect();
+        assert_eq!(keys, vec!["2026-03", "2026-04"]);
+        assert_eq!(months[0].expenses, 2000);
+
+        // Biweekly paydays on Fridays, one of them 2026-03-20
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let pay_cycle = Period::PayCycle(PayCycle { anchor: date("2026-03-20"), days: 14 });
+        let cycles = ReportService::period_totals(&db, "2026-01-01", "2026-12-31", pay_cycle).await.unwrap();
+        let cycles: Vec<(&str, i64, i64)> = cycles.iter().map(|p| (p.key.as_str(), p.income, p.expenses)).collect();
+        assert_eq!(cycles, vec![("2026-02-20", 100000, 0), ("2026-03-20", 0, 5000), ("2026-05-01", 0, 500)]);
+
+        let summary = TransactionService::get_period_summary(&db, pay_cycle, date("2026-04-01")).await.unwrap();
+        assert_eq!((summary.key.as_str(), summary.from.as_str(), summary.to.as_str()), ("2026-03-20", "2026-03-20", "2026-04-02"));
+        assert_eq!((summary.total_income, summary.total_expenses, summary.net), (0, 5000, -5000));
+        let summary = TransactionService::get_period_summary(&db, Period::fiscal_year(4).unwrap(), date("2026-03-01")).await.unwrap();
+        assert_eq!((summary.key.as_str(), summary.from.as_str(), summary.to.as_str()), ("FY2025", "2025-04-01", "2026-03-31"));
+        assert_eq!(summary.net, 98000);
+    }
+
+    #[tokio::test]
+    async fn test_category_csv_filters_category_and_range() {
+        let db = get_test_db().await;
+        let donations = CategoryService::create_category(None, &db, "Donations".to_string(), false, None).await.unwrap();
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+
+        for (category_id, date, amount_dollars, notes) in [
+            (donations, "2025-12-31", 10.0, Some("Too early")),
+            (donations, "2026-01-15", 25.0, Some("Food bank, January")),
+            (dining, "2026-01-16", 40.0, Some("Bistro")),
+            (donations, "2026-03-01", 5.5, Some("Say \"thanks\"")),
+            (donations, "2026-03-01", 1.0, Some("Same day")),
+            (donations, "2026-04-01", 99.0, Some("Too late")),
+        ] {
+            TransactionService::create_transaction(None, &db, CreateTransactionCommand {
+                category_id,
+                card_id: None,
+                transaction_date: date.to_string(),
+                amount_dollars,
+                notes: notes.map(str::to_string),
+                excluded: false,
+                new_category: None,
+            }, None).await.unwrap();
+        }
+
+        let chunks: Vec<String> = ReportService::category_csv(&db, donations, "2026-01-01", "2026-03-31").await.unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        // Header, then two chunks: the last two rows share a date, so the cursor breaks the tie by id
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(
+            chunks.concat(),
+            "date,amount,card,notes\n\
+             2026-01-15,-25.00,Cash,\"Food bank, January\"\n\
+             2026-03-01,-5.50,Cash,\"Say \"\"thanks\"\"\"\n\
+             2026-03-01,-1.00,Cash,Same day\n"
+        );
+
+        assert!(ReportService::category_csv(&db, donations, "2026-03-31", "2026-01-01").await.is_err());
+    }
+}
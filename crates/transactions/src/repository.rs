@@ -1,4 +1,4 @@
-use crate::models::{CreateTransactionRequest, Transaction};
+use crate::models::{CreateTransactionRequest, OrphanedTransaction, Transaction, TransactionSort};
 use database::{self, RepositoryError};
 use sqlx::FromRow;
 
@@ -10,6 +10,8 @@ struct TransactionRecord {
     transaction_date: String,
     amount: i64,
     notes: Option<String>,
+    excluded: bool,
+    cleared: bool,
 }
 
 impl From<TransactionRecord> for Transaction {
@@ -21,10 +23,24 @@ impl From<TransactionRecord> for Transaction {
             transaction_date: record.transaction_date,
             amount: record.amount,
             notes: record.notes,
+            excluded: record.excluded,
+            cleared: record.cleared,
         }
     }
 }
 
+/// Matches `transaction_date` in the `YYYY-MM` month bound as `$1`. A range on the bare column rather
+/// than `strftime('%Y-%m', transaction_date) = $1`, so `idx_transactions_date` can be used.
+const IN_MONTH: &str = "transaction_date >= $1 || '-01' AND transaction_date < date($1 || '-01', '+1 month')";
+
+/// Most ids bound into one `IN (...)` list, comfortably under SQLite's bound-parameter limit
+/// (999 in older builds) with room for the other parameters.
+const MAX_IN_PARAMS: usize = 500;
+
+/// Joins `t` to its category and keeps rows signed against it.
+const MIS_SIGNED: &str = "JOIN categories c ON c.id = t.category_id \
+     WHERE (c.is_income = 1 AND t.amount < 0) OR (c.is_income = 0 AND t.amount > 0)";
+
 pub(crate) struct TransactionRepository<'a> {
     conn: &'a mut database::Connection,
 }
@@ -36,13 +52,14 @@ impl<'a> TransactionRepository<'a> {
 
     pub async fn create(&mut self, req: &CreateTransactionRequest) -> Result<i64, RepositoryError> {
         let id: i64 = sqlx::query_scalar(
-            "INSERT INTO transactions (category_id, card_id, transaction_date, amount, notes) VALUES ($1, $2, $3, $4, $5) RETURNING id",
+            "INSERT INTO transactions (category_id, card_id, transaction_date, amount, notes, excluded) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
         )
         .bind(req.category_id())
         .bind(req.card_id())
         .bind(req.transaction_date())
         .bind(req.amount())
         .bind(req.notes())
+        .bind(req.excluded())
         .fetch_one(&mut *self.conn)
         .await?;
         
@@ -51,13 +68,14 @@ impl<'a> TransactionRepository<'a> {
 
     pub async fn update(&mut self, id: i64, req: &CreateTransactionRequest) -> Result<(), RepositoryError> {
         let result = sqlx::query(
-            "UPDATE transactions SET category_id = $1, card_id = $2, transaction_date = $3, amount = $4, notes = $5 WHERE id = $6",
+            "UPDATE transactions SET category_id = $1, card_id = $2, transaction_date = $3, amount = $4, notes = $5, excluded = $6 WHERE id = $7",
         )
         .bind(req.category_id())
         .bind(req.card_id())
         .bind(req.transaction_date())
         .bind(req.amount())
         .bind(req.notes())
+        .bind(req.excluded())
         .bind(id)
         .execute(&mut *self.conn)
         .await?;
@@ -70,7 +88,7 @@ impl<'a> TransactionRepository<'a> {
 
     pub async fn find_by_id(&mut self, id: i64) -> Result<Option<Transaction>, RepositoryError> {
         let record = sqlx::query_as::<_, TransactionRecord>(
-            "SELECT id, category_id, card_id, transaction_date, amount, notes FROM transactions WHERE id = $1",
+            "SELECT id, category_id, card_id, transaction_date, amount, notes, excluded, cleared FROM transactions WHERE id = $1",
         )
         .bind(id)
         .fetch_optional(&mut *self.conn)
@@ -79,11 +97,262 @@ impl<'a> TransactionRepository<'a> {
         Ok(record.map(|r| r.into()))
     }
 
-    pub async fn list_by_month(&mut self, month: &str) -> Result<Vec<Transaction>, RepositoryError> {
+    pub async fn list_by_month(&mut self, month: &str, sort: TransactionSort) -> Result<Vec<Transaction>, RepositoryError> {
+        let sql = format!(
+            "SELECT id, category_id, card_id, transaction_date, amount, notes, excluded, cleared FROM transactions WHERE {} ORDER BY {}",
+            IN_MONTH,
+            sort.order_by(),
+        );
+        let records = sqlx::query_as::<_, TransactionRecord>(&sql)
+        .bind(month)
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(records.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Transactions dated from `from` to `to` inclusive (both `YYYY-MM-DD`), oldest first.
+    pub async fn list_between(&mut self, from: &str, to: &str) -> Result<Vec<Transaction>, RepositoryError> {
+        let records = sqlx::query_as::<_, TransactionRecord>(
+            "SELECT id, category_id, card_id, transaction_date, amount, notes, excluded, cleared FROM transactions \
+             WHERE transaction_date >= $1 AND transaction_date <= $2 ORDER BY transaction_date ASC, id ASC",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(records.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Up to `limit` of one category's transactions dated `from..=to`, oldest first, starting after the
+    /// `(transaction_date, id)` cursor of the previous page (or from the start without one).
+    pub async fn list_category_page(
+        &mut self,
+        category_id: i64,
+        from: &str,
+        to: &str,
+        after: Option<(&str, i64)>,
+        limit: i64,
+    ) -> Result<Vec<Transaction>, RepositoryError> {
+        let (after_date, after_id) = after.unwrap_or(("", 0));
         let records = sqlx::query_as::<_, TransactionRecord>(
-            "SELECT id, category_id, card_id, transaction_date, amount, notes FROM transactions WHERE strftime('%Y-%m', transaction_date) = $1 ORDER BY transaction_date DESC",
+            "SELECT id, category_id, card_id, transaction_date, amount, notes, excluded, cleared FROM transactions \
+             WHERE category_id = $1 AND transaction_date >= $2 AND transaction_date <= $3 AND (transaction_date, id) > ($4, $5) \
+             ORDER BY transaction_date ASC, id ASC LIMIT $6",
+        )
+        .bind(category_id)
+        .bind(from)
+        .bind(to)
+        .bind(after_date)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(records.into_iter().map(|r| r.into()).collect())
+    }
+
+    pub async fn set_cleared(&mut self, id: i64, cleared: bool) -> Result<(), RepositoryError> {
+        let result = sqlx::query("UPDATE transactions SET cleared = $1 WHERE id = $2")
+            .bind(cleared)
+            .bind(id)
+            .execute(&mut *self.conn)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
+    /// The month's transactions not yet cleared, oldest first.
+    pub async fn list_uncleared(&mut self, month: &str) -> Result<Vec<Transaction>, RepositoryError> {
+        let sql = format!(
+            "SELECT id, category_id, card_id, transaction_date, amount, notes, excluded, cleared FROM transactions \
+             WHERE cleared = 0 AND {} ORDER BY transaction_date ASC, id ASC",
+            IN_MONTH,
+        );
+        let records = sqlx::query_as::<_, TransactionRecord>(&sql)
+            .bind(month)
+            .fetch_all(&mut *self.conn)
+            .await?;
+
+        Ok(records.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// The month's net amount in cents of cleared and of uncleared transactions. Excluded
+    /// transactions count here, since they still appear on the statement.
+    pub async fn cleared_totals(&mut self, month: &str) -> Result<(i64, i64), RepositoryError> {
+        let totals: (i64, i64) = sqlx::query_as(&format!(
+            "SELECT COALESCE(SUM(CASE WHEN cleared THEN amount ELSE 0 END), 0), \
+                    COALESCE(SUM(CASE WHEN cleared THEN 0 ELSE amount END), 0) \
+             FROM transactions WHERE {}",
+            IN_MONTH,
+        ))
+        .bind(month)
+        .fetch_one(&mut *self.conn)
+        .await?;
+
+        Ok(totals)
+    }
+
+    /// Counted income and expenses in cents, both positive, for transactions dated `from..=to`.
+    /// Excluded transactions are left out.
+    pub async fn totals_between(&mut self, from: &str, to: &str) -> Result<(i64, i64), RepositoryError> {
+        let totals: (i64, i64) = sqlx::query_as(
+            "SELECT COALESCE(SUM(CASE WHEN amount > 0 THEN amount ELSE 0 END), 0), \
+                    COALESCE(SUM(CASE WHEN amount < 0 THEN -amount ELSE 0 END), 0) \
+             FROM transactions WHERE excluded = 0 AND transaction_date >= $1 AND transaction_date <= $2",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_one(&mut *self.conn)
+        .await?;
+
+        Ok(totals)
+    }
+
+    /// Counted net totals in cents for the month, per category in `category_ids` that has any,
+    /// by category id. Excluded transactions are left out. Long id lists are queried in chunks.
+    pub async fn totals_for_categories(&mut self, month: &str, category_ids: &[i64]) -> Result<Vec<(i64, i64)>, RepositoryError> {
+        let mut ids = category_ids.to_vec();
+        ids.sort_unstable();
+        ids.dedup();
+
+        let mut totals = Vec::new();
+        for chunk in ids.chunks(MAX_IN_PARAMS) {
+            // $1 is the month; the ids follow
+            let placeholders: Vec<String> = (2..chunk.len() + 2).map(|i| format!("${}", i)).collect();
+            let sql = format!(
+                "SELECT category_id, SUM(amount) FROM transactions \
+                 WHERE excluded = 0 AND {} AND category_id IN ({}) GROUP BY category_id",
+                IN_MONTH,
+                placeholders.join(", "),
+            );
+
+            let mut query = sqlx::query_as::<_, (i64, i64)>(&sql).bind(month);
+            for id in chunk {
+                query = query.bind(id);
+            }
+            totals.extend(query.fetch_all(&mut *self.conn).await?);
+        }
+
+        totals.sort_unstable_by_key(|&(category_id, _)| category_id);
+        Ok(totals)
+    }
+
+    pub async fn count_for_month(&mut self, month: &str) -> Result<i64, RepositoryError> {
+        let count: i64 = sqlx::query_scalar(
+            &format!("SELECT COUNT(*) FROM transactions WHERE {}", IN_MONTH),
         )
         .bind(month)
+        .fetch_one(&mut *self.conn)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// A category's counted history: number of transactions, their average absolute amount in
+    /// cents, and how many fell on a Saturday or Sunday. Excluded transactions are left out.
+    pub async fn category_history(&mut self, category_id: i64) -> Result<(i64, f64, i64), RepositoryError> {
+        let history: (i64, f64, i64) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*),
+                   COALESCE(AVG(ABS(amount)), 0.0),
+                   COALESCE(SUM(strftime('%w', transaction_date) IN ('0', '6')), 0)
+            FROM transactions
+            WHERE category_id = $1 AND excluded = 0
+            "#,
+        )
+        .bind(category_id)
+        .fetch_one(&mut *self.conn)
+        .await?;
+
+        Ok(history)
+    }
+
+    /// Transactions pointing at a card or category that no longer exists. Foreign keys normally
+    /// prevent this, but databases written with enforcement off can still contain them.
+    pub async fn list_orphans(&mut self) -> Result<Vec<OrphanedTransaction>, RepositoryError> {
+        #[derive(FromRow)]
+        struct OrphanRecord {
+            #[sqlx(flatten)]
+            transaction: TransactionRecord,
+            missing_card: bool,
+            missing_category: bool,
+        }
+
+        let records = sqlx::query_as::<_, OrphanRecord>(
+            r#"
+            SELECT t.id, t.category_id, t.card_id, t.transaction_date, t.amount, t.notes, t.excluded, t.cleared,
+                   (t.card_id IS NOT NULL AND c.id IS NULL) AS missing_card,
+                   (cat.id IS NULL) AS missing_category
+            FROM transactions t
+            LEFT JOIN cards c ON c.id = t.card_id
+            LEFT JOIN categories cat ON cat.id = t.category_id
+            WHERE (t.card_id IS NOT NULL AND c.id IS NULL) OR cat.id IS NULL
+            ORDER BY t.transaction_date, t.id
+            "#,
+        )
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(records.into_iter().map(|r| OrphanedTransaction {
+            transaction: r.transaction.into(),
+            missing_card: r.missing_card,
+            missing_category: r.missing_category,
+        }).collect())
+    }
+
+    /// Ids of transactions whose sign disagrees with their category: negative income or positive
+    /// expenses. Zero amounts and orphans (see `list_orphans`) are not flagged.
+    pub async fn mis_signed_ids(&mut self) -> Result<Vec<i64>, RepositoryError> {
+        let ids = sqlx::query_scalar(&format!("SELECT t.id FROM transactions t {} ORDER BY t.id", MIS_SIGNED))
+            .fetch_all(&mut *self.conn)
+            .await?;
+
+        Ok(ids)
+    }
+
+    /// The months (YYYY-MM) holding a transaction `mis_signed_ids` would return.
+    pub async fn mis_signed_months(&mut self) -> Result<Vec<String>, RepositoryError> {
+        let months = sqlx::query_scalar(&format!(
+            "SELECT DISTINCT substr(t.transaction_date, 1, 7) FROM transactions t {} ORDER BY 1",
+            MIS_SIGNED,
+        ))
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(months)
+    }
+
+    /// Flips the sign of every transaction `mis_signed_ids` would return.
+    pub async fn fix_signs(&mut self) -> Result<u64, RepositoryError> {
+        let result = sqlx::query(&format!(
+            "UPDATE transactions SET amount = -amount WHERE id IN (SELECT t.id FROM transactions t {})",
+            MIS_SIGNED,
+        ))
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// The number of the latest committed change to any transaction, as this connection sees it.
+    pub async fn sync_cursor(&mut self) -> Result<i64, RepositoryError> {
+        Ok(sqlx::query_scalar("SELECT value FROM sync_counter WHERE id = 1")
+            .fetch_one(&mut *self.conn)
+            .await?)
+    }
+
+    /// Transactions whose latest change is numbered above `since`, oldest change first.
+    pub async fn changed_since(&mut self, since: i64) -> Result<Vec<Transaction>, RepositoryError> {
+        let records = sqlx::query_as::<_, TransactionRecord>(
+            "SELECT id, category_id, card_id, transaction_date, amount, notes, excluded, cleared FROM transactions \
+             WHERE sync_seq > $1 ORDER BY sync_seq ASC",
+        )
+        .bind(since)
         .fetch_all(&mut *self.conn)
         .await?;
 
@@ -139,7 +408,7 @@ mod tests {
         let (cat_id, card_id) = setup_deps(uow.connection()).await;
 
         let mut repo = TransactionRepository::new(uow.connection());
-        let req = CreateTransactionRequest::new(cat_id, Some(card_id), "2026-01-01".to_string(), 10.0, false, Some("Notes".into())).unwrap();
+        let req = CreateTransactionRequest::new(cat_id, Some(card_id), "2026-01-01".to_string(), 10.0, false, Some("Notes".into()), false).unwrap();
         
         let id = repo.create(&req).await.unwrap();
         assert!(id > 0);
@@ -156,13 +425,56 @@ mod tests {
         let (cat_id, card_id) = setup_deps(uow.connection()).await;
 
         let mut repo = TransactionRepository::new(uow.connection());
-        let req = CreateTransactionRequest::new(cat_id, Some(card_id), "2026-01-01".to_string(), 10.0, false, None).unwrap();
+        let req = CreateTransactionRequest::new(cat_id, Some(card_id), "2026-01-01".to_string(), 10.0, false, None, false).unwrap();
         repo.create(&req).await.unwrap();
 
-        let list = repo.list_by_month("2026-01").await.unwrap();
+        let list = repo.list_by_month("2026-01", TransactionSort::default()).await.unwrap();
         assert_eq!(list.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_count_for_month() {
+        let db = get_test_db().await;
+        let mut uow = db.begin().await.unwrap();
+        let (cat_id, card_id) = setup_deps(uow.connection()).await;
+
+        let mut repo = TransactionRepository::new(uow.connection());
+        for date in ["2026-01-01", "2026-01-31", "2026-02-01"] {
+            let req = CreateTransactionRequest::new(cat_id, Some(card_id), date.to_string(), 10.0, false, None, false).unwrap();
+            repo.create(&req).await.unwrap();
+        }
+
+        assert_eq!(repo.count_for_month("2026-01").await.unwrap(), 2);
+        assert_eq!(repo.count_for_month("2026-02").await.unwrap(), 1);
+        assert_eq!(repo.count_for_month("2026-03").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_month_range_matches_strftime() {
+        let db = get_test_db().await;
+        let mut uow = db.begin().await.unwrap();
+        let (cat_id, card_id) = setup_deps(uow.connection()).await;
+
+        let mut repo = TransactionRepository::new(uow.connection());
+        for date in ["2025-12-31", "2026-01-01", "2026-01-15", "2026-01-31", "2026-02-01", "2026-02-28", "2026-03-01", "2026-12-31", "2027-01-01"] {
+            let req = CreateTransactionRequest::new(cat_id, Some(card_id), date.to_string(), 10.0, false, None, false).unwrap();
+            repo.create(&req).await.unwrap();
+        }
+
+        for month in ["2025-12", "2026-01", "2026-02", "2026-12", "2027-01"] {
+            let mut range: Vec<i64> = repo.list_by_month(month, TransactionSort::default()).await.unwrap().iter().map(|t| t.id).collect();
+            let mut expected: Vec<i64> = sqlx::query_scalar("SELECT id FROM transactions WHERE strftime('%Y-%m', transaction_date) = $1")
+                .bind(month)
+                .fetch_all(&mut *repo.conn)
+                .await
+                .unwrap();
+            range.sort();
+            expected.sort();
+            assert!(!expected.is_empty());
+            assert_eq!(range, expected, "month {}", month);
+        }
+    }
+
     #[tokio::test]
     async fn test_update_transaction() {
         let db = get_test_db().await;
@@ -170,9 +482,9 @@ mod tests {
         let (cat_id, card_id) = setup_deps(uow.connection()).await;
 
         let mut repo = TransactionRepository::new(uow.connection());
-        let id = repo.create(&CreateTransactionRequest::new(cat_id, Some(card_id), "2026-01-01".to_string(), 10.0, false, None).unwrap()).await.unwrap();
+        let id = repo.create(&CreateTransactionRequest::new(cat_id, Some(card_id), "2026-01-01".to_string(), 10.0, false, None, false).unwrap()).await.unwrap();
 
-        let update_req = CreateTransactionRequest::new(cat_id, Some(card_id), "2026-01-02".to_string(), 20.0, true, Some("Updated".into())).unwrap();
+        let update_req = CreateTransactionRequest::new(cat_id, Some(card_id), "2026-01-02".to_string(), 20.0, true, Some("Updated".into()), false).unwrap();
         repo.update(id, &update_req).await.unwrap();
 
         let t = repo.find_by_id(id).await.unwrap().unwrap();
@@ -188,10 +500,61 @@ mod tests {
         let (cat_id, card_id) = setup_deps(uow.connection()).await;
 
         let mut repo = TransactionRepository::new(uow.connection());
-        let id = repo.create(&CreateTransactionRequest::new(cat_id, Some(card_id), "2026-01-01".to_string(), 10.0, false, None).unwrap()).await.unwrap();
+        let id = repo.create(&CreateTransactionRequest::new(cat_id, Some(card_id), "2026-01-01".to_string(), 10.0, false, None, false).unwrap()).await.unwrap();
 
         assert!(repo.find_by_id(id).await.unwrap().is_some());
         repo.delete(id).await.unwrap();
         assert!(repo.find_by_id(id).await.unwrap().is_none());
     }
+
+    #[tokio::test]
+    async fn test_list_orphans_finds_deleted_card() {
+        let db = get_test_db().await;
+        let mut uow = db.begin().await.unwrap();
+        let (cat_id, card_id) = setup_deps(uow.connection()).await;
+
+        let mut repo = TransactionRepository::new(uow.connection());
+        let req = CreateTransactionRequest::new(cat_id, Some(card_id), "2026-01-01".to_string(), 10.0, false, None, false).unwrap();
+        let id = repo.create(&req).await.unwrap();
+        let cash = CreateTransactionRequest::new(cat_id, None, "2026-01-02".to_string(), 5.0, false, None, false).unwrap();
+        repo.create(&cash).await.unwrap();
+        assert!(repo.list_orphans().await.unwrap().is_empty());
+
+        uow.commit().await.unwrap();
+
+        // Simulate a database written without foreign key enforcement (the pragma is ignored inside a transaction)
+        sqlx::query("PRAGMA foreign_keys = OFF").execute(&db.pool).await.unwrap();
+        sqlx::query("DELETE FROM cards WHERE id = $1").bind(card_id).execute(&db.pool).await.unwrap();
+
+        let mut uow = db.begin().await.unwrap();
+        let mut repo = TransactionRepository::new(uow.connection());
+        let orphans = repo.list_orphans().await.unwrap();
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].transaction.id, id);
+        assert!(orphans[0].missing_card);
+        assert!(!orphans[0].missing_category);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_date_is_rejected_by_check_constraint() {
+        let db = get_test_db().await;
+        let mut uow = db.begin().await.unwrap();
+        let (cat_id, _) = setup_deps(uow.connection()).await;
+
+        for date in ["2026-01-05 10:30:00", "2026-1-5", "2026-02-30", "05/01/2026"] {
+            let err = sqlx::query("INSERT INTO transactions (category_id, transaction_date, amount) VALUES ($1, $2, -100)")
+                .bind(cat_id)
+                .bind(date)
+                .execute(&mut *uow.connection())
+                .await
+                .unwrap_err();
+            assert!(matches!(RepositoryError::from(err), RepositoryError::CheckViolation(_)), "{} should be rejected", date);
+        }
+
+        sqlx::query("INSERT INTO transactions (category_id, transaction_date, amount) VALUES ($1, '2024-02-29', -100)")
+            .bind(cat_id)
+            .execute(&mut *uow.connection())
+            .await
+            .unwrap();
+    }
 }
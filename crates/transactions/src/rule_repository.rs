@@ -0,0 +1,178 @@
+use crate::models::{CategorizationRule, CreateRuleRequest};
+use database::{self, RepositoryError};
+use sqlx::FromRow;
+
+#[derive(FromRow)]
+struct RuleRecord {
+    id: i64,
+    match_text: String,
+    category_id: i64,
+    priority: i64,
+}
+
+impl From<RuleRecord> for CategorizationRule {
+    fn from(record: RuleRecord) -> Self {
+        CategorizationRule {
+            id: record.id,
+            match_text: record.match_text,
+            category_id: record.category_id,
+            priority: record.priority,
+        }
+    }
+}
+
+/// A rule joined with whether its target category is income, so the sign can be fixed on apply.
+#[derive(FromRow)]
+pub(crate) struct RuleTarget {
+    pub match_text: String,
+    pub category_id: i64,
+    pub is_income: bool,
+}
+
+#[derive(FromRow)]
+pub(crate) struct PendingTransaction {
+    pub id: i64,
+    pub amount: i64,
+    pub notes: Option<String>,
+}
+
+pub(crate) struct RuleRepository<'a> {
+    conn: &'a mut database::Connection,
+}
+
+impl<'a> RuleRepository<'a> {
+    pub fn new(conn: &'a mut database::Connection) -> Self {
+        Self { conn }
+    }
+
+    pub async fn create(&mut self, req: &CreateRuleRequest) -> Result<i64, RepositoryError> {
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO categorization_rules (match_text, category_id, priority) VALUES ($1, $2, $3) RETURNING id",
+        )
+        .bind(req.match_text())
+        .bind(req.category_id())
+        .bind(req.priority())
+        .fetch_one(&mut *self.conn)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn list(&mut self) -> Result<Vec<CategorizationRule>, RepositoryError> {
+        let records = sqlx::query_as::<_, RuleRecord>(
+            "SELECT id, match_text, category_id, priority FROM categorization_rules ORDER BY priority, id",
+        )
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(records.into_iter().map(|r| r.into()).collect())
+    }
+
+    pub async fn list_targets(&mut self) -> Result<Vec<RuleTarget>, RepositoryError> {
+        let targets = sqlx::query_as::<_, RuleTarget>(
+            r#"
+            SELECT r.match_text, r.category_id, c.is_income
+            FROM categorization_rules r
+            JOIN categories c ON c.id = r.category_id
+            ORDER BY r.priority, r.id
+            "#,
+        )
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(targets)
+    }
+
+    pub async fn delete(&mut self, id: i64) -> Result<(), RepositoryError> {
+        let result = sqlx::query("DELETE FROM categorization_rules WHERE id = $1")
+            .bind(id)
+            .execute(&mut *self.conn)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+
+    /// Transactions dated in `month` that sit in the category named `category_name`.
+    pub async fn list_in_category_by_name(
+        &mut self,
+        month: &str,
+        category_name: &str,
+    ) -> Result<Vec<PendingTransaction>, RepositoryError> {
+        let records = sqlx::query_as::<_, PendingTransaction>(
+            r#"
+            SELECT t.id, t.amount, t.notes
+            FROM transactions t
+            JOIN categories c ON c.id = t.category_id
+            WHERE c.name = $1 COLLATE NOCASE AND strftime('%Y-%m', t.transaction_date) = $2
+            ORDER BY t.id
+            "#,
+        )
+        .bind(category_name)
+        .bind(month)
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(records)
+    }
+
+    pub async fn recategorize(&mut self, transaction_id: i64, category_id: i64, amount: i64) -> Result<(), RepositoryError> {
+        let result = sqlx::query("UPDATE transactions SET category_id = $1, amount = $2 WHERE id = $3")
+            .bind(category_id)
+            .bind(amount)
+            .bind(transaction_id)
+            .execute(&mut *self.conn)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::get_test_db;
+
+    async fn create_category(conn: &mut database::Connection, name: &str) -> i64 {
+        sqlx::query_scalar("INSERT INTO categories (name, color, is_income, is_active) VALUES ($1, '#000', 0, 1) RETURNING id")
+            .bind(name)
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_and_list_rules_by_priority() {
+        let db = get_test_db().await;
+        let mut uow = db.begin().await.unwrap();
+        let cat_id = create_category(uow.connection(), "Dining").await;
+
+        let mut repo = RuleRepository::new(uow.connection());
+        repo.create(&CreateRuleRequest::new("Later".into(), cat_id, 10).unwrap()).await.unwrap();
+        repo.create(&CreateRuleRequest::new("First".into(), cat_id, 1).unwrap()).await.unwrap();
+
+        let rules = repo.list().await.unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].match_text, "First");
+        assert_eq!(rules[1].match_text, "Later");
+    }
+
+    #[tokio::test]
+    async fn test_delete_rule() {
+        let db = get_test_db().await;
+        let mut uow = db.begin().await.unwrap();
+        let cat_id = create_category(uow.connection(), "Dining").await;
+
+        let mut repo = RuleRepository::new(uow.connection());
+        let id = repo.create(&CreateRuleRequest::new("Cafe".into(), cat_id, 0).unwrap()).await.unwrap();
+
+        repo.delete(id).await.unwrap();
+        assert!(repo.list().await.unwrap().is_empty());
+        assert!(matches!(repo.delete(id).await, Err(RepositoryError::NotFound)));
+    }
+}
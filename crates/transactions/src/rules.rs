@@ -0,0 +1,150 @@
+use crate::models::{CategorizationRule, CreateRuleRequest};
+use crate::rule_repository::RuleRepository;
+use crate::service::TransactionError;
+use categories::models::UNCATEGORIZED_CATEGORY_NAME;
+use common::auth::UserContext;
+use database::Database;
+use database::events::ChangeKind;
+use tracing::instrument;
+
+pub struct RuleService;
+
+impl RuleService {
+    #[instrument(skip(db))]
+    pub async fn create_rule(
+        ctx: Option<&UserContext>,
+        db: &Database,
+        match_text: String,
+        category_id: i64,
+        priority: i64,
+    ) -> Result<i64, TransactionError> {
+        UserContext::authorize(ctx).map_err(TransactionError::Forbidden)?;
+
+        let req = CreateRuleRequest::new(match_text, category_id, priority)
+            .map_err(TransactionError::InvalidInput)?;
+
+        // The target category must exist
+        categories::service::CategoryService::get_category(db, category_id)
+            .await
+            .map_err(|_| TransactionError::InvalidInput("Invalid category ID".into()))?;
+
+        db.transaction(async |conn| Ok(RuleRepository::new(conn).create(&req).await?)).await
+    }
+
+    #[instrument(skip(db))]
+    pub async fn list_rules(db: &Database) -> Result<Vec<CategorizationRule>, TransactionError> {
+        let mut conn = db.connection().await?;
+        let mut repo = RuleRepository::new(&mut conn);
+
+        let rules = repo.list().await?;
+        Ok(rules)
+    }
+
+    #[instrument(skip(db))]
+    pub async fn delete_rule(ctx: Option<&UserContext>, db: &Database, id: i64) -> Result<(), TransactionError> {
+        UserContext::authorize(ctx).map_err(TransactionError::Forbidden)?;
+
+        db.transaction(async |conn| Ok(RuleRepository::new(conn).delete(id).await?)).await
+    }
+
+    /// Moves the month's uncategorized transactions into the category of the first rule
+    /// (by priority) whose match text appears in the notes, case-insensitively.
+    /// Returns the number of transactions recategorized.
+    #[instrument(skip(db))]
+    pub async fn apply_rules(ctx: Option<&UserContext>, db: &Database, month: &str) -> Result<u64, TransactionError> {
+        UserContext::authorize(ctx).map_err(TransactionError::Forbidden)?;
+
+        let updated = db.transaction(async |conn| -> Result<u64, TransactionError> {
+            let mut repo = RuleRepository::new(conn);
+
+            let rules = repo.list_targets().await?;
+            let pending = repo.list_in_category_by_name(month, UNCATEGORIZED_CATEGORY_NAME).await?;
+
+            let mut updated = 0;
+            for t in pending {
+                let Some(notes) = t.notes.as_deref().map(str::to_lowercase) else { continue };
+                let Some(rule) = rules.iter().find(|r| notes.contains(&r.match_text.to_lowercase())) else { continue };
+
+                // Re-derive the sign from the new category, as CreateTransactionRequest does
+                let amount = if rule.is_income { t.amount.abs() } else { -t.amount.abs() };
+                repo.recategorize(t.id, rule.category_id, amount).await?;
+                updated += 1;
+            }
+            Ok(updated)
+        }).await?;
+
+        if updated > 0 {
+            db.events().publish(month, ChangeKind::Updated, None);
+        }
+        tracing::info!("Applied categorization rules to {} transactions in {}", updated, month);
+        Ok(updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CreateTransactionCommand;
+    use crate::service::TransactionService;
+    use categories::service::CategoryService;
+    use database::get_test_db;
+
+    async fn uncategorized_id(db: &Database) -> i64 {
+        CategoryService::list_categories(db)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|c| c.name == UNCATEGORIZED_CATEGORY_NAME)
+            .unwrap()
+            .id
+    }
+
+    async fn add(db: &Database, category_id: i64, date: &str, notes: &str) -> i64 {
+        TransactionService::create_transaction(None, db, CreateTransactionCommand {
+            category_id,
+            card_id: None,
+            transaction_date: date.to_string(),
+            amount_dollars: 5.0,
+            notes: Some(notes.to_string()),
+            excluded: false,
+            new_category: None,
+        }, None).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_apply_rules_moves_matching_transactions() {
+        let db = get_test_db().await;
+        let uncategorized = uncategorized_id(&db).await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        RuleService::create_rule(None, &db, "Starbucks".to_string(), dining, 0).await.unwrap();
+
+        let coffee = add(&db, uncategorized, "2026-01-03", "STARBUCKS #1234").await;
+        let latte = add(&db, uncategorized, "2026-01-09", "starbucks latte").await;
+        let fuel = add(&db, uncategorized, "2026-01-10", "Shell").await;
+        let other_month = add(&db, uncategorized, "2026-02-01", "Starbucks").await;
+
+        let updated = RuleService::apply_rules(None, &db, "2026-01").await.unwrap();
+        assert_eq!(updated, 2);
+
+        for (id, expected) in [(coffee, dining), (latte, dining), (fuel, uncategorized), (other_month, uncategorized)] {
+            let t = TransactionService::get_transaction(&db, id).await.unwrap();
+            assert_eq!(t.category_id, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apply_rules_first_priority_wins() {
+        let db = get_test_db().await;
+        let uncategorized = uncategorized_id(&db).await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let coffee = CategoryService::create_category(None, &db, "Coffee".to_string(), false, None).await.unwrap();
+        RuleService::create_rule(None, &db, "Starbucks".to_string(), dining, 5).await.unwrap();
+        RuleService::create_rule(None, &db, "Starbucks".to_string(), coffee, 1).await.unwrap();
+
+        let id = add(&db, uncategorized, "2026-01-03", "Starbucks").await;
+        RuleService::apply_rules(None, &db, "2026-01").await.unwrap();
+
+        let t = TransactionService::get_transaction(&db, id).await.unwrap();
+        assert_eq!(t.category_id, coffee);
+    }
+}
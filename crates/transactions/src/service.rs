@@ -1,102 +1,207 @@
-use crate::models::{CreateTransactionRequest, Transaction, MonthlySummary};
+use crate::models::{AmountBucket, AmountHistogram, HISTOGRAM_BOUNDS, CreateTransactionCommand, SyncChanges, WeekTotal, CreateTransactionRequest, FullMonthView, OrphanedTransaction, PeriodSummary, Reconciliation, Transaction, MonthlySummary, TransactionSort};
 use crate::repository::TransactionRepository;
-use database::{RepositoryError, Database};
+use cards::service::{CardError, CardService};
+use categories::service::{CategoryError, CategoryService, MonthNoteService};
+use chrono::Datelike;
+use common::month::Month;
+use common::period::Period;
+use common::week::WeekStart;
+use database::Database;
+use database::events::ChangeKind;
+use common::errors::{Domain, DomainError};
+use common::auth::UserContext;
 use tracing::instrument;
 
-#[derive(Debug, thiserror::Error)]
-pub enum TransactionError {
-    #[error("Invalid input: {0}")]
-    InvalidInput(String),
-    #[error("Database error: {0}")]
-    Infrastructure(String),
-    #[error("Transaction not found")]
-    NotFound,
-}
+#[derive(Debug, Default)]
+pub struct Transactions;
 
-impl From<RepositoryError> for TransactionError {
-    fn from(err: RepositoryError) -> Self {
-        match err {
-            RepositoryError::NotFound => TransactionError::NotFound,
-            RepositoryError::Infrastructure(e) => TransactionError::Infrastructure(e.to_string()),
-            _ => TransactionError::Infrastructure(err.to_string()),
-        }
-    }
+impl Domain for Transactions {
+    const NOT_FOUND: &'static str = "Transaction not found";
 }
 
+pub type TransactionError = DomainError<Transactions>;
+
+/// An amount at least this many times the category's average gets a warning.
+const LARGE_AMOUNT_FACTOR: f64 = 10.0;
+/// Transactions a category needs before its history is used for warnings.
+const MIN_HISTORY_FOR_WARNINGS: i64 = 3;
+
 pub struct TransactionService;
 
 impl TransactionService {
     #[instrument(skip(db))]
     pub async fn create_transaction(
+        ctx: Option<&UserContext>,
         db: &Database,
-        category_id: i64,
-        card_id: Option<i64>,
-        date: String,
-        amount_dollars: f64,
-        notes: Option<String>,
+        cmd: CreateTransactionCommand,
+        max_per_month: Option<u32>,
     ) -> Result<i64, TransactionError> {
-        // Look up category to determine if it's income
-        let category = categories::service::CategoryService::get_category(db, category_id)
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to get category for transaction: {:?}", e);
-                TransactionError::InvalidInput("Invalid category ID".into())
-            })?;
+        Self::create_transaction_returning(ctx, db, cmd, max_per_month).await.map(|t| t.id)
+    }
 
-        let req = CreateTransactionRequest::new(category_id, card_id, date, amount_dollars, category.is_income, notes)
-            .map_err(TransactionError::InvalidInput)?;
+    /// Creates the transaction and returns it as stored (with the amount's derived sign), read back
+    /// in the same unit of work so callers can render it without a second fetch.
+    #[instrument(skip(db))]
+    pub async fn create_transaction_returning(
+        ctx: Option<&UserContext>,
+        db: &Database,
+        cmd: CreateTransactionCommand,
+        max_per_month: Option<u32>,
+    ) -> Result<Transaction, TransactionError> {
+        UserContext::authorize(ctx).map_err(TransactionError::Forbidden)?;
+
+        // Validated before the category is resolved, so a bad date or amount never creates a new category
+        let req = CreateTransactionRequest::new(
+            cmd.category_id,
+            cmd.card_id,
+            cmd.transaction_date,
+            cmd.amount_dollars,
+            false,
+            cmd.notes,
+            cmd.excluded,
+        )
+        .map_err(TransactionError::InvalidInput)?;
+
+        let mut uow = db.begin().await?;
+
+        let category = match &cmd.new_category {
+            Some(new) => CategoryService::find_or_create_on(uow.connection(), &new.name, new.is_income, new.max_categories, &new.palette)
+                .await
+                .map_err(|e| match e {
+                    CategoryError::InvalidInput(msg) | CategoryError::Conflict(msg) => TransactionError::InvalidInput(msg),
+                    other => TransactionError::Infrastructure(other.to_string()),
+                })?,
+            // Look up category to determine if it's income
+            None => CategoryService::get_category_on(uow.connection(), cmd.category_id)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to get category for transaction: {:?}", e);
+                    TransactionError::InvalidInput("Invalid category ID".into())
+                })?,
+        };
+        let req = req.for_category(category.id, category.is_income);
 
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
         let mut repo = TransactionRepository::new(uow.connection());
+
+        if let Some(max) = max_per_month {
+            // Validated as YYYY-MM-DD above, so the first 7 bytes are the month
+            let month = &req.transaction_date()[..7];
+            if repo.count_for_month(month).await? >= i64::from(max) {
+                return Err(TransactionError::InvalidInput(format!(
+                    "Transaction limit of {} for {} reached", max, month
+                )));
+            }
+        }
         
         let id = repo.create(&req).await?;
+        let transaction = repo.find_by_id(id).await?.ok_or_else(TransactionError::not_found)?;
         
-        uow.commit().await.map_err(RepositoryError::from)?;
+        uow.commit().await?;
+        db.events().publish(&transaction.transaction_date[..7], ChangeKind::Created, Some(id));
         
-        Ok(id)
+        Ok(transaction)
+    }
+
+    /// Soft warnings for a transaction about to be created: nudges, not errors, so they never
+    /// block the insert. Compares against the category's existing history, which must have at
+    /// least `MIN_HISTORY_FOR_WARNINGS` transactions.
+    #[instrument(skip(db))]
+    pub async fn warnings_for(db: &Database, cmd: &CreateTransactionCommand) -> Result<Vec<String>, TransactionError> {
+        let mut conn = db.connection().await?;
+        let (count, average, weekend_count) = TransactionRepository::new(&mut conn).category_history(cmd.category_id).await?;
+
+        let mut warnings = Vec::new();
+        if count < MIN_HISTORY_FOR_WARNINGS {
+            return Ok(warnings);
+        }
+
+        let amount = cmd.amount_dollars.abs() * 100.0;
+        if average > 0.0 && amount >= average * LARGE_AMOUNT_FACTOR {
+            warnings.push(format!(
+                "Amount is at least {}x this category's average of ${:.2}",
+                LARGE_AMOUNT_FACTOR, average / 100.0
+            ));
+        }
+
+        let is_weekend = chrono::NaiveDate::parse_from_str(&cmd.transaction_date, "%Y-%m-%d")
+            .is_ok_and(|d| matches!(d.weekday(), chrono::Weekday::Sat | chrono::Weekday::Sun));
+        if is_weekend && weekend_count == 0 {
+            warnings.push("Date falls on a weekend, but this category's transactions are usually on weekdays".to_string());
+        }
+
+        Ok(warnings)
     }
 
+    /// Moving the transaction into another month counts against that month's `max_per_month`;
+    /// staying in the same month never does.
     #[instrument(skip(db))]
     pub async fn update_transaction(
+        ctx: Option<&UserContext>,
         db: &Database,
         id: i64,
-        category_id: i64,
-        card_id: Option<i64>,
-        date: String,
-        amount_dollars: f64,
-        notes: Option<String>,
+        cmd: CreateTransactionCommand,
+        max_per_month: Option<u32>,
     ) -> Result<Transaction, TransactionError> {
+        UserContext::authorize(ctx).map_err(TransactionError::Forbidden)?;
+
         // Look up category to determine if it's income
-        let category = categories::service::CategoryService::get_category(db, category_id)
+        let category = categories::service::CategoryService::get_category(db, cmd.category_id)
             .await
             .map_err(|e| {
                 tracing::error!("Failed to get category for transaction update: {:?}", e);
                 TransactionError::InvalidInput("Invalid category ID".into())
             })?;
 
-        let req = CreateTransactionRequest::new(category_id, card_id, date, amount_dollars, category.is_income, notes)
-            .map_err(TransactionError::InvalidInput)?;
+        let req = CreateTransactionRequest::new(
+            cmd.category_id,
+            cmd.card_id,
+            cmd.transaction_date,
+            cmd.amount_dollars,
+            category.is_income,
+            cmd.notes,
+            cmd.excluded,
+        )
+        .map_err(TransactionError::InvalidInput)?;
 
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
+        let mut uow = db.begin().await?;
         let mut repo = TransactionRepository::new(uow.connection());
         
+        let previous = repo.find_by_id(id).await?
+            .ok_or_else(TransactionError::not_found)?;
+
+        let month = &req.transaction_date()[..7];
+        if let Some(max) = max_per_month
+            && &previous.transaction_date[..7] != month
+            && repo.count_for_month(month).await? >= i64::from(max)
+        {
+            return Err(TransactionError::InvalidInput(format!(
+                "Transaction limit of {} for {} reached", max, month
+            )));
+        }
         repo.update(id, &req).await?;
         
         let transaction = repo.find_by_id(id).await?
-            .ok_or(TransactionError::NotFound)?;
+            .ok_or_else(TransactionError::not_found)?;
             
-        uow.commit().await.map_err(RepositoryError::from)?;
+        uow.commit().await?;
+
+        // A date change moves the transaction out of one month and into another
+        let month = &transaction.transaction_date[..7];
+        db.events().publish(month, ChangeKind::Updated, Some(id));
+        if &previous.transaction_date[..7] != month {
+            db.events().publish(&previous.transaction_date[..7], ChangeKind::Updated, Some(id));
+        }
         
         Ok(transaction)
     }
 
     #[instrument(skip(db))]
     pub async fn get_transaction(db: &Database, id: i64) -> Result<Transaction, TransactionError> {
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
-        let mut repo = TransactionRepository::new(uow.connection());
+        let mut conn = db.connection().await?;
+        let mut repo = TransactionRepository::new(&mut conn);
         
         let transaction = repo.find_by_id(id).await?
-            .ok_or(TransactionError::NotFound)?;
+            .ok_or_else(TransactionError::not_found)?;
             
         Ok(transaction)
     }
@@ -105,41 +210,1004 @@ impl TransactionService {
     pub async fn get_month_view(
         db: &Database,
         month: &str, // YYYY-MM
+        sort: TransactionSort,
     ) -> Result<(Vec<Transaction>, MonthlySummary), TransactionError> {
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
-        let mut repo = TransactionRepository::new(uow.connection());
+        let mut conn = db.connection().await?;
+        let mut repo = TransactionRepository::new(&mut conn);
         
-        let transactions = repo.list_by_month(month).await?;
+        let transactions = repo.list_by_month(month, sort).await?;
+        let summary = Self::summarize(month, &transactions);
         
-        let mut total_income = 0;
-        let mut total_expenses = 0;
+        Ok((transactions, summary))
+    }
+
+    /// Everything the month page shows, read in one unit of work so it is a consistent snapshot
+    /// and costs a single connection checkout.
+    #[instrument(skip(db))]
+    pub async fn get_full_month_view(
+        db: &Database,
+        month: &str, // YYYY-MM
+        sort: TransactionSort,
+    ) -> Result<FullMonthView, TransactionError> {
+        let mut uow = db.begin().await?;
+
+        let transactions = TransactionRepository::new(uow.connection()).list_by_month(month, sort).await?;
+        let summary = Self::summarize(month, &transactions);
+
+        let budget_views = CategoryService::budget_view_on(uow.connection(), month)
+            .await
+            .map_err(CategoryError::recast)?;
+        let cards = CardService::list_cards_on(uow.connection())
+            .await
+            .map_err(CardError::recast)?;
+        let note = MonthNoteService::get_on(uow.connection(), month)
+            .await
+            .map_err(CategoryError::recast)?;
+        uow.rollback().await?;
+
+        Ok(FullMonthView { transactions, summary, budget_views, cards, note })
+    }
+
+    /// The month's totals computed in SQL, without loading its transactions.
+    #[instrument(skip(db))]
+    pub async fn get_month_summary(db: &Database, month: &str) -> Result<MonthlySummary, TransactionError> {
+        let parsed: Month = month.parse().map_err(TransactionError::InvalidInput)?;
+        let summary = Self::get_period_summary(db, Period::Month, parsed.first_day()).await?;
+
+        Ok(MonthlySummary {
+            month: summary.key,
+            total_income: summary.total_income,
+            total_expenses: summary.total_expenses,
+            net: summary.net,
+        })
+    }
+
+    /// Totals for the `period` containing `date`, computed in SQL. Excluded transactions are not counted.
+    #[instrument(skip(db))]
+    pub async fn get_period_summary(db: &Database, period: Period, date: chrono::NaiveDate) -> Result<PeriodSummary, TransactionError> {
+        let (first, last) = period.bounds(date);
+        let from = first.format("%Y-%m-%d").to_string();
+        let to = last.format("%Y-%m-%d").to_string();
+
+        let mut conn = db.connection().await?;
+        let (total_income, total_expenses) = TransactionRepository::new(&mut conn).totals_between(&from, &to).await?;
+
+        Ok(PeriodSummary {
+            key: period.key(date),
+            from,
+            to,
+            total_income,
+            total_expenses,
+            net: total_income - total_expenses,
+        })
+    }
+
+    /// Saturates rather than overflowing, so a corrupt amount can't take down the month page.
+    fn summarize(month: &str, transactions: &[Transaction]) -> MonthlySummary {
+        let mut total_income: i64 = 0;
+        let mut total_expenses: i64 = 0;
         
-        for t in &transactions {
+        for t in transactions.iter().filter(|t| !t.excluded) {
             if t.amount > 0 {
-                total_income += t.amount;
+                total_income = total_income.saturating_add(t.amount);
             } else {
-                total_expenses += t.amount.abs();
+                total_expenses = total_expenses.saturating_add(t.amount.saturating_abs());
             }
         }
         
-        let summary = MonthlySummary {
+        MonthlySummary {
             month: month.to_string(),
             total_income,
             total_expenses,
             net: total_income - total_expenses,
-        };
-        
-        Ok((transactions, summary))
+        }
+    }
+
+    /// Buckets the month's transaction amounts (by absolute value) into `HISTOGRAM_BOUNDS` ranges,
+    /// separately for income and expenses. Excluded transactions are not counted.
+    #[instrument(skip(db))]
+    pub async fn amount_histogram(db: &Database, month: &str) -> Result<AmountHistogram, TransactionError> {
+        let (transactions, _) = Self::get_month_view(db, month, TransactionSort::default()).await?;
+
+        let mut income = Self::empty_buckets();
+        let mut expenses = Self::empty_buckets();
+
+        for t in transactions.iter().filter(|t| !t.excluded) {
+            let buckets = if t.amount > 0 { &mut income } else { &mut expenses };
+            let amount = t.amount.saturating_abs();
+            let index = HISTOGRAM_BOUNDS.iter().position(|&bound| amount < bound).unwrap_or(HISTOGRAM_BOUNDS.len());
+            buckets[index].count += 1;
+        }
+
+        Ok(AmountHistogram { month: month.to_string(), income, expenses })
+    }
+
+    /// The month's income and expenses per week, weeks beginning on `week_start`. Every week
+    /// touching the month is listed, empty ones included. Excluded transactions are not counted.
+    #[instrument(skip(db))]
+    pub async fn weekly_breakdown(db: &Database, month: &str, week_start: WeekStart) -> Result<Vec<WeekTotal>, TransactionError> {
+        let parsed: Month = month.parse().map_err(TransactionError::InvalidInput)?;
+        let (transactions, _) = Self::get_month_view(db, month, TransactionSort::default()).await?;
+
+        let first = parsed.first_day();
+        let last = parsed.next().first_day().pred_opt().unwrap_or(first);
+
+        let mut weeks = Vec::new();
+        let mut week = week_start.week_of(first);
+        while week <= last {
+            let start = week.max(first);
+            let end = (week + chrono::Duration::days(6)).min(last);
+            weeks.push(WeekTotal {
+                start: start.format("%Y-%m-%d").to_string(),
+                end: end.format("%Y-%m-%d").to_string(),
+                label: format!("{} - {}", start.format("%b %-d"), end.format("%b %-d")),
+                income: 0,
+                expenses: 0,
+            });
+            week += chrono::Duration::days(7);
+        }
+
+        for t in transactions.iter().filter(|t| !t.excluded) {
+            let Ok(date) = chrono::NaiveDate::parse_from_str(&t.transaction_date, "%Y-%m-%d") else { continue };
+            let index = ((week_start.week_of(date) - week_start.week_of(first)).num_days() / 7) as usize;
+            let Some(bucket) = weeks.get_mut(index) else { continue };
+            if t.amount > 0 {
+                bucket.income = bucket.income.saturating_add(t.amount);
+            } else {
+                bucket.expenses = bucket.expenses.saturating_add(t.amount.saturating_abs());
+            }
+        }
+
+        Ok(weeks)
+    }
+
+    fn empty_buckets() -> Vec<AmountBucket> {
+        let dollars = |cents: i64| cents / 100;
+        let mut min = 0;
+        let mut buckets = Vec::with_capacity(HISTOGRAM_BOUNDS.len() + 1);
+
+        for &max in &HISTOGRAM_BOUNDS {
+            buckets.push(AmountBucket {
+                label: format!("${}-{}", dollars(min), dollars(max)),
+                min,
+                max: Some(max),
+                count: 0,
+            });
+            min = max;
+        }
+        buckets.push(AmountBucket { label: format!("${}+", dollars(min)), min, max: None, count: 0 });
+
+        buckets
+    }
+
+    /// What a category has actually received (income) or spent (expense) in the given transactions,
+    /// as a positive number of cents. Excluded transactions are not counted. Saturates on corrupt amounts.
+    pub fn category_actual(transactions: &[Transaction], category_id: i64, is_income: bool) -> i64 {
+        let counted = transactions.iter().filter(|t| t.category_id == category_id && !t.excluded);
+        if is_income {
+            // For income, sum positive amounts
+            counted.filter(|t| t.amount > 0).fold(0, |sum, t| sum.saturating_add(t.amount))
+        } else {
+            // For expenses, sum absolute negative amounts
+            counted.filter(|t| t.amount < 0).fold(0, |sum, t| sum.saturating_add(t.amount.saturating_abs()))
+        }
+    }
+
+    /// Clones the source month's transactions in `category_ids` into the target month on the same
+    /// day-of-month (clamped to the target's last day). A transaction is skipped when the target
+    /// already had one with the same category, amount and notes before the copy, each of those
+    /// standing in for one source transaction only, so running twice copies nothing new while two
+    /// identical bills both get copied. Fails without copying anything if the target month would go
+    /// over `max_per_month`. Returns the number of transactions copied.
+    #[instrument(skip(db))]
+    pub async fn copy_fixed_transactions(
+        ctx: Option<&UserContext>,
+        db: &Database,
+        source_month: &str,
+        target_month: &str,
+        category_ids: &[i64],
+        max_per_month: Option<u32>,
+    ) -> Result<u64, TransactionError> {
+        UserContext::authorize(ctx).map_err(TransactionError::Forbidden)?;
+
+        let target: Month = target_month.parse().map_err(TransactionError::InvalidInput)?;
+
+        let mut uow = db.begin().await?;
+        let mut repo = TransactionRepository::new(uow.connection());
+
+        let source = repo.list_by_month(source_month, TransactionSort::default()).await?;
+        let mut unmatched = repo.list_by_month(target_month, TransactionSort::default()).await?;
+
+        let mut copied = 0;
+        for t in source.iter().filter(|t| category_ids.contains(&t.category_id)) {
+            let already_copied = unmatched.iter()
+                .position(|e| e.category_id == t.category_id && e.amount == t.amount && e.notes == t.notes);
+            if let Some(index) = already_copied {
+                unmatched.swap_remove(index);
+                continue;
+            }
+
+            if let Some(max) = max_per_month
+                && repo.count_for_month(target_month).await? >= i64::from(max)
+            {
+                return Err(TransactionError::InvalidInput(format!(
+                    "Transaction limit of {} for {} reached", max, target_month
+                )));
+            }
+
+            let day = chrono::NaiveDate::parse_from_str(&t.transaction_date, "%Y-%m-%d")
+                .map(|d| d.day())
+                .unwrap_or(1);
+
+            let req = CreateTransactionRequest::new(
+                t.category_id,
+                t.card_id,
+                target.day_clamped(day).format("%Y-%m-%d").to_string(),
+                t.amount.abs() as f64 / 100.0,
+                t.amount > 0,
+                t.notes.clone(),
+                t.excluded,
+            )
+            .map_err(TransactionError::InvalidInput)?;
+
+            repo.create(&req).await?;
+            copied += 1;
+        }
+
+        uow.commit().await?;
+        if copied > 0 {
+            db.events().publish(target_month, ChangeKind::Created, None);
+        }
+        Ok(copied)
+    }
+
+    /// Parses shorthand like `"45.50 groceries discover lunch"` into a transaction dated today.
+    ///
+    /// The first numeric token is the amount. Other tokens are matched case-insensitively against
+    /// active category names, then card names (exact name, or a prefix of the name or any of its words).
+    /// Whatever is left over becomes the notes. Without a card the transaction is recorded as cash.
+    #[instrument(skip(db))]
+    pub async fn parse_quick_add(db: &Database, input: &str) -> Result<CreateTransactionCommand, TransactionError> {
+        let categories = categories::service::CategoryService::list_categories(db)
+            .await
+            .map_err(CategoryError::recast)?;
+        let cards = cards::service::CardService::list_active_cards(db)
+            .await
+            .map_err(CardError::recast)?;
+
+        let category_names: Vec<(i64, &str)> = categories.iter()
+            .filter(|c| c.is_active)
+            .map(|c| (c.id, c.name.as_str()))
+            .collect();
+        let card_names: Vec<(i64, &str)> = cards.iter().map(|c| (c.id, c.name.as_str())).collect();
+
+        let mut amount_dollars = None;
+        let mut category_id = None;
+        let mut card_id = None;
+        let mut notes = Vec::new();
+
+        for token in input.split_whitespace() {
+            // The add form's parser, so "nan" or "1e3" stay words and "$1,234.56" is an amount
+            if amount_dollars.is_none() && let Ok(amount) = common::money::parse_dollars(token) {
+                amount_dollars = Some(amount);
+                continue;
+            }
+
+            if category_id.is_none() {
+                match match_name(token, &category_names) {
+                    NameMatch::One(id) => {
+                        category_id = Some(id);
+                        continue;
+                    }
+                    NameMatch::Many(names) => {
+                        return Err(TransactionError::InvalidInput(format!(
+                            "'{}' matches several categories: {}", token, names.join(", ")
+                        )));
+                    }
+                    NameMatch::None => {}
+                }
+            }
+
+            if card_id.is_none() {
+                match match_name(token, &card_names) {
+                    NameMatch::One(id) => {
+                        card_id = Some(id);
+                        continue;
+                    }
+                    NameMatch::Many(names) => {
+                        return Err(TransactionError::InvalidInput(format!(
+                            "'{}' matches several cards: {}", token, names.join(", ")
+                        )));
+                    }
+                    NameMatch::None => {}
+                }
+            }
+
+            notes.push(token);
+        }
+
+        let amount_dollars = amount_dollars
+            .ok_or_else(|| TransactionError::InvalidInput("No amount found in quick-add input".into()))?;
+        let category_id = category_id.ok_or_else(|| {
+            TransactionError::InvalidInput(format!("Could not match a category in '{}'", input.trim()))
+        })?;
+
+        Ok(CreateTransactionCommand {
+            category_id,
+            card_id,
+            transaction_date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            amount_dollars,
+            notes: if notes.is_empty() { None } else { Some(notes.join(" ")) },
+            excluded: false,
+            new_category: None,
+        })
+    }
+
+    #[instrument(skip(db))]
+    pub async fn list_orphans(db: &Database) -> Result<Vec<OrphanedTransaction>, TransactionError> {
+        let mut conn = db.connection().await?;
+        let mut repo = TransactionRepository::new(&mut conn);
+
+        let orphans = repo.list_orphans().await?;
+        Ok(orphans)
+    }
+
+    /// Ids of transactions whose stored sign disagrees with their category's `is_income`.
+    #[instrument(skip(db))]
+    pub async fn audit_sign_consistency(db: &Database) -> Result<Vec<i64>, TransactionError> {
+        let mut conn = db.connection().await?;
+        Ok(TransactionRepository::new(&mut conn).mis_signed_ids().await?)
+    }
+
+    /// Re-signs every transaction `audit_sign_consistency` reports, in one transaction, and returns their ids.
+    #[instrument(skip(db))]
+    pub async fn fix_sign_consistency(ctx: Option<&UserContext>, db: &Database) -> Result<Vec<i64>, TransactionError> {
+        UserContext::authorize(ctx).map_err(TransactionError::Forbidden)?;
+
+        let mut uow = db.begin().await?;
+        let mut repo = TransactionRepository::new(uow.connection());
+
+        let ids = repo.mis_signed_ids().await?;
+        let months = repo.mis_signed_months().await?;
+        repo.fix_signs().await?;
+
+        uow.commit().await?;
+        for month in months {
+            db.events().publish(month, ChangeKind::Updated, None);
+        }
+        Ok(ids)
+    }
+
+    /// Counted net totals in cents for just the given categories in the month, as
+    /// `(category_id, total)` sorted by id. Categories without transactions are left out.
+    #[instrument(skip(db))]
+    pub async fn summary_for_categories(db: &Database, month: &str, category_ids: &[i64]) -> Result<Vec<(i64, i64)>, TransactionError> {
+        if category_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = db.connection().await?;
+        Ok(TransactionRepository::new(&mut conn).totals_for_categories(month, category_ids).await?)
+    }
+
+    /// Ticks the transaction off against the bank statement (or unticks it), returning it as stored.
+    #[instrument(skip(db))]
+    pub async fn set_cleared(ctx: Option<&UserContext>, db: &Database, id: i64, cleared: bool) -> Result<Transaction, TransactionError> {
+        UserContext::authorize(ctx).map_err(TransactionError::Forbidden)?;
+
+        let mut uow = db.begin().await?;
+        let mut repo = TransactionRepository::new(uow.connection());
+
+        repo.set_cleared(id, cleared).await?;
+        let transaction = repo.find_by_id(id).await?.ok_or_else(TransactionError::not_found)?;
+
+        uow.commit().await?;
+        db.events().publish(&transaction.transaction_date[..7], ChangeKind::Updated, Some(id));
+        Ok(transaction)
+    }
+
+    /// The month's uncleared transactions and the cleared-vs-uncleared totals, for reconciling.
+    #[instrument(skip(db))]
+    pub async fn reconciliation(db: &Database, month: &str) -> Result<Reconciliation, TransactionError> {
+        let mut conn = db.connection().await?;
+        let mut repo = TransactionRepository::new(&mut conn);
+
+        let uncleared = repo.list_uncleared(month).await?;
+        let (cleared_total, uncleared_total) = repo.cleared_totals(month).await?;
+
+        Ok(Reconciliation { month: month.to_string(), cleared_total, uncleared_total, uncleared })
+    }
+
+    /// Transactions changed after the cursor `since`, for incremental sync; 0 fetches everything.
+    /// The cursor and the changes are read from one snapshot, and change numbers are assigned inside
+    /// the writing transaction, so a write still uncommitted during this read is numbered above the
+    /// returned cursor and comes back on the next sync.
+    #[instrument(skip(db))]
+    pub async fn changes_since(db: &Database, since: i64) -> Result<SyncChanges, TransactionError> {
+        if since < 0 {
+            return Err(TransactionError::InvalidInput(format!("Invalid sync cursor {}", since)));
+        }
+
+        let mut uow = db.begin().await?;
+        let mut repo = TransactionRepository::new(uow.connection());
+        let cursor = repo.sync_cursor().await?;
+        let transactions = repo.changed_since(since).await?;
+        uow.rollback().await?;
+
+        Ok(SyncChanges { since, cursor, transactions })
     }
 
     #[instrument(skip(db))]
-    pub async fn delete_transaction(db: &Database, id: i64) -> Result<(), TransactionError> {
-        let mut uow = db.begin().await.map_err(RepositoryError::from)?;
+    pub async fn delete_transaction(ctx: Option<&UserContext>, db: &Database, id: i64) -> Result<(), TransactionError> {
+        UserContext::authorize(ctx).map_err(TransactionError::Forbidden)?;
+
+        let mut uow = db.begin().await?;
         let mut repo = TransactionRepository::new(uow.connection());
         
+        let transaction = repo.find_by_id(id).await?
+            .ok_or_else(TransactionError::not_found)?;
         repo.delete(id).await?;
         
-        uow.commit().await.map_err(RepositoryError::from)?;
+        uow.commit().await?;
+        db.events().publish(&transaction.transaction_date[..7], ChangeKind::Deleted, Some(id));
         Ok(())
     }
 }
+
+enum NameMatch {
+    None,
+    One(i64),
+    Many(Vec<String>),
+}
+
+/// An exact (case-insensitive) name wins outright; otherwise the token must prefix exactly one name
+/// or one of its words.
+fn match_name(token: &str, names: &[(i64, &str)]) -> NameMatch {
+    let token = token.to_lowercase();
+
+    if let Some((id, _)) = names.iter().find(|(_, name)| name.to_lowercase() == token) {
+        return NameMatch::One(*id);
+    }
+
+    let matches: Vec<&(i64, &str)> = names.iter()
+        .filter(|(_, name)| {
+            let name = name.to_lowercase();
+            name.starts_with(&token) || name.split_whitespace().any(|word| word.starts_with(&token))
+        })
+        .collect();
+
+    match matches.as_slice() {
+        [] => NameMatch::None,
+        [(id, _)] => NameMatch::One(*id),
+        many => NameMatch::Many(many.iter().map(|(_, name)| name.to_string()).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::NewCategory;
+    use crate::rules::RuleService;
+    use common::money::RoundingMode;
+    use common::palette::Palette;
+    use database::get_test_db;
+
+    fn command(category_id: i64, date: &str, amount_dollars: f64, excluded: bool) -> CreateTransactionCommand {
+        CreateTransactionCommand {
+            category_id,
+            card_id: None,
+            transaction_date: date.to_string(),
+            amount_dollars,
+            notes: None,
+            excluded,
+            new_category: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_excluded_transaction_not_in_summary() {
+        let db = get_test_db().await;
+        let cat = CategoryService::create_category(None, &db, "Transfers".to_string(), false, None).await.unwrap();
+
+        TransactionService::create_transaction(None, &db, command(cat, "2026-01-10", 20.0, false), None).await.unwrap();
+        let (_, before) = TransactionService::get_month_view(&db, "2026-01", TransactionSort::default()).await.unwrap();
+
+        TransactionService::create_transaction(None, &db, command(cat, "2026-01-11", 500.0, true), None).await.unwrap();
+        let (transactions, after) = TransactionService::get_month_view(&db, "2026-01", TransactionSort::default()).await.unwrap();
+
+        assert_eq!(transactions.len(), 2, "excluded rows are still listed");
+        assert_eq!(before.total_expenses, 2000);
+        assert_eq!(after.total_expenses, 2000);
+        assert_eq!(after.net, before.net);
+    }
+
+    #[tokio::test]
+    async fn test_excluded_transaction_not_in_category_actual() {
+        let db = get_test_db().await;
+        let cat = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+
+        TransactionService::create_transaction(None, &db, command(cat, "2026-01-10", 30.0, false), None).await.unwrap();
+        TransactionService::create_transaction(None, &db, command(cat, "2026-01-12", 45.0, true), None).await.unwrap();
+        let (transactions, _) = TransactionService::get_month_view(&db, "2026-01", TransactionSort::default()).await.unwrap();
+
+        assert_eq!(TransactionService::category_actual(&transactions, cat, false), 3000);
+    }
+
+    #[tokio::test]
+    async fn test_amount_desc_sorts_by_absolute_amount() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let pay = CategoryService::create_category(None, &db, "Paycheck".to_string(), true, None).await.unwrap();
+
+        TransactionService::create_transaction(None, &db, command(dining, "2026-01-03", 25.0, false), None).await.unwrap();
+        TransactionService::create_transaction(None, &db, command(pay, "2026-01-01", 100.0, false), None).await.unwrap();
+        TransactionService::create_transaction(None, &db, command(dining, "2026-01-02", 250.0, false), None).await.unwrap();
+
+        let (transactions, _) = TransactionService::get_month_view(&db, "2026-01", TransactionSort::AmountDesc).await.unwrap();
+        let amounts: Vec<i64> = transactions.iter().map(|t| t.amount).collect();
+        assert_eq!(amounts, vec![-25000, 10000, -2500]);
+
+        let (transactions, _) = TransactionService::get_month_view(&db, "2026-01", TransactionSort::from_param("newest")).await.unwrap();
+        let dates: Vec<&str> = transactions.iter().map(|t| t.transaction_date.as_str()).collect();
+        assert_eq!(dates, vec!["2026-01-03", "2026-01-02", "2026-01-01"]);
+    }
+
+    #[tokio::test]
+    async fn test_full_month_view_matches_separate_reads() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        CategoryService::set_monthly_limit(None, &db, dining, "2026-01".to_string(), 200.0, RoundingMode::HalfUp).await.unwrap();
+        CardService::create_card(None, &db, "Visa".to_string()).await.unwrap();
+        TransactionService::create_transaction(None, &db, command(dining, "2026-01-03", 25.0, false), None).await.unwrap();
+        TransactionService::create_transaction(None, &db, command(dining, "2026-01-09", 40.0, true), None).await.unwrap();
+
+        let full = TransactionService::get_full_month_view(&db, "2026-01", TransactionSort::default()).await.unwrap();
+
+        let (transactions, summary) = TransactionService::get_month_view(&db, "2026-01", TransactionSort::default()).await.unwrap();
+        let budget_views = CategoryService::get_budget_view(&db, "2026-01").await.unwrap();
+        let cards = CardService::list_cards(&db).await.unwrap();
+
+        assert_eq!(full.transactions, transactions);
+        assert_eq!(format!("{:?}", full.summary), format!("{:?}", summary));
+        // The SQL totals agree with the ones summed from the loaded rows, excluded row included
+        let sql_summary = TransactionService::get_month_summary(&db, "2026-01").await.unwrap();
+        assert_eq!(format!("{:?}", sql_summary), format!("{:?}", summary));
+        assert_eq!(format!("{:?}", full.budget_views), format!("{:?}", budget_views));
+        assert_eq!(format!("{:?}", full.cards), format!("{:?}", cards));
+    }
+
+    #[tokio::test]
+    async fn test_create_transaction_respects_max_per_month() {
+        let db = get_test_db().await;
+        let cat = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+
+        TransactionService::create_transaction(None, &db, command(cat, "2026-01-10", 10.0, false), Some(2)).await.unwrap();
+        TransactionService::create_transaction(None, &db, command(cat, "2026-01-11", 10.0, false), Some(2)).await.unwrap();
+        let result = TransactionService::create_transaction(None, &db, command(cat, "2026-01-12", 10.0, false), Some(2)).await;
+        assert!(matches!(result, Err(TransactionError::InvalidInput(_))));
+
+        // Other months are counted separately
+        TransactionService::create_transaction(None, &db, command(cat, "2026-02-01", 10.0, false), Some(2)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_update_transaction_respects_max_per_month() {
+        let db = get_test_db().await;
+        let cat = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        TransactionService::create_transaction(None, &db, command(cat, "2026-01-10", 10.0, false), None).await.unwrap();
+        let january = TransactionService::create_transaction(None, &db, command(cat, "2026-01-11", 10.0, false), None).await.unwrap();
+        let february = TransactionService::create_transaction(None, &db, command(cat, "2026-02-01", 10.0, false), None).await.unwrap();
+
+        // January is full, so February's transaction can't move in
+        let result = TransactionService::update_transaction(None, &db, february, command(cat, "2026-01-20", 10.0, false), Some(2)).await;
+        assert!(matches!(result, Err(TransactionError::InvalidInput(ref msg)) if msg.contains("2026-01")), "{:?}", result);
+        assert_eq!(TransactionService::get_transaction(&db, february).await.unwrap().transaction_date, "2026-02-01");
+
+        // Edits within a full month still work, and a month with room takes a move
+        TransactionService::update_transaction(None, &db, january, command(cat, "2026-01-12", 15.0, false), Some(2)).await.unwrap();
+        TransactionService::update_transaction(None, &db, january, command(cat, "2026-02-02", 15.0, false), Some(2)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_parse_quick_add_fully_specified() {
+        let db = get_test_db().await;
+        let groceries = CategoryService::list_categories(&db).await.unwrap()
+            .into_iter().find(|c| c.name == "Groceries").unwrap().id;
+        let discover = cards::service::CardService::list_cards(&db).await.unwrap()
+            .into_iter().find(|c| c.name == "Discover Credit").unwrap().id;
+
+        let cmd = TransactionService::parse_quick_add(&db, "45.50 groceries discover lunch run").await.unwrap();
+
+        assert_eq!(cmd.amount_dollars, 45.50);
+        assert_eq!(cmd.category_id, groceries);
+        assert_eq!(cmd.card_id, Some(discover));
+        assert_eq!(cmd.notes.as_deref(), Some("lunch run"));
+        assert_eq!(cmd.transaction_date, chrono::Local::now().format("%Y-%m-%d").to_string());
+    }
+
+    #[tokio::test]
+    async fn test_parse_quick_add_ambiguous_category() {
+        let db = get_test_db().await;
+
+        // Both "Mazda auto" and "Elantra auto" contain a word starting with "auto"
+        let result = TransactionService::parse_quick_add(&db, "20 auto oil change").await;
+        match result {
+            Err(TransactionError::InvalidInput(msg)) => {
+                assert!(msg.contains("Mazda auto"));
+                assert!(msg.contains("Elantra auto"));
+            }
+            other => panic!("expected ambiguity error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_quick_add_reads_amounts_like_the_form() {
+        let db = get_test_db().await;
+        let groceries = CategoryService::list_categories(&db).await.unwrap()
+            .into_iter().find(|c| c.name == "Groceries").unwrap().id;
+
+        let cmd = TransactionService::parse_quick_add(&db, "nan inf groceries $1,234.56 1e3").await.unwrap();
+
+        assert_eq!(cmd.amount_dollars, 1234.56);
+        assert_eq!(cmd.category_id, groceries);
+        assert_eq!(cmd.notes.as_deref(), Some("nan inf 1e3"));
+    }
+
+    #[tokio::test]
+    async fn test_parse_quick_add_requires_category() {
+        let db = get_test_db().await;
+        let result = TransactionService::parse_quick_add(&db, "12.00 zzz").await;
+        assert!(matches!(result, Err(TransactionError::InvalidInput(msg)) if msg.contains("category")));
+    }
+
+    #[tokio::test]
+    async fn test_copy_fixed_transactions_forward() {
+        let db = get_test_db().await;
+        let rent = CategoryService::create_category(None, &db, "Housing".to_string(), false, None).await.unwrap();
+        let phone = CategoryService::create_category(None, &db, "Mobile".to_string(), false, None).await.unwrap();
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+
+        let mut rent_cmd = command(rent, "2026-01-01", 1300.0, false);
+        rent_cmd.notes = Some("Rent".to_string());
+        TransactionService::create_transaction(None, &db, rent_cmd, None).await.unwrap();
+        let mut phone_cmd = command(phone, "2026-01-31", 30.0, false);
+        phone_cmd.notes = Some("Phone bill".to_string());
+        TransactionService::create_transaction(None, &db, phone_cmd, None).await.unwrap();
+        TransactionService::create_transaction(None, &db, command(dining, "2026-01-15", 25.0, false), None).await.unwrap();
+
+        let copied = TransactionService::copy_fixed_transactions(None, &db, "2026-01", "2026-02", &[rent, phone], None).await.unwrap();
+        assert_eq!(copied, 2);
+
+        let (feb, _) = TransactionService::get_month_view(&db, "2026-02", TransactionSort::default()).await.unwrap();
+        assert_eq!(feb.len(), 2);
+        let phone_copy = feb.iter().find(|t| t.category_id == phone).unwrap();
+        assert_eq!(phone_copy.transaction_date, "2026-02-28");
+        assert_eq!(phone_copy.amount, -3000);
+        let rent_copy = feb.iter().find(|t| t.category_id == rent).unwrap();
+        assert_eq!(rent_copy.transaction_date, "2026-02-01");
+        assert_eq!(rent_copy.notes.as_deref(), Some("Rent"));
+
+        // Running again copies nothing
+        let copied = TransactionService::copy_fixed_transactions(None, &db, "2026-01", "2026-02", &[rent, phone], None).await.unwrap();
+        assert_eq!(copied, 0);
+    }
+
+    #[tokio::test]
+    async fn test_copy_fixed_transactions_keeps_identical_bills_apart() {
+        let db = get_test_db().await;
+        let gym = CategoryService::create_category(None, &db, "Gym".to_string(), false, None).await.unwrap();
+        for date in ["2026-01-05", "2026-01-12"] {
+            let mut cmd = command(gym, date, 50.0, false);
+            cmd.notes = Some("Weekly class".to_string());
+            TransactionService::create_transaction(None, &db, cmd, None).await.unwrap();
+        }
+
+        let copied = TransactionService::copy_fixed_transactions(None, &db, "2026-01", "2026-02", &[gym], None).await.unwrap();
+        assert_eq!(copied, 2);
+        let copied = TransactionService::copy_fixed_transactions(None, &db, "2026-01", "2026-02", &[gym], None).await.unwrap();
+        assert_eq!(copied, 0);
+
+        let (feb, _) = TransactionService::get_month_view(&db, "2026-02", TransactionSort::default()).await.unwrap();
+        let mut dates: Vec<&str> = feb.iter().map(|t| t.transaction_date.as_str()).collect();
+        dates.sort_unstable();
+        assert_eq!(dates, vec!["2026-02-05", "2026-02-12"]);
+    }
+
+    #[tokio::test]
+    async fn test_copy_fixed_transactions_respects_month_limit() {
+        let db = get_test_db().await;
+        let gym = CategoryService::create_category(None, &db, "Gym".to_string(), false, None).await.unwrap();
+        TransactionService::create_transaction(None, &db, command(gym, "2026-01-05", 50.0, false), None).await.unwrap();
+        TransactionService::create_transaction(None, &db, command(gym, "2026-01-12", 60.0, false), None).await.unwrap();
+        TransactionService::create_transaction(None, &db, command(gym, "2026-02-20", 10.0, false), None).await.unwrap();
+
+        // Room for one more in February, but two to copy: nothing is copied
+        let err = TransactionService::copy_fixed_transactions(None, &db, "2026-01", "2026-02", &[gym], Some(2)).await.unwrap_err();
+        assert!(matches!(err, TransactionError::InvalidInput(ref msg) if msg.contains("limit of 2")), "{:?}", err);
+        let (feb, _) = TransactionService::get_month_view(&db, "2026-02", TransactionSort::default()).await.unwrap();
+        assert_eq!(feb.len(), 1);
+
+        let copied = TransactionService::copy_fixed_transactions(None, &db, "2026-01", "2026-02", &[gym], Some(3)).await.unwrap();
+        assert_eq!(copied, 2);
+    }
+
+    #[tokio::test]
+    async fn test_amount_histogram_buckets() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let bonus = CategoryService::create_category(None, &db, "Bonus".to_string(), true, None).await.unwrap();
+
+        for amount in [4.50, 9.99, 10.0, 49.99, 75.0, 120.0, 300.0] {
+            TransactionService::create_transaction(None, &db, command(dining, "2026-03-05", amount, false), None).await.unwrap();
+        }
+        TransactionService::create_transaction(None, &db, command(dining, "2026-03-06", 2.0, true), None).await.unwrap();
+        TransactionService::create_transaction(None, &db, command(bonus, "2026-03-01", 2500.0, false), None).await.unwrap();
+        // Another month is not counted
+        TransactionService::create_transaction(None, &db, command(dining, "2026-04-01", 5.0, false), None).await.unwrap();
+
+        let histogram = TransactionService::amount_histogram(&db, "2026-03").await.unwrap();
+
+        let labels: Vec<&str> = histogram.expenses.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, ["$0-10", "$10-50", "$50-100", "$100+"]);
+        let counts = |buckets: &[AmountBucket]| buckets.iter().map(|b| b.count).collect::<Vec<_>>();
+        assert_eq!(counts(&histogram.expenses), [2, 2, 1, 2]);
+        assert_eq!(counts(&histogram.income), [0, 0, 0, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_weekly_breakdown_follows_week_start() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+
+        // 2026-03-01 is a Sunday, 2026-03-02 a Monday
+        TransactionService::create_transaction(None, &db, command(dining, "2026-03-01", 10.0, false), None).await.unwrap();
+        TransactionService::create_transaction(None, &db, command(dining, "2026-03-02", 20.0, false), None).await.unwrap();
+
+        let monday = TransactionService::weekly_breakdown(&db, "2026-03", WeekStart::Monday).await.unwrap();
+        assert_eq!(monday[0].label, "Mar 1 - Mar 1");
+        assert_eq!(monday[0].expenses, 1000);
+        assert_eq!(monday[1].start, "2026-03-02");
+        assert_eq!(monday[1].expenses, 2000);
+        assert_eq!(monday.len(), 6);
+        assert_eq!(monday.last().unwrap().end, "2026-03-31");
+
+        let sunday = TransactionService::weekly_breakdown(&db, "2026-03", WeekStart::Sunday).await.unwrap();
+        assert_eq!(sunday[0].label, "Mar 1 - Mar 7");
+        assert_eq!(sunday[0].expenses, 3000);
+        assert_eq!(sunday.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_create_transaction_returning_matches_persisted() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let mut cmd = command(dining, "2026-01-10", 12.5, false);
+        cmd.notes = Some("Lunch".to_string());
+
+        let created = TransactionService::create_transaction_returning(None, &db, cmd, None).await.unwrap();
+
+        assert_eq!(created.amount, -1250, "expense sign is derived from the category");
+        assert_eq!(created.notes.as_deref(), Some("Lunch"));
+        assert_eq!(TransactionService::get_transaction(&db, created.id).await.unwrap(), created);
+    }
+
+    #[tokio::test]
+    async fn test_no_roles_context_cannot_create_transaction() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let nobody = UserContext { user_id: 2, roles: Vec::new() };
+
+        let result = TransactionService::create_transaction(Some(&nobody), &db, command(dining, "2026-01-10", 5.0, false), None).await;
+        assert!(matches!(result, Err(TransactionError::Forbidden(_))));
+        let (transactions, _) = TransactionService::get_month_view(&db, "2026-01", TransactionSort::default()).await.unwrap();
+        assert!(transactions.is_empty());
+
+        let denied = RuleService::create_rule(Some(&nobody), &db, "Bistro".to_string(), dining, 0).await;
+        assert!(matches!(denied, Err(TransactionError::Forbidden(_))));
+        assert!(RuleService::list_rules(&db).await.unwrap().is_empty());
+
+        let owner = UserContext::owner();
+        TransactionService::create_transaction(Some(&owner), &db, command(dining, "2026-01-10", 5.0, false), None).await.unwrap();
+        let rule = RuleService::create_rule(Some(&owner), &db, "Bistro".to_string(), dining, 0).await.unwrap();
+
+        assert!(matches!(RuleService::delete_rule(Some(&nobody), &db, rule).await, Err(TransactionError::Forbidden(_))));
+        assert_eq!(RuleService::list_rules(&db).await.unwrap().len(), 1);
+        assert!(matches!(RuleService::apply_rules(Some(&nobody), &db, "2026-01").await, Err(TransactionError::Forbidden(_))));
+
+        let note = MonthNoteService::set(Some(&nobody), &db, "2026-01", "Tight month").await;
+        assert!(matches!(note, Err(CategoryError::Forbidden(_))));
+        assert_eq!(MonthNoteService::get(&db, "2026-01").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_create_transaction_with_new_category_name() {
+        let db = get_test_db().await;
+        let mut cmd = command(0, "2026-01-04", 12.0, false);
+        cmd.new_category = Some(NewCategory { name: "  Pet   Supplies ".to_string(), is_income: false, max_categories: None, palette: Palette::default() });
+
+        let first = TransactionService::create_transaction_returning(None, &db, cmd.clone(), None).await.unwrap();
+
+        let category = CategoryService::get_category(&db, first.category_id).await.unwrap();
+        assert_eq!(category.name, "Pet Supplies");
+        assert!(!category.is_income);
+        assert_eq!(first.amount, -1200);
+
+        // A differently-cased name reuses the category instead of creating another
+        cmd.new_category = Some(NewCategory { name: "pet supplies".to_string(), is_income: true, max_categories: None, palette: Palette::default() });
+        let second = TransactionService::create_transaction_returning(None, &db, cmd.clone(), None).await.unwrap();
+        assert_eq!(second.category_id, first.category_id);
+        assert_eq!(second.amount, -1200);
+
+        let count = CategoryService::list_categories(&db).await.unwrap().len();
+        cmd.new_category = Some(NewCategory { name: "Bonus".to_string(), is_income: false, max_categories: Some(count as u32), palette: Palette::default() });
+        let err = TransactionService::create_transaction(None, &db, cmd, None).await.unwrap_err();
+        assert!(matches!(err, TransactionError::InvalidInput(msg) if msg.contains("Category limit")));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_transaction_does_not_create_new_category() {
+        let db = get_test_db().await;
+        let before = CategoryService::list_categories(&db).await.unwrap().len();
+
+        for (date, amount) in [("2026-13-40", 12.0), ("2026-01-04", 12.345)] {
+            let mut cmd = command(0, date, amount, false);
+            cmd.new_category = Some(NewCategory { name: "Side Gig".to_string(), is_income: true, max_categories: None, palette: Palette::default() });
+            let err = TransactionService::create_transaction_returning(None, &db, cmd, None).await.unwrap_err();
+            assert!(matches!(err, TransactionError::InvalidInput(_)));
+        }
+        assert_eq!(CategoryService::list_categories(&db).await.unwrap().len(), before);
+
+        let mut cmd = command(0, "2026-01-04", 12.0, false);
+        cmd.new_category = Some(NewCategory { name: "Side Gig".to_string(), is_income: true, max_categories: None, palette: Palette::default() });
+        let created = TransactionService::create_transaction_returning(None, &db, cmd, None).await.unwrap();
+        assert_eq!(created.amount, 1200);
+    }
+
+    #[tokio::test]
+    async fn test_clearing_shrinks_uncleared_list() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let first = TransactionService::create_transaction(None, &db, command(dining, "2026-01-05", 20.0, false), None).await.unwrap();
+        let second = TransactionService::create_transaction(None, &db, command(dining, "2026-01-09", 30.0, true), None).await.unwrap();
+
+        let before = TransactionService::reconciliation(&db, "2026-01").await.unwrap();
+        assert_eq!(before.uncleared.len(), 2);
+        assert_eq!((before.cleared_total, before.uncleared_total), (0, -5000));
+
+        let cleared = TransactionService::set_cleared(None, &db, first, true).await.unwrap();
+        assert!(cleared.cleared);
+
+        let after = TransactionService::reconciliation(&db, "2026-01").await.unwrap();
+        assert_eq!(after.uncleared.iter().map(|t| t.id).collect::<Vec<_>>(), vec![second]);
+        assert_eq!((after.cleared_total, after.uncleared_total), (-2000, -3000));
+
+        // Unticking puts it back
+        TransactionService::set_cleared(None, &db, first, false).await.unwrap();
+        assert_eq!(TransactionService::reconciliation(&db, "2026-01").await.unwrap().uncleared.len(), 2);
+
+        assert!(matches!(TransactionService::set_cleared(None, &db, 9999, true).await, Err(TransactionError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_summary_for_selected_categories() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let fuel = CategoryService::create_category(None, &db, "Fuel".to_string(), false, None).await.unwrap();
+        let gym = CategoryService::create_category(None, &db, "Gym".to_string(), false, None).await.unwrap();
+        for (category, amount) in [(dining, 12.0), (dining, 8.0), (fuel, 40.0), (gym, 30.0)] {
+            TransactionService::create_transaction(None, &db, command(category, "2026-01-10", amount, false), None).await.unwrap();
+        }
+        TransactionService::create_transaction(None, &db, command(dining, "2026-02-01", 99.0, false), None).await.unwrap();
+
+        let totals = TransactionService::summary_for_categories(&db, "2026-01", &[fuel, dining]).await.unwrap();
+        let mut expected = vec![(dining, -2000), (fuel, -4000)];
+        expected.sort_unstable();
+        assert_eq!(totals, expected);
+
+        assert!(TransactionService::summary_for_categories(&db, "2026-01", &[]).await.unwrap().is_empty());
+
+        // More ids than fit in one IN list still come back in a single result
+        let many: Vec<i64> = (10_000..11_000).chain([gym]).collect();
+        assert_eq!(TransactionService::summary_for_categories(&db, "2026-01", &many).await.unwrap(), vec![(gym, -3000)]);
+    }
+
+    #[tokio::test]
+    async fn test_sign_audit_detects_and_fixes_mis_signed_rows() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let fine = TransactionService::create_transaction(None, &db, command(dining, "2026-01-05", 20.0, false), None).await.unwrap();
+        let wrong = TransactionService::create_transaction(None, &db, command(dining, "2026-01-06", 30.0, false), None).await.unwrap();
+        sqlx::query("UPDATE transactions SET amount = 3000 WHERE id = $1").bind(wrong).execute(&db.pool).await.unwrap();
+
+        let flagged = TransactionService::audit_sign_consistency(&db).await.unwrap();
+        assert!(flagged.contains(&wrong));
+        assert!(!flagged.contains(&fine));
+
+        let mut events = db.events().subscribe();
+        let fixed = TransactionService::fix_sign_consistency(None, &db).await.unwrap();
+        assert!(fixed.contains(&wrong));
+        let event = events.try_recv().unwrap();
+        assert_eq!((event.month.as_str(), event.kind), ("2026-01", ChangeKind::Updated));
+        assert_eq!(TransactionService::get_transaction(&db, wrong).await.unwrap().amount, -3000);
+        assert!(TransactionService::audit_sign_consistency(&db).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_writes_publish_month_events_after_commit() {
+        let db = get_test_db().await;
+        let dining = CategoryService::create_category(None, &db, "Dining".to_string(), false, None).await.unwrap();
+        let mut events = db.events().subscribe();
+
+        let id = TransactionService::create_transaction(None, &db, command(dining, "2026-01-05", 20.0, false), None).await.unwrap();
+        // Moving it to February changes both months
+        TransactionService::update_transaction(None, &db, id, command(dining, "2026-02-05", 20.0, false), None).await.unwrap();
+        TransactionService::delete_transaction(None, &db, id).await.unwrap();
+        // A failed write publishes nothing
+        assert!(TransactionService::delete_transaction(None, &db, id).await.is_err());
+
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            received.push((event.month, event.kind, event.transaction_id));
+        }
+        assert_eq!(received, vec![
+            ("2026-01".to_string(), ChangeKind::Created, Some(id)),
+            ("2026-02".to_string(), ChangeKind::Updated, Some(id)),
+            ("2026-01".to_string(), ChangeKind::Updated, Some(id)),
+            ("2026-02".to_string(), ChangeKind::Deleted, Some(id)),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_changes_since_returns_only_later_changes() {
+        let db = get_test_db().await;
+        let cat = CategoryService::create_category(None, &db, "Hobbies".to_string(), false, None).await.unwrap();
+
+        let untouched = TransactionService::create_transaction(None, &db, command(cat, "2026-01-10", 10.0, false), None).await.unwrap();
+        let edited = TransactionService::create_transaction(None, &db, command(cat, "2026-01-11", 20.0, false), None).await.unwrap();
+
+        let first = TransactionService::changes_since(&db, 0).await.unwrap();
+        let ids: Vec<i64> = first.transactions.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![untouched, edited]);
+
+        TransactionService::update_transaction(None, &db, edited, command(cat, "2026-01-11", 25.0, false), None).await.unwrap();
+        let added = TransactionService::create_transaction(None, &db, command(cat, "2026-01-12", 30.0, false), None).await.unwrap();
+
+        let second = TransactionService::changes_since(&db, first.cursor).await.unwrap();
+        let ids: Vec<i64> = second.transactions.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![edited, added]);
+        assert!(second.cursor > first.cursor);
+
+        let third = TransactionService::changes_since(&db, second.cursor).await.unwrap();
+        assert!(third.transactions.is_empty());
+        assert_eq!(third.cursor, second.cursor);
+    }
+
+    #[tokio::test]
+    async fn test_changes_since_picks_up_write_uncommitted_during_sync() {
+        let db = get_test_db().await;
+        let cat = CategoryService::create_category(None, &db, "Hobbies".to_string(), false, None).await.unwrap();
+        let baseline = TransactionService::changes_since(&db, 0).await.unwrap().cursor;
+
+        // A second pool on the same file, so the write and the sync use different connections
+        let path = db.pool.connect_options().get_filename().display().to_string();
+        let writer = Database::new(&format!("sqlite:{}", path), None).await.unwrap();
+        let mut uow = writer.begin().await.unwrap();
+        let req = CreateTransactionRequest::new(cat, None, "2026-01-10".to_string(), 10.0, false, None, false).unwrap();
+        let id = TransactionRepository::new(uow.connection()).create(&req).await.unwrap();
+
+        let during = TransactionService::changes_since(&db, baseline).await.unwrap();
+        assert!(during.transactions.is_empty());
+        uow.commit().await.unwrap();
+
+        let after = TransactionService::changes_since(&db, during.cursor).await.unwrap();
+        let ids: Vec<i64> = after.transactions.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![id]);
+    }
+
+    #[tokio::test]
+    async fn test_changes_since_rejects_negative_cursor() {
+        let db = get_test_db().await;
+        let result = TransactionService::changes_since(&db, -1).await;
+        assert!(matches!(result, Err(TransactionError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,30 @@
+-- Category names are unique regardless of case ("Food" vs "food")
+
+-- Existing case duplicates are merged into the lowest id first, or the index below can't be created
+CREATE TEMP TABLE category_merge AS
+SELECT c.id AS old_id, (SELECT MIN(k.id) FROM categories k WHERE lower(k.name) = lower(c.name)) AS new_id
+FROM categories c;
+
+-- Where merged categories both have a budget for a month, the lowest id's budget is kept
+DELETE FROM monthly_budgets WHERE EXISTS (
+    SELECT 1 FROM monthly_budgets o
+    JOIN category_merge mo ON mo.old_id = o.category_id
+    JOIN category_merge mb ON mb.old_id = monthly_budgets.category_id
+    WHERE mo.new_id = mb.new_id AND o.month = monthly_budgets.month AND o.category_id < monthly_budgets.category_id
+);
+
+UPDATE monthly_budgets SET category_id = (SELECT new_id FROM category_merge WHERE old_id = category_id)
+WHERE category_id IN (SELECT old_id FROM category_merge WHERE old_id <> new_id);
+
+UPDATE transactions SET category_id = (SELECT new_id FROM category_merge WHERE old_id = category_id)
+WHERE category_id IN (SELECT old_id FROM category_merge WHERE old_id <> new_id);
+
+-- The kept category stays active if any of its duplicates was
+UPDATE categories SET is_active = 1
+WHERE id IN (SELECT m.new_id FROM category_merge m JOIN categories d ON d.id = m.old_id WHERE d.is_active = 1);
+
+DELETE FROM categories WHERE id IN (SELECT old_id FROM category_merge WHERE old_id <> new_id);
+
+DROP TABLE category_merge;
+
+CREATE UNIQUE INDEX idx_categories_name_nocase ON categories(name COLLATE NOCASE);
@@ -0,0 +1,2 @@
+-- Transactions flagged as excluded (transfers, reimbursed expenses) are listed but not counted
+ALTER TABLE transactions ADD COLUMN excluded BOOLEAN NOT NULL DEFAULT 0;
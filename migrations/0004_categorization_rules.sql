@@ -0,0 +1,12 @@
+-- Bucket for transactions that have not been categorized yet (imports, quick entry)
+INSERT OR IGNORE INTO categories (name, color, is_income) VALUES ('Uncategorized', '#f8f9fa', 0);
+
+-- Auto-categorization rules: notes containing match_text move to category_id.
+-- Lower priority values are tried first.
+CREATE TABLE categorization_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    match_text TEXT NOT NULL,
+    category_id INTEGER NOT NULL,
+    priority INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY (category_id) REFERENCES categories(id) ON DELETE CASCADE
+);
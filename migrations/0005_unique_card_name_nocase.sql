@@ -0,0 +1,20 @@
+-- Card names are stored trimmed and are unique regardless of case ("Visa" vs "visa ")
+
+-- Existing duplicates are merged into the lowest id first, or the index below can't be created
+CREATE TEMP TABLE card_merge AS
+SELECT c.id AS old_id, (SELECT MIN(k.id) FROM cards k WHERE lower(trim(k.name)) = lower(trim(c.name))) AS new_id
+FROM cards c;
+
+UPDATE transactions SET card_id = (SELECT new_id FROM card_merge WHERE old_id = card_id)
+WHERE card_id IN (SELECT old_id FROM card_merge WHERE old_id <> new_id);
+
+-- The kept card stays active if any of its duplicates was
+UPDATE cards SET is_active = 1
+WHERE id IN (SELECT m.new_id FROM card_merge m JOIN cards d ON d.id = m.old_id WHERE d.is_active = 1);
+
+DELETE FROM cards WHERE id IN (SELECT old_id FROM card_merge WHERE old_id <> new_id);
+
+DROP TABLE card_merge;
+
+UPDATE cards SET name = trim(name);
+CREATE UNIQUE INDEX idx_cards_name_nocase ON cards(name COLLATE NOCASE);
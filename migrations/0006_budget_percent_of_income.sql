@@ -0,0 +1,4 @@
+-- Budgets can be a fixed amount or a percentage of the month's actual income.
+-- For 'percent_of_income' budgets, limit_amount is ignored and computed at view time.
+ALTER TABLE monthly_budgets ADD COLUMN mode TEXT NOT NULL DEFAULT 'fixed' CHECK (mode IN ('fixed', 'percent_of_income'));
+ALTER TABLE monthly_budgets ADD COLUMN percent REAL;
@@ -0,0 +1,2 @@
+-- Optional free-form group ("Needs", "Wants") used to subtotal the budget table
+ALTER TABLE categories ADD COLUMN budget_group TEXT;
@@ -0,0 +1,2 @@
+-- Debt-paydown categories may carry a negative monthly limit: a paydown target rather than a cap
+ALTER TABLE categories ADD COLUMN allows_negative_budget BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+-- A free-text memo per month ("vacation month, expect overspend"), shown as a banner on the month view
+CREATE TABLE month_notes (
+    month TEXT PRIMARY KEY NOT NULL, -- YYYY-MM
+    text TEXT NOT NULL
+);
@@ -0,0 +1,71 @@
+-- transaction_date is a plain calendar date. Month grouping compares it as YYYY-MM-DD text, so a
+-- datetime or an unpadded date would silently land in the wrong month; reject them instead.
+-- SQLite cannot add a CHECK to an existing table, so the table is rebuilt. Nothing references
+-- transactions, so it can be dropped with foreign keys on.
+
+-- Older versions accepted unpadded dates ("2026-1-5") and datetimes; repair those first so the copy
+-- below can't trip the new CHECK. Datetimes are cut to their date part, then month and day padded.
+CREATE TEMP TABLE date_fix AS
+SELECT id, d, substr(d, 6, instr(substr(d, 6), '-') - 1) AS m, substr(substr(d, 6), instr(substr(d, 6), '-') + 1) AS dd
+FROM (
+    SELECT id, CASE
+        WHEN instr(trim(transaction_date), ' ') > 0 THEN substr(trim(transaction_date), 1, instr(trim(transaction_date), ' ') - 1)
+        WHEN instr(trim(transaction_date), 'T') > 0 THEN substr(trim(transaction_date), 1, instr(trim(transaction_date), 'T') - 1)
+        ELSE trim(transaction_date)
+    END AS d
+    FROM transactions
+);
+
+UPDATE transactions
+SET transaction_date = (
+    SELECT printf('%s-%02d-%02d', substr(f.d, 1, 4), CAST(f.m AS INTEGER), CAST(f.dd AS INTEGER))
+    FROM date_fix f WHERE f.id = transactions.id
+)
+WHERE id IN (
+    SELECT id FROM date_fix
+    WHERE d GLOB '[0-9][0-9][0-9][0-9]-*'
+      AND (m GLOB '[0-9]' OR m GLOB '[0-9][0-9]')
+      AND (dd GLOB '[0-9]' OR dd GLOB '[0-9][0-9]')
+);
+
+DROP TABLE date_fix;
+
+-- Anything still not a real YYYY-MM-DD date can't be guessed at; stop with a message saying how to find it
+CREATE TEMP TABLE unrepairable_dates (id INTEGER);
+CREATE TEMP TRIGGER reject_unrepairable_dates BEFORE INSERT ON unrepairable_dates
+BEGIN
+    SELECT RAISE(ABORT, 'Some transactions have a transaction_date that is not a YYYY-MM-DD date. Find them with SELECT id, transaction_date FROM transactions WHERE date(transaction_date) IS NOT transaction_date, fix or delete them, then restart.');
+END;
+
+INSERT INTO unrepairable_dates
+SELECT id FROM transactions
+WHERE NOT (transaction_date GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9]' AND date(transaction_date) IS transaction_date);
+
+DROP TRIGGER reject_unrepairable_dates;
+DROP TABLE unrepairable_dates;
+
+CREATE TABLE transactions_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    category_id INTEGER NOT NULL,
+    card_id INTEGER,
+    transaction_date TEXT NOT NULL CHECK (
+        transaction_date GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9]'
+        AND date(transaction_date) IS transaction_date
+    ), -- Format: YYYY-MM-DD, a real calendar date
+    amount INTEGER NOT NULL, -- Cents, Positive=Income, Negative=Expense
+    notes TEXT,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    excluded BOOLEAN NOT NULL DEFAULT 0,
+    FOREIGN KEY (category_id) REFERENCES categories(id) ON DELETE RESTRICT,
+    FOREIGN KEY (card_id) REFERENCES cards(id) ON DELETE SET NULL
+);
+
+INSERT INTO transactions_new (id, category_id, card_id, transaction_date, amount, notes, created_at, excluded)
+SELECT id, category_id, card_id, transaction_date, amount, notes, created_at, excluded FROM transactions;
+
+DROP TABLE transactions;
+ALTER TABLE transactions_new RENAME TO transactions;
+
+CREATE INDEX idx_transactions_date ON transactions(transaction_date);
+CREATE INDEX idx_transactions_category ON transactions(category_id);
+CREATE INDEX idx_transactions_card ON transactions(card_id);
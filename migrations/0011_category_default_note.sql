@@ -0,0 +1,2 @@
+-- Text that pre-fills the note field when the category is picked for a new transaction
+ALTER TABLE categories ADD COLUMN default_note TEXT;
@@ -0,0 +1,3 @@
+-- 50/30/20-style class for the ratio report; 'ignore' leaves the category out of it
+ALTER TABLE categories ADD COLUMN classification TEXT NOT NULL DEFAULT 'ignore'
+    CHECK (classification IN ('need', 'want', 'saving', 'ignore'));
@@ -0,0 +1,2 @@
+-- Transactions ticked off against a bank statement while reconciling
+ALTER TABLE transactions ADD COLUMN cleared BOOLEAN NOT NULL DEFAULT 0;
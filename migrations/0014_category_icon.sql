@@ -0,0 +1,2 @@
+-- Short emoji shown next to the category name
+ALTER TABLE categories ADD COLUMN icon TEXT;
@@ -0,0 +1,30 @@
+-- Incremental sync cursor. Every insert or update of a transaction takes the next value of one
+-- counter inside the writing transaction. SQLite has a single writer, so the values commit in
+-- order: a reader that sees the counter at N has seen every change numbered N or below, and a
+-- change still uncommitted when it looked will get a number above N.
+CREATE TABLE sync_counter (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    value INTEGER NOT NULL
+);
+
+ALTER TABLE transactions ADD COLUMN sync_seq INTEGER NOT NULL DEFAULT 0;
+
+UPDATE transactions SET sync_seq = id;
+
+INSERT INTO sync_counter (id, value) VALUES (1, (SELECT COALESCE(MAX(sync_seq), 0) FROM transactions));
+
+CREATE INDEX idx_transactions_sync_seq ON transactions(sync_seq);
+
+CREATE TRIGGER transactions_sync_insert AFTER INSERT ON transactions
+BEGIN
+    UPDATE sync_counter SET value = value + 1 WHERE id = 1;
+    UPDATE transactions SET sync_seq = (SELECT value FROM sync_counter WHERE id = 1) WHERE id = NEW.id;
+END;
+
+-- Skipped for the trigger's own write of sync_seq, so it doesn't re-fire
+CREATE TRIGGER transactions_sync_update AFTER UPDATE ON transactions
+WHEN NEW.sync_seq IS OLD.sync_seq
+BEGIN
+    UPDATE sync_counter SET value = value + 1 WHERE id = 1;
+    UPDATE transactions SET sync_seq = (SELECT value FROM sync_counter WHERE id = 1) WHERE id = NEW.id;
+END;